@@ -4,6 +4,9 @@ use qsniffer::run_proxy;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // 日志通过 tracing 输出
+    tracing_subscriber::fmt::init();
+
     // 示例验证器函数
     let client_validator = Arc::new(|data: &bytes::Bytes, version| {
         println!("客户端数据包验证 - 协议版本: {}, 数据长度: {}",  version, data.len());
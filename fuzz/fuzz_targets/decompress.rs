@@ -0,0 +1,18 @@
+//! 任意字节作为压缩帧 (未压缩长度 + zlib 数据) 解压
+#![no_main]
+
+use bytes::Bytes;
+use libfuzzer_sys::fuzz_target;
+use qexed_tcp_connect::{decompress_packet, read_varint};
+
+fuzz_target!(|data: &[u8]| {
+    let declared = read_varint(&mut &data[..]);
+    if let Ok(packet) = decompress_packet(Bytes::copy_from_slice(data)) {
+        // 解压结果的长度与声明的一致,声明为 0 时原样返回
+        match declared {
+            Ok(0) => assert!(packet.len() < data.len()),
+            Ok(size) => assert_eq!(packet.len(), size as usize),
+            Err(_) => unreachable!("decompressed without a valid length"),
+        }
+    }
+});
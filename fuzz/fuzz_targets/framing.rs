@@ -0,0 +1,33 @@
+//! 读缓冲区中的任意字节按帧拆分,第一个字节决定是否启用压缩
+#![no_main]
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use qexed_tcp_connect::{try_parse_packet, MAX_PACKET_SIZE};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&flags, data)) = data.split_first() else {
+        return;
+    };
+    let compressed = flags & 1 == 1;
+    let mut buffer = BytesMut::from(data);
+    loop {
+        let remaining = buffer.len();
+        match try_parse_packet(&mut buffer, compressed) {
+            Ok(Some(frame)) => {
+                // 每取出一帧至少消耗长度字段
+                assert!(buffer.len() < remaining);
+                if !compressed {
+                    assert!(frame.len() < remaining);
+                    assert!(frame.len() <= MAX_PACKET_SIZE);
+                }
+            }
+            // 数据不完整时缓冲区保持不变,等待更多数据
+            Ok(None) => {
+                assert_eq!(buffer.len(), remaining);
+                break;
+            }
+            Err(_) => break,
+        }
+    }
+});
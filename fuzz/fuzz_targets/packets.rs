@@ -0,0 +1,43 @@
+//! 任意帧按每个状态和流向的包池解析
+//!
+//! 输入: 包池编号 (1 字节) + 协议版本 (i32,大端) + 帧 (包ID + 数据)
+//!
+//! 格式错误的数据应当以解码错误拒绝,任何 panic 都算崩溃
+#![no_main]
+
+use bytes::Bytes;
+use libfuzzer_sys::fuzz_target;
+use qexed_tcp_connect::{net_types::packet::Packet, packet::decode::PacketReader};
+use qsniffer::packets::{client, server};
+
+type IdToPacket = fn(u32, i32) -> Box<dyn Packet>;
+
+const POOLS: &[IdToPacket] = &[
+    client::handshaking::pool::id_to_packet,
+    client::status::pool::id_to_packet,
+    client::login::pool::id_to_packet,
+    server::status::pool::id_to_packet,
+    server::login::pool::id_to_packet,
+];
+
+fn decode(id_to_packet: IdToPacket, protocol_version: i32, frame: Bytes) {
+    let mut buf = frame;
+    let mut reader = PacketReader::new(Box::new(&mut buf)).with_protocol_version(protocol_version);
+    reader.track_fields();
+    let id = reader.varint().0 as u32;
+    let mut packet = id_to_packet(id, protocol_version);
+    packet.deserialize(&mut reader);
+    reader.take_fields();
+    // 数据包转储同样会格式化解析结果,出错时也会输出
+    let _ = format!("{:?}", packet);
+    let _ = reader.finish();
+}
+
+fuzz_target!(|data: &[u8]| {
+    let [pool, a, b, c, d, frame @ ..] = data else {
+        return;
+    };
+    let id_to_packet = POOLS[*pool as usize % POOLS.len()];
+    let protocol_version = i32::from_be_bytes([*a, *b, *c, *d]);
+    decode(id_to_packet, protocol_version, Bytes::copy_from_slice(frame));
+});
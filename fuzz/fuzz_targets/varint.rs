@@ -0,0 +1,45 @@
+//! 帧层的 `read_varint` 与数据包读取器的 `PacketReader::varint` 结果一致
+#![no_main]
+
+use std::io::ErrorKind;
+
+use bytes::{Buf, BytesMut};
+use libfuzzer_sys::fuzz_target;
+use qexed_tcp_connect::{
+    net_types::var_int::VarInt,
+    packet::{decode::PacketReader, encode::PacketWriter},
+    read_varint,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let mut buf = data;
+    match read_varint(&mut buf) {
+        Ok(value) => {
+            let length = data.len() - buf.len();
+            assert!((1..=5).contains(&length));
+
+            let mut view = data;
+            let mut reader = PacketReader::new(Box::new(&mut view));
+            assert_eq!(reader.varint(), VarInt(value));
+            drop(reader);
+            assert_eq!(view.remaining(), buf.remaining());
+
+            // 重新编码后不长于原数据 (原数据可能带有多余的 0x80 前缀)
+            let mut encoded = BytesMut::new();
+            PacketWriter::new(&mut encoded).varint(&VarInt(value));
+            assert!(encoded.len() <= length);
+            assert_eq!(read_varint(&mut &encoded[..]).unwrap(), value);
+        }
+        Err(error) => {
+            match error.kind() {
+                ErrorKind::UnexpectedEof => assert!(data.len() < 5),
+                kind => assert_eq!(kind, ErrorKind::InvalidData),
+            }
+            // 读取器同样记录为解码错误
+            let mut view = data;
+            let mut reader = PacketReader::new(Box::new(&mut view));
+            assert_eq!(reader.varint(), VarInt(0));
+            assert!(reader.failed());
+        }
+    }
+});
@@ -0,0 +1,585 @@
+// src/field.rs
+use std::collections::HashSet;
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Attribute, DeriveInput, Expr, Fields, Ident, LitStr, Member, Type};
+
+use crate::packet::option_tokens;
+
+/// 字段属性名,解析后会从生成的结构体中移除
+const FIELD_ATTRIBUTES: &[&str] = &[
+    "varint",
+    "varlong",
+    "prefixed",
+    "fixed",
+    "rest",
+    "json",
+    "nbt",
+    "skip",
+    "optional_if",
+    "since",
+    "until",
+];
+
+/// 字段在网络上的编码方式
+enum Codec {
+    /// 使用字段类型的 Subdata 实现
+    Default,
+    /// `#[varint]`: 整数按 VarInt 编码
+    VarInt,
+    /// `#[varlong]`: 整数按 VarLong 编码
+    VarLong,
+    /// `#[prefixed(u16)]`: 指定长度前缀类型的数组
+    Prefixed(Type),
+    /// `#[fixed(N)]`: 固定元素个数,没有长度前缀
+    Fixed(Expr),
+    /// `#[rest]`: 一直读到数据结束,没有长度前缀
+    Rest,
+    /// `#[json]`: 通过 serde 编码为 JSON 文本
+    Json,
+    /// `#[nbt]`: 通过 NbtConvert 编码为网络 NBT
+    Nbt,
+}
+
+/// 一个字段的编码配置
+pub struct FieldSpec {
+    member: Member,
+    ty: Type,
+    /// 枚举变体中绑定该字段的变量名
+    pub binding: Ident,
+    name: String,
+    codec: Codec,
+    pub skip: bool,
+    optional_if: Option<Expr>,
+    /// `#[since(N)]` / `#[until(N)]`: 字段存在的协议版本范围
+    since: Option<i32>,
+    until: Option<i32>,
+}
+
+/// 生成的代码如何访问字段
+#[derive(Clone, Copy)]
+pub enum Access {
+    /// 结构体: `self.field`
+    SelfField,
+    /// 枚举变体: 模式绑定的同名变量 (序列化时为引用,反序列化时为局部值)
+    Binding,
+}
+
+/// 字段序列化/反序列化生成的代码
+pub struct FieldCodecs {
+    pub serialize: Vec<TokenStream>,
+    pub deserialize: Vec<TokenStream>,
+    /// 每个字段的 `FieldDescriptor`,枚举没有
+    pub descriptors: Vec<TokenStream>,
+    /// 生成任意值的语句 (`ArbitraryData`),结构体和枚举的最后一个是构造 `Self` 的表达式
+    pub arbitrary: Vec<TokenStream>,
+}
+
+fn is_field_attribute(attr: &Attribute) -> bool {
+    FIELD_ATTRIBUTES.iter().any(|name| attr.path().is_ident(name))
+}
+
+/// 两个字符串的编辑距离
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                previous.min(current).min(row[j]) + 1
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// 与字段属性名拼写相近的未知属性报错,其余属性 (doc、serde 等) 原样保留
+fn check_unknown_attribute(attr: &Attribute) -> syn::Result<()> {
+    let Some(ident) = attr.path().get_ident() else {
+        return Ok(());
+    };
+    let name = ident.to_string();
+    let limit = if name.len() <= 4 { 1 } else { 2 };
+    match FIELD_ATTRIBUTES
+        .iter()
+        .find(|known| edit_distance(&name, known) <= limit)
+    {
+        Some(known) => Err(syn::Error::new(
+            ident.span(),
+            format!("unknown field attribute `{}`, did you mean `{}`?", name, known),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// 解析单个字段上的属性
+fn parse_field(member: Member, ty: Type, attrs: &[Attribute]) -> syn::Result<FieldSpec> {
+    let (name, binding) = match &member {
+        Member::Named(ident) => (ident.to_string(), ident.clone()),
+        Member::Unnamed(index) => (
+            index.index.to_string(),
+            format_ident!("__field{}", index.index, span = index.span),
+        ),
+    };
+    let mut codec = None;
+    let mut skip = None;
+    let mut optional_if = None;
+    let mut since = None;
+    let mut until = None;
+
+    for attr in attrs.iter().filter(|attr| !is_field_attribute(attr)) {
+        check_unknown_attribute(attr)?;
+    }
+    for attr in attrs.iter().filter(|attr| is_field_attribute(attr)) {
+        let path = attr.path();
+        let parsed = if path.is_ident("skip") {
+            attr.meta.require_path_only()?;
+            skip = Some(attr.span());
+            continue;
+        } else if path.is_ident("optional_if") {
+            if optional_if.is_some() {
+                return Err(syn::Error::new(attr.span(), "duplicate `optional_if` attribute"));
+            }
+            let lit: LitStr = match &attr.meta.require_name_value()?.value {
+                Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) => lit.clone(),
+                other => {
+                    return Err(syn::Error::new(
+                        other.span(),
+                        "expected a string literal: `#[optional_if = \"expr\"]`",
+                    ));
+                }
+            };
+            optional_if = Some(lit.parse::<Expr>()?);
+            continue;
+        } else if path.is_ident("since") || path.is_ident("until") {
+            let version = attr.parse_args::<syn::LitInt>()?.base10_parse::<i32>()?;
+            let slot = if path.is_ident("since") { &mut since } else { &mut until };
+            if slot.replace(version).is_some() {
+                return Err(syn::Error::new(attr.span(), "duplicate version attribute"));
+            }
+            continue;
+        } else if path.is_ident("varint") {
+            attr.meta.require_path_only()?;
+            Codec::VarInt
+        } else if path.is_ident("varlong") {
+            attr.meta.require_path_only()?;
+            Codec::VarLong
+        } else if path.is_ident("rest") {
+            attr.meta.require_path_only()?;
+            Codec::Rest
+        } else if path.is_ident("json") {
+            attr.meta.require_path_only()?;
+            Codec::Json
+        } else if path.is_ident("nbt") {
+            attr.meta.require_path_only()?;
+            Codec::Nbt
+        } else if path.is_ident("prefixed") {
+            let prefix: Type = attr.parse_args()?;
+            // `varint` 作为 VarInt 前缀的简写
+            if let Type::Path(type_path) = &prefix
+                && type_path.path.is_ident("varint")
+            {
+                Codec::Prefixed(syn::parse_quote_spanned! {prefix.span()=>
+                    qexed_tcp_connect::net_types::var_int::VarInt
+                })
+            } else {
+                Codec::Prefixed(prefix)
+            }
+        } else {
+            Codec::Fixed(attr.parse_args::<Expr>()?)
+        };
+        if codec.is_some() {
+            return Err(syn::Error::new(
+                attr.span(),
+                "a field can only have one encoding attribute",
+            ));
+        }
+        codec = Some(parsed);
+    }
+
+    if let (Some(since), Some(until)) = (since, until)
+        && since > until
+    {
+        return Err(syn::Error::new(
+            member.span(),
+            "`since` must not be greater than `until`",
+        ));
+    }
+
+    if let Some(span) = skip
+        && (codec.is_some() || optional_if.is_some() || since.is_some() || until.is_some())
+    {
+        return Err(syn::Error::new(
+            span,
+            "`#[skip]` cannot be combined with other field attributes",
+        ));
+    }
+
+    Ok(FieldSpec {
+        member,
+        ty,
+        binding,
+        name,
+        codec: codec.unwrap_or(Codec::Default),
+        skip: skip.is_some(),
+        optional_if,
+        since,
+        until,
+    })
+}
+
+/// 写入 `value` (类型为 `&T`) 的代码
+fn write_value(codec: &Codec, ty: &Type, value: &TokenStream) -> TokenStream {
+    match codec {
+        // 字段类型没有实现 Subdata 时错误指向字段类型
+        Codec::Default => {
+            let value = respan(value.clone(), ty.span());
+            quote_spanned! {ty.span()=> w.serialize(#value); }
+        }
+        Codec::VarInt => quote! {
+            w.varint(&qexed_tcp_connect::net_types::var_int::VarInt(*#value as i32));
+        },
+        Codec::VarLong => quote! {
+            w.varlong(&qexed_tcp_connect::net_types::var_long::VarLong(*#value as i64));
+        },
+        Codec::Prefixed(prefix) => quote! { w.prefixed_vec::<#prefix, _>(#value); },
+        Codec::Fixed(len) => quote! { w.fixed_vec(#value, #len); },
+        Codec::Rest => quote! { w.rest_vec(#value); },
+        Codec::Json => quote! { w.json_as(#value); },
+        Codec::Nbt => quote! { w.nbt_as(#value); },
+    }
+}
+
+/// 把 token 的位置替换为 span,让编译错误指向字段而不是宏调用处
+fn respan(tokens: TokenStream, span: proc_macro2::Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            if let TokenTree::Group(group) = &token {
+                let mut respanned = proc_macro2::Group::new(group.delimiter(), respan(group.stream(), span));
+                respanned.set_span(span);
+                return TokenTree::Group(respanned);
+            }
+            token.set_span(span);
+            token
+        })
+        .collect()
+}
+
+/// 读取一个值的表达式
+fn read_value(codec: &Codec, ty: &Type) -> TokenStream {
+    match codec {
+        Codec::Default => quote_spanned! {ty.span()=> r.deserialize() },
+        Codec::VarInt => quote! { r.varint().0 as _ },
+        Codec::VarLong => quote! { r.varlong().0 as _ },
+        Codec::Prefixed(prefix) => quote! { r.prefixed_vec::<#prefix, _>() },
+        Codec::Fixed(len) => quote! { r.fixed_vec(#len) },
+        Codec::Rest => quote! { r.rest_vec() },
+        Codec::Json => quote! { r.json_as() },
+        Codec::Nbt => quote! { r.nbt_as() },
+    }
+}
+
+/// 生成一个任意值的表达式,满足编码的限制
+fn arbitrary_value(codec: &Codec) -> TokenStream {
+    let support = quote! { qexed_tcp_connect::net_types::arbitrary_data };
+    match codec {
+        Codec::Default | Codec::Json => quote! { #support::ArbitraryData::arbitrary_data(u)? },
+        // 读回时经过 i32 / i64 转换,从这两个类型生成才能往返
+        Codec::VarInt => quote! { u.arbitrary::<i32>()? as _ },
+        Codec::VarLong => quote! { u.arbitrary::<i64>()? as _ },
+        Codec::Prefixed(prefix) => quote! {
+            #support::arbitrary_vec(u, <#prefix as qexed_tcp_connect::net_types::array::LengthPrefix>::MAX)?
+        },
+        Codec::Fixed(len) => quote! { #support::arbitrary_fixed_vec(u, #len)? },
+        Codec::Rest => quote! { #support::arbitrary_vec(u, usize::MAX)? },
+        Codec::Nbt => quote! { #support::ArbitraryNbt::arbitrary_nbt(u)? },
+    }
+}
+
+/// 生成字段的任意值,绑定为同名局部变量
+///
+/// `optional_if` 的条件直接使用前面已生成的字段;当前版本 (最新版本) 没有的字段和
+/// `#[skip]` 字段为默认值,与反序列化的结果一致
+fn arbitrary_field(spec: &FieldSpec) -> TokenStream {
+    let binding = &spec.binding;
+    let ty = &spec.ty;
+    if spec.skip {
+        return quote! { let #binding: #ty = Default::default(); };
+    }
+    let mut value = arbitrary_value(&spec.codec);
+    if let Some(expr) = &spec.optional_if {
+        value = quote! {
+            if #expr {
+                Some(#value)
+            } else {
+                None
+            }
+        };
+    }
+    if spec.since.is_some() || spec.until.is_some() {
+        let since = option_tokens(spec.since);
+        let until = option_tokens(spec.until);
+        value = quote! {
+            if qexed_tcp_connect::packet::version_in_range(qexed_tcp_connect::packet::UNKNOWN_PROTOCOL_VERSION, #since, #until) {
+                #value
+            } else {
+                Default::default()
+            }
+        };
+    }
+    quote! { let #binding: #ty = #value; }
+}
+
+/// 用字段的同名局部变量构造结构体或枚举变体
+pub fn construct(path: TokenStream, fields: &Fields, specs: &[FieldSpec]) -> TokenStream {
+    let bindings = specs.iter().map(|spec| &spec.binding);
+    match fields {
+        Fields::Named(_) => quote! { #path { #(#bindings),* } },
+        Fields::Unnamed(_) => quote! { #path(#(#bindings),*) },
+        Fields::Unit => quote! { #path },
+    }
+}
+
+/// 去掉 token 之间的空格,用于生成描述字符串
+fn compact(tokens: TokenStream) -> String {
+    tokens.to_string().replace(' ', "")
+}
+
+/// 生成字段的 `FieldDescriptor`
+fn descriptor(spec: &FieldSpec) -> TokenStream {
+    let name = &spec.name;
+    let ty = &spec.ty;
+    let rust_type = compact(quote! { #ty });
+    let encoding = if spec.skip {
+        quote! { Skip }
+    } else {
+        match &spec.codec {
+            Codec::Default => quote! { Default },
+            Codec::VarInt => quote! { VarInt },
+            Codec::VarLong => quote! { VarLong },
+            Codec::Prefixed(prefix) => {
+                let prefix = compact(quote! { #prefix });
+                quote! { Prefixed(#prefix) }
+            }
+            Codec::Fixed(len) => {
+                let len = compact(quote! { #len });
+                quote! { Fixed(#len) }
+            }
+            Codec::Rest => quote! { Rest },
+            Codec::Json => quote! { Json },
+            Codec::Nbt => quote! { Nbt },
+        }
+    };
+    let optional_if = match &spec.optional_if {
+        Some(expr) => {
+            let expr = quote! { #expr }.to_string();
+            quote! { Some(#expr) }
+        }
+        None => quote! { None },
+    };
+    let since = option_tokens(spec.since);
+    let until = option_tokens(spec.until);
+    quote! {
+        qexed_tcp_connect::net_types::packet::FieldDescriptor {
+            name: #name,
+            rust_type: #rust_type,
+            encoding: qexed_tcp_connect::net_types::packet::FieldEncoding::#encoding,
+            optional_if: #optional_if,
+            since: #since,
+            until: #until,
+        }
+    }
+}
+
+/// 收集表达式中出现的标识符
+fn collect_idents(tokens: TokenStream, idents: &mut HashSet<String>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                idents.insert(ident.to_string());
+            }
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            _ => {}
+        }
+    }
+}
+
+/// 生成 `optional_if` 的条件表达式,把条件中用到的前面字段绑定为同名局部变量
+fn condition(expr: &Expr, earlier: &[&FieldSpec], access: Access) -> TokenStream {
+    let mut idents = HashSet::new();
+    collect_idents(quote! { #expr }, &mut idents);
+    let bindings = earlier
+        .iter()
+        .filter(|spec| matches!(spec.member, Member::Named(_)))
+        .filter(|spec| idents.contains(&spec.binding.to_string()))
+        .map(|spec| {
+            let binding = &spec.binding;
+            let place = match access {
+                Access::SelfField => {
+                    let member = &spec.member;
+                    quote! { self.#member }
+                }
+                Access::Binding => quote! { (*#binding) },
+            };
+            quote! {
+                #[allow(clippy::clone_on_copy)]
+                let #binding = #place.clone();
+            }
+        });
+    quote! {
+        {
+            #(#bindings)*
+            #expr
+        }
+    }
+}
+
+/// 解析字段属性并从字段上移除
+pub fn parse_fields(fields: &mut Fields) -> syn::Result<Vec<FieldSpec>> {
+    let mut specs = Vec::new();
+    for (index, field) in fields.iter_mut().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(syn::Index {
+                index: index as u32,
+                span: field.ty.span(),
+            }),
+        };
+        specs.push(parse_field(member, field.ty.clone(), &field.attrs)?);
+        field.attrs.retain(|attr| !is_field_attribute(attr));
+    }
+
+    // `#[rest]` 会读完剩余数据,后面不能再有参与编码的字段
+    let wire: Vec<&FieldSpec> = specs.iter().filter(|spec| !spec.skip).collect();
+    if let Some(position) = wire.iter().position(|spec| matches!(spec.codec, Codec::Rest))
+        && let Some(next) = wire.get(position + 1)
+    {
+        return Err(syn::Error::new(
+            next.member.span(),
+            "no field can follow a `#[rest]` field",
+        ));
+    }
+    Ok(specs)
+}
+
+/// 生成每个字段的读写代码
+pub fn generate_codecs(specs: &[FieldSpec], access: Access) -> FieldCodecs {
+    let wire: Vec<&FieldSpec> = specs.iter().filter(|spec| !spec.skip).collect();
+    let mut codecs = FieldCodecs {
+        serialize: Vec::new(),
+        deserialize: Vec::new(),
+        descriptors: specs.iter().map(descriptor).collect(),
+        arbitrary: specs.iter().map(arbitrary_field).collect(),
+    };
+    for (index, spec) in wire.iter().enumerate() {
+        let member = &spec.member;
+        let binding = &spec.binding;
+        let name = &spec.name;
+        let read = read_value(&spec.codec, &spec.ty);
+        let value = match access {
+            Access::SelfField => quote! { &self.#member },
+            Access::Binding => quote! { #binding },
+        };
+        let (write, read) = match &spec.optional_if {
+            None => (
+                write_value(&spec.codec, &spec.ty, &value),
+                quote! {
+                    {
+                        r.field(#name);
+                        #read
+                    }
+                },
+            ),
+            Some(expr) => {
+                let write_cond = condition(expr, &wire[..index], access);
+                // 反序列化时前面的字段已经读出: 结构体在 self 上,枚举变体是局部变量
+                let read_cond = match access {
+                    Access::SelfField => write_cond.clone(),
+                    Access::Binding => quote! { #expr },
+                };
+                let write = write_value(&spec.codec, &spec.ty, &quote! { value });
+                // 条件成立但值为 None 时不写入,读取方会得到解码错误
+                (
+                    quote_spanned! {expr.span()=>
+                        if #write_cond {
+                            if let Some(value) = (#value).as_ref() {
+                                #write
+                            }
+                        }
+                    },
+                    quote_spanned! {expr.span()=>
+                        if #read_cond {
+                            r.field(#name);
+                            Some(#read)
+                        } else {
+                            None
+                        }
+                    },
+                )
+            }
+        };
+        if spec.since.is_none() && spec.until.is_none() {
+            codecs.serialize.push(write);
+            codecs.deserialize.push(match access {
+                Access::SelfField => quote! { self.#member = #read; },
+                Access::Binding => quote! { let #binding = #read; },
+            });
+            continue;
+        }
+        // 当前协议版本没有该字段时不读写,反序列化时保持默认值
+        let since = option_tokens(spec.since);
+        let until = option_tokens(spec.until);
+        codecs.serialize.push(quote! {
+            if w.supports(#since, #until) {
+                #write
+            }
+        });
+        codecs.deserialize.push(match access {
+            Access::SelfField => quote! {
+                if r.supports(#since, #until) {
+                    self.#member = #read;
+                }
+            },
+            Access::Binding => quote! {
+                let #binding = if r.supports(#since, #until) {
+                    #read
+                } else {
+                    Default::default()
+                };
+            },
+        });
+    }
+    codecs
+}
+
+/// 解析结构体字段属性,生成每个字段的读写代码,并从结构体上移除这些属性
+pub fn field_codecs(input: &mut DeriveInput) -> syn::Result<FieldCodecs> {
+    match &mut input.data {
+        syn::Data::Struct(data_struct) => {
+            let specs = parse_fields(&mut data_struct.fields)?;
+            let mut codecs = generate_codecs(&specs, Access::SelfField);
+            codecs.arbitrary.push(construct(quote! { Self }, &data_struct.fields, &specs));
+            Ok(codecs)
+        }
+        syn::Data::Enum(data_enum) => Err(syn::Error::new(
+            data_enum.enum_token.span,
+            "enums are only supported by `#[substruct]`",
+        )),
+        syn::Data::Union(data_union) => Err(syn::Error::new(
+            data_union.union_token.span,
+            "unions are not supported",
+        )),
+    }
+}
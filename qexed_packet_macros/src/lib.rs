@@ -4,44 +4,57 @@ use proc_macro::TokenStream;
 use syn::parse_macro_input;
 
 // 声明 packet 模块
+mod field;
 mod packet;
-use packet::{PacketId, PacketConfig, implement_packet};
+mod variant;
+use packet::{PacketArgs, PacketConfig, implement_packet};
 
 /// 主属性宏
 #[proc_macro_attribute]
 pub fn packet(args: TokenStream, input: TokenStream) -> TokenStream {
-    // 解析包ID参数
-    let packet_id = parse_macro_input!(args as PacketId);
+    // 解析包ID和版本范围参数
+    let args = parse_macro_input!(args as PacketArgs);
     
     // 解析输入结构体
     let input = parse_macro_input!(input as syn::DeriveInput);
     
     // 实现Packet trait（不再包含Default实现）
-    match implement_packet(packet_id.value, &input) {
+    match implement_packet(&args, &input) {
         Ok(tokens) => tokens.into(),
         Err(e) => e.to_compile_error().into(),
     }
 }
 
 /// 函数式过程宏 - 生成id到packet的映射函数，支持自定义NullPacket类型
+///
+/// `id_to_packet!([a::A, b::B], NullPacket)` 按各数据包 `#[packet(id = ..)]` 声明的ID生成
+/// `ID_*` 常量、`id_to_packet(id, protocol_version)` 和 `packet_name(id, protocol_version)`,
+/// 同一协议版本下ID重复时编译失败。
+/// 末尾加上 `enum ClientLoginPacket` 时还会生成每个数据包一个变体的枚举
 #[proc_macro]
 pub fn id_to_packet(input: TokenStream) -> TokenStream {
     let packet_config = parse_macro_input!(input as PacketConfig);
     
-    let expanded = packet::generate_function_with_consts(&packet_config.packets, &packet_config.null_packet);
+    let expanded = packet::generate_function_with_consts(&packet_config);
     
     TokenStream::from(expanded)
 }
 // 在 src/lib.rs 中添加
 
 /// Subdata trait 实现宏
+///
+/// 用于结构体 (具名、元组、单元) 和枚举;枚举的标签默认是 VarInt,
+/// 可以用 `#[substruct(tag = u8)]` / `#[substruct(tag = identifier)]` 指定
 #[proc_macro_attribute]
-pub fn substruct(_args: TokenStream, input: TokenStream) -> TokenStream {
-    // 解析输入结构体
+pub fn substruct(args: TokenStream, input: TokenStream) -> TokenStream {
+    // 解析参数 (枚举的标签类型)
+    let args = parse_macro_input!(args as variant::SubstructArgs);
+    
+    // 解析输入结构体或枚举
     let input = parse_macro_input!(input as syn::DeriveInput);
     
     // 实现Subdata trait
-    match packet::implement_subdata(&input) {
+    match packet::implement_subdata(&args, &input) {
         Ok(tokens) => tokens.into(),
         Err(e) => e.to_compile_error().into(),
     }
@@ -52,13 +65,16 @@ pub fn substruct(_args: TokenStream, input: TokenStream) -> TokenStream {
 pub fn id_to_packet_with_consts(input: TokenStream) -> TokenStream {
     let packet_config = parse_macro_input!(input as PacketConfig);
     
-    let expanded = packet::generate_function_with_consts(&packet_config.packets, &packet_config.null_packet);
+    let expanded = packet::generate_function_with_consts(&packet_config);
     
     TokenStream::from(expanded)
 }
 // 在 src/lib.rs 顶部添加
 mod packet_match;
 /// 包匹配过程宏
+///
+/// `case! ID_Handshake => Handshake as p { .. }` 使用 `base::pool::ID_Handshake`,
+/// 省略ID写成 `case! Handshake as p { .. }` 时使用 `Handshake` 的 `PacketMeta::ID`
 #[proc_macro]
 pub fn packet_match(input: TokenStream) -> TokenStream {
     packet_match::parse_packet_match(input)
@@ -1,35 +1,109 @@
 // src/packet.rs
-use proc_macro2;
-use quote::{quote};
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
 use syn::{DeriveInput, Ident};
 use syn::parse::{Parse, ParseStream};
 
-/// 解析包ID参数
-pub struct PacketId {
-    pub value: u32,
+use crate::field::{field_codecs, FieldCodecs};
+use crate::variant::{enum_codecs, SubstructArgs, TagKind};
+
+/// 解析 `#[packet(...)]` 参数
+pub struct PacketArgs {
+    pub id: u32,
+    /// 数据包出现的第一个协议版本
+    pub since: Option<i32>,
+    /// 数据包存在的最后一个协议版本
+    pub until: Option<i32>,
+    /// `ConnectionState` 的变体名
+    pub state: Option<Ident>,
+    /// `PacketDirection` 的变体名
+    pub direction: Option<Ident>,
 }
 
-impl Parse for PacketId {
+impl Parse for PacketArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        // 解析 id = 0x00 格式
-        let ident: syn::Ident = input.parse()?;
-        if ident != "id" {
-            return Err(syn::Error::new(ident.span(), "expected `id`"));
+        // 解析 id = 0x00, since = 764, until = 766, state = login, direction = clientbound,
+        // 参数顺序不限,id 必须提供
+        let mut id = None;
+        let mut args = PacketArgs { id: 0, since: None, until: None, state: None, direction: None };
+        while !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            input.parse::<syn::Token![=]>()?;
+            let duplicate = match key.to_string().as_str() {
+                "id" => {
+                    let lit: syn::LitInt = input.parse()?;
+                    id.replace(lit.base10_parse::<u32>()?).is_some()
+                }
+                "since" | "until" => {
+                    let version = input.parse::<syn::LitInt>()?.base10_parse::<i32>()?;
+                    let slot = if key == "since" { &mut args.since } else { &mut args.until };
+                    slot.replace(version).is_some()
+                }
+                "state" => {
+                    let value: Ident = input.parse()?;
+                    let variant = match value.to_string().as_str() {
+                        "handshaking" => "Handshaking",
+                        "status" => "Status",
+                        "login" => "Login",
+                        "configuration" => "Configuration",
+                        "play" => "Play",
+                        _ => {
+                            return Err(syn::Error::new(
+                                value.span(),
+                                "expected `handshaking`, `status`, `login`, `configuration` or `play`",
+                            ));
+                        }
+                    };
+                    args.state.replace(Ident::new(variant, value.span())).is_some()
+                }
+                "direction" => {
+                    let value: Ident = input.parse()?;
+                    let variant = match value.to_string().as_str() {
+                        "serverbound" => "Serverbound",
+                        "clientbound" => "Clientbound",
+                        _ => {
+                            return Err(syn::Error::new(
+                                value.span(),
+                                "expected `serverbound` or `clientbound`",
+                            ));
+                        }
+                    };
+                    args.direction.replace(Ident::new(variant, value.span())).is_some()
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "expected `id`, `since`, `until`, `state` or `direction`",
+                    ));
+                }
+            };
+            if duplicate {
+                return Err(syn::Error::new(key.span(), format!("duplicate `{}`", key)));
+            }
+            if let (Some(since), Some(until)) = (args.since, args.until)
+                && since > until
+            {
+                return Err(syn::Error::new(key.span(), "`since` must not be greater than `until`"));
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<syn::Token![,]>()?;
         }
         
-        input.parse::<syn::Token![=]>()?;
-        
-        let lit: syn::LitInt = input.parse()?;
-        let value = lit.base10_parse::<u32>()?;
-        
-        Ok(PacketId { value })
+        args.id = id.ok_or_else(|| {
+            syn::Error::new(proc_macro2::Span::call_site(), "missing packet ID: `#[packet(id = 0x00)]`")
+        })?;
+        Ok(args)
     }
 }
 
 /// 解析包配置结构，包括包列表和NullPacket类型
 pub struct PacketConfig {
-    pub packets: Vec<Ident>,
+    pub packets: Vec<syn::Path>,
     pub null_packet: Ident,
+    /// 可选的 `enum Name`,生成包含该状态所有数据包的枚举
+    pub packet_enum: Option<Ident>,
 }
 
 impl Parse for PacketConfig {
@@ -37,7 +111,7 @@ impl Parse for PacketConfig {
         // 解析包列表 [Handshake, StatusRequest, ...]
         let content;
         syn::bracketed!(content in input);
-        let packets = content.parse_terminated(Ident::parse, syn::Token![,])?;
+        let packets = content.parse_terminated(syn::Path::parse_mod_style, syn::Token![,])?;
         
         // 解析逗号分隔符
         input.parse::<syn::Token![,]>()?;
@@ -45,154 +119,407 @@ impl Parse for PacketConfig {
         // 解析NullPacket类型
         let null_packet = input.parse::<Ident>()?;
         
+        // 可选的 , enum ClientLoginPacket
+        let mut packet_enum = None;
+        if input.peek(syn::Token![,]) {
+            input.parse::<syn::Token![,]>()?;
+            if !input.is_empty() {
+                input.parse::<syn::Token![enum]>()?;
+                packet_enum = Some(input.parse::<Ident>()?);
+            }
+        }
+        
         Ok(PacketConfig {
             packets: packets.into_iter().collect(),
             null_packet,
+            packet_enum,
         })
     }
 }
 
 /// 实现Packet trait
-pub fn implement_packet(packet_id: u32, input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
-    let struct_name = &input.ident;
+pub fn implement_packet(args: &PacketArgs, input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    reject_generics(input)?;
+    let mut input = input.clone();
     
-    // 移除默认值解析，只保留序列化字段
-    let serialize_fields = parse_serialize_fields(input)?;
+    // 解析字段属性并从结构体上移除
+    let codecs = field_codecs(&mut input)?;
     
     // 只生成Packet trait实现，移除Default实现
-    let packet_impl = generate_packet_impl(struct_name, packet_id, &serialize_fields);
+    let packet_impl = generate_packet_impl(&input.ident, args, &codecs);
+    let arbitrary_impl = generate_arbitrary_impl(&input.ident, &codecs);
     
+    let input = with_serde_derive(&input);
     Ok(quote! {
         #input
         
         #packet_impl
+        
+        #arbitrary_impl
     })
 }
 
-/// 只解析用于序列化的字段名（移除默认值处理）
-fn parse_serialize_fields(input: &DeriveInput) -> syn::Result<Vec<proc_macro2::TokenStream>> {
-    let mut serialize_fields = Vec::new();
-    
-    if let syn::Data::Struct(data_struct) = &input.data {
-        if let syn::Fields::Named(fields_named) = &data_struct.fields {
-            for field in &fields_named.named {
-                let field_name = field.ident.as_ref().unwrap();
-                
-                serialize_fields.push(quote! {
-                    #field_name
-                });
+/// 生成的 impl 不带泛型参数,带泛型的类型需要手写实现
+fn reject_generics(input: &DeriveInput) -> syn::Result<()> {
+    if input.generics.params.is_empty() && input.generics.where_clause.is_none() {
+        return Ok(());
+    }
+    Err(syn::Error::new(
+        input.generics.span(),
+        "generic types are not supported, implement the trait by hand",
+    ))
+}
+
+/// 生成 `Option<i32>` 字面量
+pub fn option_tokens(value: Option<i32>) -> proc_macro2::TokenStream {
+    match value {
+        Some(value) => quote! { Some(#value) },
+        None => quote! { None },
+    }
+}
+
+/// qexed_tcp_connect 启用 `serde` 特性时派生 Serialize/Deserialize
+///
+/// 特性按 qexed_tcp_connect 判断: 生成的 `#[cfg]` 会按使用方的特性求值,所以交给它导出的宏展开
+fn with_serde_derive(input: &DeriveInput) -> proc_macro2::TokenStream {
+    quote! {
+        qexed_tcp_connect::__with_serde_derive! {
+            #input
+        }
+    }
+}
+
+/// qexed_tcp_connect 启用 `arbitrary` 特性时实现 ArbitraryData 和 arbitrary::Arbitrary
+fn generate_arbitrary_impl(struct_name: &Ident, codecs: &FieldCodecs) -> proc_macro2::TokenStream {
+    let arbitrary = &codecs.arbitrary;
+    quote! {
+        qexed_tcp_connect::__if_arbitrary! {
+            impl qexed_tcp_connect::net_types::arbitrary_data::ArbitraryData for #struct_name {
+                fn arbitrary_data(u: &mut qexed_tcp_connect::arbitrary::Unstructured) -> qexed_tcp_connect::arbitrary::Result<Self> {
+                    Ok({
+                        #(#arbitrary)*
+                    })
+                }
+            }
+
+            impl<'a> qexed_tcp_connect::arbitrary::Arbitrary<'a> for #struct_name {
+                fn arbitrary(u: &mut qexed_tcp_connect::arbitrary::Unstructured<'a>) -> qexed_tcp_connect::arbitrary::Result<Self> {
+                    qexed_tcp_connect::net_types::arbitrary_data::ArbitraryData::arbitrary_data(u)
+                }
             }
         }
     }
-    
-    Ok(serialize_fields)
 }
 
-fn generate_packet_impl(struct_name: &Ident, packet_id: u32, serialize_fields: &[proc_macro2::TokenStream]) -> proc_macro2::TokenStream {
+fn generate_packet_impl(struct_name: &Ident, args: &PacketArgs, codecs: &FieldCodecs) -> proc_macro2::TokenStream {
+    let FieldCodecs { serialize, deserialize, descriptors, .. } = codecs;
+    let packet_id = args.id;
+    let supports_version = (args.since.is_some() || args.until.is_some()).then(|| {
+        quote! {
+            fn supports_version(&self, protocol_version: i32) -> bool {
+                qexed_tcp_connect::packet::version_in_range(protocol_version, <Self as qexed_tcp_connect::net_types::packet::PacketMeta>::SINCE, <Self as qexed_tcp_connect::net_types::packet::PacketMeta>::UNTIL)
+            }
+        }
+    });
+    let since = option_tokens(args.since);
+    let until = option_tokens(args.until);
+    let state = match &args.state {
+        Some(state) => quote! { Some(qexed_tcp_connect::net_types::packet::ConnectionState::#state) },
+        None => quote! { None },
+    };
+    let direction = match &args.direction {
+        Some(direction) => quote! { Some(qexed_tcp_connect::net_types::packet::PacketDirection::#direction) },
+        None => quote! { None },
+    };
+    // Clone / PartialEq 缺失时错误指向结构体名而不是宏调用处
+    let box_clone = quote_spanned! {struct_name.span()=>
+        fn box_clone(&self) -> Box<dyn qexed_tcp_connect::net_types::packet::Packet> {
+            Box::new(Clone::clone(self))
+        }
+    };
+    let dyn_eq = quote_spanned! {struct_name.span()=>
+        fn dyn_eq(&self, other: &dyn qexed_tcp_connect::net_types::packet::Packet) -> bool {
+            other.as_any().downcast_ref::<Self>().is_some_and(|other| PartialEq::eq(self, other))
+        }
+    };
     quote! {
+        impl qexed_tcp_connect::net_types::packet::PacketMeta for #struct_name {
+            const ID: u32 = #packet_id;
+            const NAME: &'static str = stringify!(#struct_name);
+            const SINCE: Option<i32> = #since;
+            const UNTIL: Option<i32> = #until;
+            const STATE: Option<qexed_tcp_connect::net_types::packet::ConnectionState> = #state;
+            const DIRECTION: Option<qexed_tcp_connect::net_types::packet::PacketDirection> = #direction;
+            const FIELDS: &'static [qexed_tcp_connect::net_types::packet::FieldDescriptor] = &[
+                #(#descriptors),*
+            ];
+            qexed_tcp_connect::__if_arbitrary! {
+                const ARBITRARY: qexed_tcp_connect::packet::round_trip::ArbitraryPacket =
+                    qexed_tcp_connect::packet::round_trip::ArbitraryPacket::of::<Self>();
+            }
+        }
+
         impl qexed_tcp_connect::net_types::packet::Packet for #struct_name {
             fn id(&self) -> u32 {
-                #packet_id
+                <Self as qexed_tcp_connect::net_types::packet::PacketMeta>::ID
+            }
+
+            fn name(&self) -> &'static str {
+                <Self as qexed_tcp_connect::net_types::packet::PacketMeta>::NAME
             }
             
             fn serialize(&self, w: &mut qexed_tcp_connect::packet::encode::PacketWriter) {
-                #(w.serialize(&self.#serialize_fields);)*
+                #(#serialize)*
             }
 
             fn deserialize(&mut self, r: &mut qexed_tcp_connect::packet::decode::PacketReader) {
-                #(self.#serialize_fields = r.deserialize();)*
+                #(#deserialize)*
             }
             
             fn as_any(&self) -> &dyn std::any::Any {
                 self
             }
-        }
-    }
-}
 
-/// 为每个数据包生成匹配分支
-pub fn generate_match_arms(packets: &[Ident]) -> Vec<proc_macro2::TokenStream> {
-    packets.iter().enumerate().map(|(index, packet_name)| {
-        let packet_id = index as u32;
-        quote! {
-            #packet_id => Box::new(#packet_name::new()),
-        }
-    }).collect()
-}
+            fn into_any(self: Box<Self>) -> Box<dyn std::any::Any + Send + Sync> {
+                self
+            }
+
+            #box_clone
 
+            #dyn_eq
 
+            #supports_version
+
+            fn fields(&self) -> &'static [qexed_tcp_connect::net_types::packet::FieldDescriptor] {
+                <Self as qexed_tcp_connect::net_types::packet::PacketMeta>::FIELDS
+            }
+
+            qexed_tcp_connect::__if_serde! {
+                fn to_json(&self) -> Option<qexed_tcp_connect::serde_json::Value> {
+                    qexed_tcp_connect::serde_json::to_value(self).ok()
+                }
+            }
+        }
+    }
+}
 
 /// 实现 Subdata trait
-pub fn implement_subdata(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
-    let struct_name = &input.ident;
+pub fn implement_subdata(args: &SubstructArgs, input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    reject_generics(input)?;
+    let mut input = input.clone();
     
-    // 解析序列化字段
-    let serialize_fields = parse_serialize_fields(input)?;
+    // 解析字段属性并从结构体上移除,枚举按标签分派到各个变体
+    let codecs = match &mut input.data {
+        syn::Data::Enum(data_enum) => {
+            let kind = args.tag.as_ref().map_or(TagKind::VarInt, |(kind, _)| *kind);
+            enum_codecs(&input.ident, data_enum, kind)?
+        }
+        _ => {
+            if let Some((_, span)) = &args.tag {
+                return Err(syn::Error::new(*span, "`tag` only applies to enums"));
+            }
+            field_codecs(&mut input)?
+        }
+    };
     
     // 生成 Subdata trait 实现
-    let subdata_impl = generate_subdata_impl(struct_name, &serialize_fields);
+    let subdata_impl = generate_subdata_impl(&input.ident, &codecs);
+    let arbitrary_impl = generate_arbitrary_impl(&input.ident, &codecs);
     
+    let input = with_serde_derive(&input);
     Ok(quote! {
         #input
         
         #subdata_impl
+        
+        #arbitrary_impl
     })
 }
 
-fn generate_subdata_impl(struct_name: &Ident, serialize_fields: &[proc_macro2::TokenStream]) -> proc_macro2::TokenStream {
+fn generate_subdata_impl(struct_name: &Ident, codecs: &FieldCodecs) -> proc_macro2::TokenStream {
+    let FieldCodecs { serialize, deserialize, .. } = codecs;
+    let new = quote_spanned! {struct_name.span()=>
+        fn new() -> Self {
+            <Self as Default>::default()
+        }
+    };
     quote! {
         impl qexed_tcp_connect::net_types::subdata::Subdata for #struct_name {
-            fn new() -> Self {
-                Self::default()
-            }
+            #new
 
             fn serialize(&self, w: &mut qexed_tcp_connect::packet::encode::PacketWriter) {
-                #(w.serialize(&self.#serialize_fields);)*
+                #(#serialize)*
             }
 
             fn deserialize(&mut self, r: &mut qexed_tcp_connect::packet::decode::PacketReader) {
-                #(self.#serialize_fields = r.deserialize();)*
+                #(#deserialize)*
             }
         }
     }
 }
 
-// 在原有的 generate_function 基础上，增加常量生成
-pub fn generate_function_with_consts(
-    packets: &[Ident],
-    null_packet: &Ident
-) -> proc_macro2::TokenStream {
-    let match_arms = generate_match_arms(packets);
+/// 数据包路径的最后一段 (结构体名)
+fn packet_ident(path: &syn::Path) -> &Ident {
+    &path.segments.last().expect("empty packet path").ident
+}
+
+/// `<Path as PacketMeta>::ITEM`,类型没有实现 PacketMeta (不是 `#[packet]` 定义的) 时错误指向该路径
+fn packet_meta(path: &syn::Path, item: &str) -> proc_macro2::TokenStream {
+    let item = Ident::new(item, path.span());
+    quote_spanned! {path.span()=> <#path as qexed_tcp_connect::net_types::packet::PacketMeta>::#item }
+}
+
+/// 根据数据包声明的 `ID` 常量生成 ID 常量、ID 冲突检查、`id_to_packet` 和 `packet_name`
+pub fn generate_function_with_consts(config: &PacketConfig) -> proc_macro2::TokenStream {
+    let PacketConfig { packets, null_packet, packet_enum } = config;
     let const_defs = generate_const_definitions(packets);
+    let packet_enum = packet_enum.as_ref().map(|name| generate_packet_enum(name, packets));
+    let duplicate_checks = generate_duplicate_checks(packets);
+    let meta = |item| packets.iter().map(move |path| packet_meta(path, item)).collect::<Vec<_>>();
+    let (ids, names) = (meta("ID"), meta("NAME"));
+    let (sinces, untils) = (meta("SINCE"), meta("UNTIL"));
+    let arbitrary = meta("ARBITRARY");
+    let constructors = packets.iter().map(|path| {
+        quote_spanned! {path.span()=>
+            Box::new(<#path as Default>::default()) as Box<dyn qexed_tcp_connect::net_types::packet::Packet>
+        }
+    });
     
     quote! {
         #(#const_defs)*
         
-        /// 根据数据包ID创建对应的Packet实例
-        pub fn id_to_packet(id: u32) -> Box<dyn qexed_tcp_connect::net_types::packet::Packet> {
+        // 同一协议版本下两个数据包的ID相同时编译失败
+        const _: () = {
+            #(#duplicate_checks)*
+        };
+        
+        /// 根据数据包ID和协议版本创建对应的Packet实例
+        pub fn id_to_packet(id: u32, protocol_version: i32) -> Box<dyn qexed_tcp_connect::net_types::packet::Packet> {
             match id {
-                #(#match_arms)*
+                #(
+                    #ids if qexed_tcp_connect::packet::version_in_range(protocol_version, #sinces, #untils) => {
+                        #constructors
+                    }
+                )*
                 _ => {
                     // 使用传入的NullPacket类型
-                    log::warn!("Unknown packet ID: 0x{:X}, returning {} instance", id, stringify!(#null_packet));
+                    qexed_tcp_connect::tracing::warn!(packet_id = id, "Unknown packet ID: 0x{:X}, returning {} instance", id, stringify!(#null_packet));
                     Box::new(#null_packet::new())
                 },
             }
         }
+        
+        #packet_enum
+        
+        qexed_tcp_connect::__if_arbitrary! {
+            /// 每个数据包生成任意实例的函数,用于往返测试
+            #[allow(dead_code)]
+            pub const ARBITRARY_PACKETS: &[qexed_tcp_connect::packet::round_trip::ArbitraryPacket] = &[
+                #(#arbitrary,)*
+            ];
+        }
+        
+        /// 根据数据包ID和协议版本查询数据包名称
+        #[allow(dead_code)]
+        pub fn packet_name(id: u32, protocol_version: i32) -> Option<&'static str> {
+            match id {
+                #(
+                    #ids if qexed_tcp_connect::packet::version_in_range(protocol_version, #sinces, #untils) => Some(#names),
+                )*
+                _ => None,
+            }
+        }
     }
 }
 
 /// 为每个数据包生成常量定义
-fn generate_const_definitions(packets: &[Ident]) -> Vec<proc_macro2::TokenStream> {
-    packets.iter().enumerate().map(|(index, packet_name)| {
-        let packet_id = index as u32;
-        let const_name = format!("ID_{}", packet_name);
-        let const_ident = Ident::new(&const_name, packet_name.span());
+fn generate_const_definitions(packets: &[syn::Path]) -> Vec<proc_macro2::TokenStream> {
+    packets.iter().map(|path| {
+        let packet_name = packet_ident(path);
+        let const_ident = format_ident!("ID_{}", packet_name);
+        let id = packet_meta(path, "ID");
         
         quote! {
             /// 数据包ID常量
-            #[allow(dead_code)]
-            pub const #const_ident: u32 = #packet_id;
+            #[allow(dead_code, non_upper_case_globals)]
+            pub const #const_ident: u32 = #id;
         }
     }).collect()
-}
\ No newline at end of file
+}
+
+/// 两两比较数据包ID,版本范围重叠且ID相同时在编译期报错
+fn generate_duplicate_checks(packets: &[syn::Path]) -> Vec<proc_macro2::TokenStream> {
+    let mut checks = Vec::new();
+    for (index, a) in packets.iter().enumerate() {
+        for b in &packets[index + 1..] {
+            let [id_a, since_a, until_a] = ["ID", "SINCE", "UNTIL"].map(|item| packet_meta(a, item));
+            let [id_b, since_b, until_b] = ["ID", "SINCE", "UNTIL"].map(|item| packet_meta(b, item));
+            let message = format!(
+                "duplicate packet ID: {} and {}",
+                quote!(#a).to_string().replace(' ', ""),
+                quote!(#b).to_string().replace(' ', ""),
+            );
+            checks.push(quote_spanned! {b.span()=>
+                assert!(
+                    #id_a != #id_b
+                        || !qexed_tcp_connect::packet::version_ranges_overlap(
+                            #since_a,
+                            #until_a,
+                            #since_b,
+                            #until_b,
+                        ),
+                    #message
+                );
+            });
+        }
+    }
+    checks
+}
+
+/// 生成包含所有数据包的枚举,便于用 `match` 穷尽处理
+fn generate_packet_enum(name: &Ident, packets: &[syn::Path]) -> proc_macro2::TokenStream {
+    let variants: Vec<&Ident> = packets.iter().map(packet_ident).collect();
+    quote! {
+        /// 该状态的所有数据包,`Other` 为未知数据包
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        pub enum #name {
+            #(#variants(#packets),)*
+            Other(Box<dyn qexed_tcp_connect::net_types::packet::Packet>),
+        }
+
+        #[allow(dead_code)]
+        impl #name {
+            /// 按具体类型转换,不属于该状态的数据包放入 `Other`
+            pub fn from_packet(packet: Box<dyn qexed_tcp_connect::net_types::packet::Packet>) -> Self {
+                #(
+                    let packet = match packet.downcast::<#packets>() {
+                        Ok(packet) => return #name::#variants(*packet),
+                        Err(packet) => packet,
+                    };
+                )*
+                #name::Other(packet)
+            }
+
+            pub fn into_packet(self) -> Box<dyn qexed_tcp_connect::net_types::packet::Packet> {
+                match self {
+                    #(#name::#variants(packet) => Box::new(packet),)*
+                    #name::Other(packet) => packet,
+                }
+            }
+
+            pub fn as_packet(&self) -> &dyn qexed_tcp_connect::net_types::packet::Packet {
+                match self {
+                    #(#name::#variants(packet) => packet,)*
+                    #name::Other(packet) => packet.as_ref(),
+                }
+            }
+        }
+
+        impl From<Box<dyn qexed_tcp_connect::net_types::packet::Packet>> for #name {
+            fn from(packet: Box<dyn qexed_tcp_connect::net_types::packet::Packet>) -> Self {
+                Self::from_packet(packet)
+            }
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use proc_macro2::TokenStream as TokenStream2;
-use quote::{quote};
+use quote::{quote, quote_spanned};
 use syn::{parse_macro_input, Ident, Type, Block, Token, Path};
 use syn::parse::{Parse, ParseStream, Result};
+use syn::spanned::Spanned;
 
 /// 解析 packet_match 宏的输入
 pub struct PacketMatchInput {
@@ -13,7 +14,7 @@ pub struct PacketMatchInput {
 
 /// 解析单个包匹配分支
 pub struct PacketCase {
-    packet_id: Ident,  // 改为 Ident，因为现在只需要简化的常量名
+    packet_id: Option<Ident>,  // 省略时使用包类型的 PacketMeta::ID
     packet_type: Type, // 保持 Type，但现在是相对路径
     var_name: Ident,
     handler: Block,
@@ -82,9 +83,14 @@ impl Parse for PacketMatchInput {
 
 /// 解析单个 case 分支，现在需要基础路径来构建完整路径
 fn parse_packet_case(input: ParseStream, _base_path: &Path) -> Result<PacketCase> {
-    // 解析包ID（现在只需要简化的常量名）
-    let packet_id: Ident = input.parse()?;
-    input.parse::<Token![=>]>()?;
+    // 解析包ID（现在只需要简化的常量名），可以省略
+    let packet_id = if input.peek(syn::Ident) && input.peek2(Token![=>]) {
+        let packet_id: Ident = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        Some(packet_id)
+    } else {
+        None
+    };
     
     // 解析包类型（相对路径）
     let packet_type: Type = input.parse()?;
@@ -109,18 +115,17 @@ fn generate_packet_match(input: PacketMatchInput) -> TokenStream2 {
     let packet_var = &input.packet_var;
     let base_path = &input.base_path;
     let cases = &input.cases;
-    let default_case = &input.default_case;
+    let default_handler = match &input.default_case {
+        Some(default_handler) => quote! { #default_handler },
+        None => quote! { {} },
+    };
     
     // 生成所有 case 分支
     let case_arms: Vec<_> = cases.iter().map(|case| {
-        let packet_id = &case.packet_id;
         let packet_type = &case.packet_type;
         let var_name = &case.var_name;
         let handler = &case.handler;
         
-        // 构建完整的包ID路径（基础路径 + pool + 包ID）
-        let full_packet_id = quote! { #base_path::pool::#packet_id };
-        
         // 构建完整的包类型路径（基础路径 + 包类型）
         let full_packet_type = if let syn::Type::Path(type_path) = packet_type {
             if type_path.path.segments.is_empty() {
@@ -140,21 +145,25 @@ fn generate_packet_match(input: PacketMatchInput) -> TokenStream2 {
             packet_type.clone()
         };
         
+        // 构建完整的包ID路径（基础路径 + pool + 包ID），常量不存在时错误指向包ID
+        let full_packet_id = match &case.packet_id {
+            Some(packet_id) => quote_spanned! {packet_id.span()=> #base_path::pool::#packet_id },
+            None => quote_spanned! {packet_type.span()=>
+                <#full_packet_type as qexed_tcp_connect::net_types::packet::PacketMeta>::ID
+            },
+        };
+        
+        // ID 相同但类型不符时(例如ID常量与类型写错)走默认分支,而不是什么都不做
         quote! {
-            #full_packet_id => {
-                if let Some(#var_name) = #packet_var.as_any().downcast_ref::<#full_packet_type>() {
-                    #handler
-                }
+            #full_packet_id => match #packet_var.as_any().downcast_ref::<#full_packet_type>() {
+                Some(#var_name) => #handler,
+                None => #default_handler,
             }
         }
     }).collect();
     
     // 生成默认分支
-    let default_arm = if let Some(default_handler) = default_case {
-        quote! { _ => #default_handler }
-    } else {
-        quote! { _ => {} }
-    };
+    let default_arm = quote! { _ => #default_handler };
     
     quote! {
         {
@@ -0,0 +1,284 @@
+// src/variant.rs
+use std::collections::HashSet;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{DataEnum, Expr, Fields, Ident, Lit};
+
+use crate::field::{construct, generate_codecs, parse_fields, Access, FieldCodecs};
+
+/// 枚举标签 (判别值) 的编码方式
+#[derive(Clone, Copy)]
+pub enum TagKind {
+    VarInt,
+    U8,
+    /// 命名空间ID字符串,省略命名空间时为 `minecraft`
+    Identifier,
+}
+
+/// `#[substruct(...)]` 的参数
+#[derive(Default)]
+pub struct SubstructArgs {
+    pub tag: Option<(TagKind, Span)>,
+}
+
+impl Parse for SubstructArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = SubstructArgs::default();
+        if input.is_empty() {
+            return Ok(args);
+        }
+        // 解析 tag = varint / u8 / identifier 格式
+        let key: Ident = input.parse()?;
+        if key != "tag" {
+            return Err(syn::Error::new(key.span(), "expected `tag`"));
+        }
+        input.parse::<syn::Token![=]>()?;
+        let value: Ident = input.parse()?;
+        let kind = match value.to_string().as_str() {
+            "varint" => TagKind::VarInt,
+            "u8" => TagKind::U8,
+            "identifier" => TagKind::Identifier,
+            _ => {
+                return Err(syn::Error::new(
+                    value.span(),
+                    "expected `varint`, `u8` or `identifier`",
+                ));
+            }
+        };
+        args.tag = Some((kind, value.span()));
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after tag"));
+        }
+        Ok(args)
+    }
+}
+
+/// 变体的标签值
+enum Tag {
+    Number(i64),
+    Identifier(String),
+}
+
+/// `CraftingShapeless` -> `crafting_shapeless`
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if index > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// 解析变体上的 `#[id = ..]` 并从变体上移除;没有指定时数字标签接着上一个递增,
+/// 字符串标签为 `minecraft:` 加蛇形命名的变体名
+fn variant_tag(variant: &mut syn::Variant, kind: TagKind, next: i64) -> syn::Result<Tag> {
+    let mut tag = None;
+    for attr in variant.attrs.iter().filter(|attr| attr.path().is_ident("id")) {
+        if tag.is_some() {
+            return Err(syn::Error::new(attr.span(), "duplicate `id` attribute"));
+        }
+        let value = &attr.meta.require_name_value()?.value;
+        tag = Some(match (kind, value) {
+            (
+                TagKind::VarInt | TagKind::U8,
+                Expr::Lit(syn::ExprLit {
+                    lit: Lit::Int(lit), ..
+                }),
+            ) => {
+                let id = lit.base10_parse::<i64>()?;
+                let max = tag_max(kind);
+                if id > max {
+                    return Err(syn::Error::new(lit.span(), format!("tag {} exceeds {}", id, max)));
+                }
+                Tag::Number(id)
+            }
+            (
+                TagKind::Identifier,
+                Expr::Lit(syn::ExprLit {
+                    lit: Lit::Str(lit), ..
+                }),
+            ) => Tag::Identifier(lit.value()),
+            (TagKind::Identifier, other) => {
+                return Err(syn::Error::new(other.span(), "expected a string identifier"));
+            }
+            (_, other) => return Err(syn::Error::new(other.span(), "expected an integer tag")),
+        });
+    }
+    variant.attrs.retain(|attr| !attr.path().is_ident("id"));
+    if let Some(discriminant) = &variant.discriminant {
+        return Err(syn::Error::new(
+            discriminant.1.span(),
+            "use `#[id = ..]` instead of an explicit discriminant",
+        ));
+    }
+    if let Some(tag) = tag {
+        return Ok(tag);
+    }
+    match kind {
+        TagKind::Identifier => Ok(Tag::Identifier(format!("minecraft:{}", snake_case(&variant.ident.to_string())))),
+        // 递增后超出范围时不回绕,否则会和前面的标签重复
+        _ if next > tag_max(kind) => Err(syn::Error::new(
+            variant.ident.span(),
+            format!("tag {} exceeds {}, specify it with `#[id = ..]`", next, tag_max(kind)),
+        )),
+        _ => Ok(Tag::Number(next)),
+    }
+}
+
+/// 数字标签的最大值
+fn tag_max(kind: TagKind) -> i64 {
+    match kind {
+        TagKind::U8 => u8::MAX as i64,
+        _ => i32::MAX as i64,
+    }
+}
+
+/// 匹配时忽略 `minecraft:` 命名空间
+fn tag_key(id: &str) -> &str {
+    id.strip_prefix("minecraft:").unwrap_or(id)
+}
+
+/// 为枚举生成按标签分派的读写代码,并移除变体和字段上的属性
+pub fn enum_codecs(enum_name: &Ident, data_enum: &mut DataEnum, kind: TagKind) -> syn::Result<FieldCodecs> {
+    let mut serialize_arms = Vec::new();
+    let mut deserialize_arms = Vec::new();
+    let mut arbitrary_arms = Vec::new();
+    let mut seen = HashSet::new();
+    let mut next = 0;
+
+    for variant in data_enum.variants.iter_mut() {
+        let tag = variant_tag(variant, kind, next)?;
+        let (write_tag, pattern) = match &tag {
+            Tag::Number(id) => {
+                next = id + 1;
+                let write = match kind {
+                    TagKind::U8 => {
+                        let id = *id as u8;
+                        quote! { w.u8(#id); }
+                    }
+                    _ => {
+                        let id = *id as i32;
+                        quote! { w.varint(&qexed_tcp_connect::net_types::var_int::VarInt(#id)); }
+                    }
+                };
+                let pattern = match kind {
+                    TagKind::U8 => {
+                        let id = *id as u8;
+                        quote! { #id }
+                    }
+                    _ => {
+                        let id = *id as i32;
+                        quote! { #id }
+                    }
+                };
+                (write, pattern)
+            }
+            Tag::Identifier(id) => {
+                let key = tag_key(id);
+                (quote! { w.string(#id); }, quote! { #key })
+            }
+        };
+        let key = match &tag {
+            Tag::Number(id) => id.to_string(),
+            Tag::Identifier(id) => tag_key(id).to_string(),
+        };
+        if !seen.insert(key.clone()) {
+            return Err(syn::Error::new(
+                variant.ident.span(),
+                format!("duplicate tag `{}` in enum `{}`", key, enum_name),
+            ));
+        }
+
+        let variant_name = &variant.ident;
+        let specs = parse_fields(&mut variant.fields)?;
+        let FieldCodecs { serialize, deserialize, arbitrary, .. } = generate_codecs(&specs, Access::Binding);
+        let bindings: Vec<TokenStream> = specs
+            .iter()
+            .map(|spec| {
+                let binding = &spec.binding;
+                if spec.skip { quote! { _ } } else { quote! { #binding } }
+            })
+            .collect();
+        let defaults = specs.iter().filter(|spec| spec.skip).map(|spec| {
+            let binding = &spec.binding;
+            quote! { let #binding = Default::default(); }
+        });
+        let serialize_pattern = match &variant.fields {
+            Fields::Named(_) => {
+                let wire = specs.iter().filter(|spec| !spec.skip).map(|spec| &spec.binding);
+                quote! { Self::#variant_name { #(#wire,)* .. } }
+            }
+            Fields::Unnamed(_) => quote! { Self::#variant_name(#(#bindings),*) },
+            Fields::Unit => quote! { Self::#variant_name },
+        };
+        let construct = construct(quote! { Self::#variant_name }, &variant.fields, &specs);
+
+        serialize_arms.push(quote! {
+            #serialize_pattern => {
+                #write_tag
+                #(#serialize)*
+            }
+        });
+        deserialize_arms.push(quote! {
+            #pattern => {
+                #(#deserialize)*
+                #(#defaults)*
+                #construct
+            }
+        });
+        let index = arbitrary_arms.len();
+        arbitrary_arms.push(quote! {
+            #index => {
+                #(#arbitrary)*
+                #construct
+            }
+        });
+    }
+
+    let read_tag = match kind {
+        TagKind::VarInt => quote! { let tag = r.varint().0; },
+        TagKind::U8 => quote! { let tag = r.u8(); },
+        TagKind::Identifier => quote! {
+            let tag = r.string();
+            let tag = tag.strip_prefix("minecraft:").unwrap_or(&tag);
+        },
+    };
+    let serialize = quote! {
+        match self {
+            #(#serialize_arms)*
+        }
+    };
+    let deserialize = quote! {
+        r.field("tag");
+        #read_tag
+        *self = match tag {
+            #(#deserialize_arms)*
+            other => {
+                r.fail(format!("Unknown {} tag: {}", stringify!(#enum_name), other));
+                return;
+            }
+        };
+    };
+    let variants = arbitrary_arms.len();
+    let arbitrary = quote! {
+        match u.choose_index(#variants)? {
+            #(#arbitrary_arms)*
+            _ => unreachable!(),
+        }
+    };
+    Ok(FieldCodecs {
+        serialize: vec![serialize],
+        deserialize: vec![deserialize],
+        descriptors: Vec::new(),
+        arbitrary: vec![arbitrary],
+    })
+}
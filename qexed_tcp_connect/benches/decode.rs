@@ -0,0 +1,59 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use qexed_tcp_connect::{
+    net_types::{rest_buffer::RestBuffer, var_int::VarInt},
+    packet::{decode::PacketReader, encode::PacketWriter},
+    try_parse_packet,
+};
+
+/// 模拟区块数据包: 包ID + 字符串 + 大量剩余数据
+fn frame(payload: usize) -> Bytes {
+    let mut buf = BytesMut::new();
+    let mut w = PacketWriter::new(&mut buf);
+    w.varint(&VarInt(0x27));
+    w.string("minecraft:overworld");
+    w.bytes(&vec![0xAB; payload]);
+    buf.freeze()
+}
+
+fn framed(frames: usize, payload: usize) -> BytesMut {
+    let frame = frame(payload);
+    let mut buf = BytesMut::new();
+    for _ in 0..frames {
+        let mut w = PacketWriter::new(&mut buf);
+        w.varint(&VarInt(frame.len() as i32));
+        buf.put_slice(&frame);
+    }
+    buf
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    let data = frame(64 * 1024);
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("rest_buffer", |b| {
+        b.iter(|| {
+            let mut buf = data.clone();
+            let mut r = PacketReader::new(Box::new(&mut buf));
+            r.varint();
+            let name = r.string();
+            let rest: RestBuffer = r.deserialize();
+            black_box((name, rest))
+        })
+    });
+
+    let stream = framed(16, 64 * 1024);
+    group.throughput(Throughput::Bytes(stream.len() as u64));
+    group.bench_function("try_parse_packet", |b| {
+        b.iter(|| {
+            let mut buffer = stream.clone();
+            while let Some(frame) = try_parse_packet(&mut buffer, false).unwrap() {
+                black_box(frame);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
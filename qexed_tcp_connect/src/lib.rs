@@ -1,20 +1,76 @@
 use std::io::ErrorKind;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::net::TcpStream;
 use tokio::{io::AsyncReadExt, net::TcpListener};
 use std::io::{Error, Result};
-use bytes::{BytesMut, Buf};
+use bytes::{Bytes, BytesMut, Buf};
 use crate::net_types::packet::Packet;
 use crate::net_types::var_int::VarInt;
 use crate::packet::decode::PacketReader;
 use crate::packet::encode::PacketWriter;
-use std::io::{Cursor};
 use tokio::io::{ AsyncWriteExt, ReadHalf, WriteHalf};
 use bytes::{BufMut};
 use flate2::Compression;
 use flate2::bufread::{ZlibDecoder, ZlibEncoder};
 use std::io::Read;
+// 宏生成的代码通过这里引用 serde,使用方不需要直接依赖
+pub use serde;
+pub use serde_json;
+pub use tracing;
+#[cfg(feature = "arbitrary")]
+pub use arbitrary;
+
+// 宏生成的代码按本 crate 的特性展开,而不是按使用方的特性
+// 启用 `serde` 时为数据包派生 Serialize/Deserialize
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __with_serde_derive {
+    ($($item:tt)*) => {
+        #[derive($crate::serde::Serialize, $crate::serde::Deserialize)]
+        #[serde(crate = "qexed_tcp_connect::serde")]
+        $($item)*
+    };
+}
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __with_serde_derive {
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
+// 启用 `serde` 时展开为给定的代码,否则为空
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_serde {
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_serde {
+    ($($item:tt)*) => {};
+}
+// 启用 `arbitrary` 时展开为给定的代码,否则为空
+#[cfg(feature = "arbitrary")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_arbitrary {
+    ($($item:tt)*) => {
+        $($item)*
+    };
+}
+#[cfg(not(feature = "arbitrary"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __if_arbitrary {
+    ($($item:tt)*) => {};
+}
 pub mod net_types;
 pub mod packet;
 pub mod player;
@@ -42,7 +98,7 @@ impl PacketListener {
         }
     }
     pub fn split(self)->(PacketRead,PacketSend){
-        return (
+        (
             PacketRead{
                 buffer: BytesMut::with_capacity(4096),
                 socket_read:self.socket_read,
@@ -51,7 +107,8 @@ impl PacketListener {
             PacketSend{
                 socket_write:self.socket_write,
                 compression_threshold:Arc::new(AtomicUsize::new(self.compression_threshold)),
-                compression_enabled:Arc::clone(&self.compression_enabled)
+                compression_enabled:Arc::clone(&self.compression_enabled),
+                protocol_version:Arc::new(AtomicI32::new(packet::UNKNOWN_PROTOCOL_VERSION)),
             },
             
         )
@@ -68,6 +125,7 @@ pub struct PacketSend {
     pub socket_write: WriteHalf<TcpStream>,
     compression_threshold: Arc<AtomicUsize>, // 改为原子类型
     compression_enabled: Arc<AtomicBool>, // 是否启用压缩
+    protocol_version: Arc<AtomicI32>, // 序列化数据包时使用的协议版本
 }
 
 impl PacketSend {
@@ -76,12 +134,14 @@ impl PacketSend {
             socket_write,
             compression_threshold: Arc::new(AtomicUsize::new(compression_threshold)),
             compression_enabled: Arc::new(AtomicBool::new(false)),
+            protocol_version: Arc::new(AtomicI32::new(packet::UNKNOWN_PROTOCOL_VERSION)),
         }
     }
 
-    pub async fn send<T: Packet>(&mut self, packet: &T) -> Result<()> {
+    pub async fn send<T: Packet + ?Sized>(&mut self, packet: &T) -> Result<()> {
         let mut buf = BytesMut::new();
-        let mut writer = PacketWriter::new(&mut buf);
+        let mut writer = PacketWriter::new(&mut buf)
+            .with_protocol_version(self.protocol_version.load(Ordering::Relaxed));
         writer.varint(&VarInt(packet.id().try_into().unwrap()));
         packet.serialize(&mut writer); // 序列化数据包
         self.send_raw(buf.freeze()).await
@@ -96,7 +156,7 @@ impl PacketSend {
     }
 
     async fn send_uncompressed(&mut self, data: bytes::Bytes) -> Result<()> {
-        let mut buf = BytesMut::new();
+        let mut buf = BytesMut::with_capacity(5 + data.len());
         write_varint(data.len() as i32, &mut buf); // 长度字段
         buf.put(data);
         self.socket_write.write_all(&buf).await?;
@@ -123,7 +183,7 @@ impl PacketSend {
         }
         
         // 写入总长度 (VarInt)
-        let mut final_buf = BytesMut::new();
+        let mut final_buf = BytesMut::with_capacity(5 + buf.len());
         write_varint(buf.len() as i32, &mut final_buf);
         final_buf.put(buf);
         
@@ -143,6 +203,11 @@ impl PacketSend {
     // 设置压缩值
     pub fn set_compression_value(&self, compression_threshold: usize) {
         self.compression_threshold.store(compression_threshold, Ordering::Relaxed);
+    }
+
+    // 设置协议版本
+    pub fn set_protocol_version(&self, protocol_version: i32) {
+        self.protocol_version.store(protocol_version, Ordering::Relaxed);
     }    
 }
 pub struct PacketRead{
@@ -153,18 +218,23 @@ pub struct PacketRead{
 impl PacketRead{
     
     pub async fn read(&mut self) -> Result<Vec<u8>> {
+        Ok(self.read_frame().await?.into())
+    }
+
+    /// 读取一个完整的帧(包ID + 数据)
+    ///
+    /// 未压缩的帧直接引用读缓冲区,不复制
+    pub async fn read_frame(&mut self) -> Result<Bytes> {
         loop {
             if let Some(packet) = self.try_parse_packet()? {
                 return Ok(packet);
             }
 
-            // 从套接字读取更多数据
-            let mut temp_buf = [0u8; 1024];
-            match self.socket_read.read(&mut temp_buf).await {
+            // 直接读入缓冲区,避免中间拷贝
+            self.buffer.reserve(4096);
+            match self.socket_read.read_buf(&mut self.buffer).await {
                 Ok(0) => return Err(Error::new(ErrorKind::ConnectionAborted, "Connection closed")),
-                Ok(n) => {
-                    self.buffer.extend_from_slice(&temp_buf[..n]);
-                }
+                Ok(_) => {}
                 Err(e) if e.kind() == ErrorKind::WouldBlock => continue,
                 Err(e) => return Err(e),
             }
@@ -172,77 +242,111 @@ impl PacketRead{
     }
 
     /// 尝试从缓冲区解析完整数据包（处理压缩）
-    fn try_parse_packet(&mut self) -> Result<Option<Vec<u8>>> {
-        // 创建缓冲区视图（不消耗数据）
-        let mut buf_view = self.buffer.clone().freeze();
-        
-        // 1. 读取数据包长度 (VarInt)
-        let packet_len = match read_varint(&mut buf_view) {
-            Ok(len) => len as usize,
-            Err(_) => return Ok(None), // 长度不完整
-        };
-        
-        // 检查整个数据包是否可用
-        let varint_len = self.buffer.len() - buf_view.len();
-        if self.buffer.len() < varint_len + packet_len {
-            return Ok(None);
+    fn try_parse_packet(&mut self) -> Result<Option<Bytes>> {
+        let compressed = self.compression_enabled.load(Ordering::Relaxed);
+        try_parse_packet(&mut self.buffer, compressed)
+    }
+    // 启用或禁用压缩
+    pub fn set_compression(&self, enabled: bool) {
+        self.compression_enabled.store(enabled, Ordering::Relaxed);
+    }    
+}
+/// 从读缓冲区中取出一个完整的帧,数据不完整时返回 `None`
+///
+/// 未压缩的帧是缓冲区的切片,不复制
+pub fn try_parse_packet(buffer: &mut BytesMut, compressed: bool) -> Result<Option<Bytes>> {
+    // 在切片上读取长度,不消耗也不复制缓冲区
+    let mut buf_view = &buffer[..];
+    
+    // 1. 读取数据包长度 (VarInt)
+    let packet_len = match read_varint(&mut buf_view) {
+        Ok(len) if len < 0 => {
+            return Err(Error::new(ErrorKind::InvalidData, format!("Negative packet length: {}", len)));
         }
-        
-        // 消耗缓冲区中的长度字段
-        self.buffer.advance(varint_len);
-        
-        // 提取数据包部分
-        let packet_data = self.buffer.split_to(packet_len);
-        
-        // 2. 处理压缩
-        let raw_data = if self.compression_enabled.load(Ordering::Relaxed) {
-            self.decompress_packet(packet_data)?
-        } else {
-            packet_data.to_vec()
-        };
-        
-        Ok(Some(raw_data))
+        Ok(len) if len as usize > MAX_PACKET_SIZE => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Packet length {} exceeds protocol maximum of {}", len, MAX_PACKET_SIZE),
+            ));
+        }
+        Ok(len) => len as usize,
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None), // 长度不完整
+        Err(e) => return Err(e),
+    };
+    
+    // 检查整个数据包是否可用
+    let varint_len = buffer.len() - buf_view.len();
+    if buffer.len() < varint_len + packet_len {
+        return Ok(None);
+    }
+    
+    // 消耗缓冲区中的长度字段
+    buffer.advance(varint_len);
+    
+    // 提取数据包部分
+    let packet_data = buffer.split_to(packet_len).freeze();
+    
+    // 2. 处理压缩
+    if compressed {
+        decompress_packet(packet_data).map(Some)
+    } else {
+        Ok(Some(packet_data))
     }
+}
 
-    /// 解压缩数据包
-    fn decompress_packet(&self, data: BytesMut) -> Result<Vec<u8>> {
-        let mut cursor = Cursor::new(&data);
-        
-        // 读取未压缩数据长度
-        let uncompressed_size = read_varint(&mut cursor)? as usize;
-        let header_len = cursor.position() as usize;
+/// 解压缩数据包,未压缩的数据包直接返回切片
+pub fn decompress_packet(mut data: Bytes) -> Result<Bytes> {
+    // 读取未压缩数据长度
+    let uncompressed_size = read_varint(&mut data)?;
+    if uncompressed_size < 0 {
+        return Err(Error::new(ErrorKind::InvalidData, format!("Negative uncompressed size: {}", uncompressed_size)));
+    }
+    let uncompressed_size = uncompressed_size as usize;
+    if uncompressed_size > MAX_UNCOMPRESSED_SIZE {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Uncompressed size {} exceeds protocol maximum of {}", uncompressed_size, MAX_UNCOMPRESSED_SIZE),
+        ));
+    }
+    
+    if uncompressed_size == 0 {
+        // 未压缩的数据包
+        Ok(data)
+    } else {
+        // 解压缩数据,不信任声明的长度做预分配
+        // 最多多解压一个字节,声明长度偏小的压缩炸弹不会耗尽内存
+        let mut decoder = ZlibDecoder::new(&data[..]).take(uncompressed_size as u64 + 1);
+        let mut decompressed = Vec::with_capacity(uncompressed_size.min(MAX_PREALLOCATE));
+        decoder.read_to_end(&mut decompressed)?;
         
-        if uncompressed_size == 0 {
-            // 未压缩的数据包
-            Ok(data[header_len..].to_vec())
-        } else {
-            // 解压缩数据
-            let compressed_data = &data[header_len..];
-            let mut decoder = ZlibDecoder::new(compressed_data);
-            let mut decompressed = Vec::with_capacity(uncompressed_size);
-            decoder.read_to_end(&mut decompressed)?;
-            
-            if decompressed.len() != uncompressed_size {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    format!(
-                        "Decompressed size mismatch: expected {}, got {}",
-                        uncompressed_size,
-                        decompressed.len()
-                    ),
-                ));
-            }
-            
-            Ok(decompressed)
+        if decompressed.len() != uncompressed_size {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Decompressed size mismatch: expected {}, got {}",
+                    uncompressed_size,
+                    decompressed.len()
+                ),
+            ));
         }
+        
+        Ok(decompressed.into())
     }
-    // 启用或禁用压缩
-    pub fn set_compression(&self, enabled: bool) {
-        self.compression_enabled.store(enabled, Ordering::Relaxed);
-    }    
 }
+
+/// 解压时按声明长度预分配的上限
+const MAX_PREALLOCATE: usize = 2 * 1024 * 1024;
+
+/// 帧长度的上限,即三字节 VarInt 能表示的最大值,与原版相同
+pub const MAX_PACKET_SIZE: usize = 2097151;
+
+/// 解压后数据包的最大长度,与原版服务端相同
+const MAX_UNCOMPRESSED_SIZE: usize = 8 * 1024 * 1024;
+
 /// 读取 Minecraft 协议的变长整数 (VarInt)
-fn read_varint<B: Buf>(buf: &mut B) -> Result<i32> {
+///
+/// 数据不完整时返回 `UnexpectedEof`,超过 5 个字节时返回 `InvalidData`
+pub fn read_varint<B: Buf>(buf: &mut B) -> Result<i32> {
     let mut value = 0;
     let mut position = 0;
     let mut current_byte;
@@ -281,11 +385,11 @@ fn write_varint(value: i32, buf: &mut BytesMut) {
 }
 
 pub fn read_packet(data: Vec<u8>,status:u8,match_function:fn(u8,u32)->Box<dyn Packet>)->anyhow::Result<Box<dyn Packet>>{
-    let mut buf = BytesMut::new();
-    buf.extend_from_slice(&data);
+    let mut buf = Bytes::from(data);
     let mut reader = PacketReader::new(Box::new(&mut buf));
     let id = reader.varint().0 as u32;
     let mut decoded: Box<dyn Packet> = match_function(status,id);
     decoded.deserialize(&mut reader);
+    reader.finish()?;
     Ok(decoded)
 }
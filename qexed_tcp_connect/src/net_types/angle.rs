@@ -0,0 +1,37 @@
+use crate::{
+    net_types::subdata::Subdata,
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+/// 角度,一圈分为 256 步
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Angle(pub u8);
+impl Angle {
+    /// 从角度(度)转换,超出一圈的部分会被截断
+    pub fn from_degrees(degrees: f32) -> Self {
+        Angle((degrees / 360.0 * 256.0).rem_euclid(256.0) as u8)
+    }
+    /// 转换为角度(度), 范围 [0, 360)
+    pub fn degrees(&self) -> f32 {
+        self.0 as f32 * 360.0 / 256.0
+    }
+}
+impl Subdata for Angle {
+    fn new() -> Self {
+        Angle(0)
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.u8(self.0);
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.0 = r.u8();
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for Angle {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(Angle(u.arbitrary()?))
+    }
+}
@@ -0,0 +1,169 @@
+use arbitrary::{Result, Unstructured};
+
+use crate::net_types::nbt::{NbtConvert, NbtTag};
+
+/// 能按网络格式原样往返的任意值
+///
+/// 启用 `arbitrary` 特性时,`#[packet]` / `#[substruct]` 会为类型生成实现,
+/// 同时实现 `arbitrary::Arbitrary`。生成的值满足字段编码的限制 (长度前缀上限、
+/// 固定长度、`optional_if` 条件等),按最新协议版本序列化再反序列化后与原值相等
+pub trait ArbitraryData: Sized {
+    fn arbitrary_data(u: &mut Unstructured) -> Result<Self>;
+}
+
+macro_rules! arbitrary_data_via_arbitrary {
+    ($($ty:ty),*) => {
+        $(
+            impl ArbitraryData for $ty {
+                fn arbitrary_data(u: &mut Unstructured) -> Result<Self> {
+                    u.arbitrary()
+                }
+            }
+        )*
+    };
+}
+
+arbitrary_data_via_arbitrary!(u8, i8, u16, i16, u32, i32, u64, i64, bool, String);
+
+// NaN 与自身不相等,生成时替换为 0
+impl ArbitraryData for f32 {
+    fn arbitrary_data(u: &mut Unstructured) -> Result<Self> {
+        let value: f32 = u.arbitrary()?;
+        Ok(if value.is_nan() { 0.0 } else { value })
+    }
+}
+
+impl ArbitraryData for f64 {
+    fn arbitrary_data(u: &mut Unstructured) -> Result<Self> {
+        let value: f64 = u.arbitrary()?;
+        Ok(if value.is_nan() { 0.0 } else { value })
+    }
+}
+
+impl ArbitraryData for uuid::Uuid {
+    fn arbitrary_data(u: &mut Unstructured) -> Result<Self> {
+        Ok(uuid::Uuid::from_bytes(u.arbitrary()?))
+    }
+}
+
+impl<T: ArbitraryData, const N: usize> ArbitraryData for [T; N] {
+    fn arbitrary_data(u: &mut Unstructured) -> Result<Self> {
+        let values = (0..N).map(|_| T::arbitrary_data(u)).collect::<Result<Vec<_>>>()?;
+        Ok(values.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+impl<T: ArbitraryData> ArbitraryData for Option<T> {
+    fn arbitrary_data(u: &mut Unstructured) -> Result<Self> {
+        Ok(if u.arbitrary()? { Some(T::arbitrary_data(u)?) } else { None })
+    }
+}
+
+impl<T: ArbitraryData> ArbitraryData for Vec<T> {
+    fn arbitrary_data(u: &mut Unstructured) -> Result<Self> {
+        arbitrary_vec(u, usize::MAX)
+    }
+}
+
+/// JSON 值的最大嵌套深度
+const MAX_JSON_DEPTH: u32 = 3;
+
+// 不生成小数: serde_json 默认不保证小数解析后与原值完全相同
+fn arbitrary_json(u: &mut Unstructured, depth: u32) -> Result<serde_json::Value> {
+    use serde_json::Value;
+    let kinds = if depth < MAX_JSON_DEPTH { 6 } else { 4 };
+    Ok(match u.choose_index(kinds)? {
+        0 => Value::Null,
+        1 => Value::Bool(u.arbitrary()?),
+        2 => Value::from(u.arbitrary::<i64>()?),
+        3 => Value::String(u.arbitrary()?),
+        4 => Value::Array(
+            arbitrary_items(u, usize::MAX, |u| arbitrary_json(u, depth + 1))?,
+        ),
+        _ => Value::Object(
+            arbitrary_items(u, usize::MAX, |u| Ok((u.arbitrary()?, arbitrary_json(u, depth + 1)?)))?
+                .into_iter()
+                .collect(),
+        ),
+    })
+}
+
+impl ArbitraryData for serde_json::Value {
+    fn arbitrary_data(u: &mut Unstructured) -> Result<Self> {
+        arbitrary_json(u, 0)
+    }
+}
+
+/// 每个元素前读取一个布尔值决定是否继续,数据用完时结束
+pub fn arbitrary_items<T>(
+    u: &mut Unstructured,
+    max: usize,
+    mut item: impl FnMut(&mut Unstructured) -> Result<T>,
+) -> Result<Vec<T>> {
+    let mut items = Vec::new();
+    while items.len() < max && u.arbitrary()? {
+        items.push(item(u)?);
+    }
+    Ok(items)
+}
+
+/// 生成不超过 max 个元素的数组,用于 `#[prefixed(..)]` / `#[rest]` 字段
+pub fn arbitrary_vec<T: ArbitraryData>(u: &mut Unstructured, max: usize) -> Result<Vec<T>> {
+    arbitrary_items(u, max, T::arbitrary_data)
+}
+
+/// 生成恰好 len 个元素的数组,用于 `#[fixed(N)]` 字段
+pub fn arbitrary_fixed_vec<T: ArbitraryData>(u: &mut Unstructured, len: usize) -> Result<Vec<T>> {
+    (0..len).map(|_| T::arbitrary_data(u)).collect()
+}
+
+/// 作为 `#[nbt]` 字段能原样往返的任意值
+///
+/// 只生成转换为 NBT 时不丢失信息的值,例如 JSON 中不生成布尔值 (读回是整数)
+/// 和 null (在复合标签中会被省略)
+pub trait ArbitraryNbt: NbtConvert {
+    fn arbitrary_nbt(u: &mut Unstructured) -> Result<Self>;
+}
+
+/// 非空的复合标签键,TextComponent 会展开只有空键的复合标签
+pub fn arbitrary_nbt_key(u: &mut Unstructured) -> Result<String> {
+    let key = NbtTag::arbitrary_string(u)?;
+    Ok(if key.is_empty() { "_".to_string() } else { key })
+}
+
+/// 转换为 NBT 再转回时不变的 JSON 值: 没有布尔值、null 和小数,
+/// 数组中的元素都对应同一种标签类型
+pub fn arbitrary_nbt_json(u: &mut Unstructured, depth: u32) -> Result<serde_json::Value> {
+    let kinds = if depth < MAX_JSON_DEPTH { 5 } else { 3 };
+    let kind = u.choose_index(kinds)?;
+    arbitrary_nbt_json_kind(u, kind, depth)
+}
+
+fn arbitrary_nbt_json_kind(u: &mut Unstructured, kind: usize, depth: u32) -> Result<serde_json::Value> {
+    use serde_json::Value;
+    Ok(match kind {
+        // Int
+        0 => Value::from(u.arbitrary::<i32>()?),
+        // Long,在 i32 范围内的值会读回为 Int,翻转一个高位移出该范围
+        1 => {
+            let value: i64 = u.arbitrary()?;
+            Value::from(if i32::try_from(value).is_ok() { value ^ 1 << 40 } else { value })
+        }
+        2 => Value::String(NbtTag::arbitrary_string(u)?),
+        3 => {
+            let element = u.choose_index(if depth + 1 < MAX_JSON_DEPTH { 5 } else { 3 })?;
+            Value::Array(arbitrary_items(u, usize::MAX, |u| arbitrary_nbt_json_kind(u, element, depth + 1))?)
+        }
+        _ => Value::Object(
+            arbitrary_items(u, usize::MAX, |u| Ok((arbitrary_nbt_key(u)?, arbitrary_nbt_json(u, depth + 1)?)))?
+                .into_iter()
+                .collect(),
+        ),
+    })
+}
+
+impl ArbitraryNbt for serde_json::Value {
+    fn arbitrary_nbt(u: &mut Unstructured) -> Result<Self> {
+        arbitrary_nbt_json(u, 0)
+    }
+}
@@ -0,0 +1,273 @@
+use std::marker::PhantomData;
+
+use crate::{
+    net_types::{subdata::Subdata, var_int::VarInt},
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+
+/// 数组长度前缀的编码方式
+pub trait LengthPrefix {
+    /// 前缀能表示的最大长度
+    const MAX: usize;
+    fn write_len(w: &mut PacketWriter, len: usize);
+    /// 读取长度,长度不合法时记录解码错误并返回 0
+    fn read_len(r: &mut PacketReader) -> usize;
+}
+
+impl LengthPrefix for u8 {
+    const MAX: usize = u8::MAX as usize;
+    fn write_len(w: &mut PacketWriter, len: usize) {
+        w.u8(len as u8);
+    }
+    fn read_len(r: &mut PacketReader) -> usize {
+        let len = r.u8();
+        r.check_len(len as i64, 1)
+    }
+}
+
+impl LengthPrefix for u16 {
+    const MAX: usize = u16::MAX as usize;
+    fn write_len(w: &mut PacketWriter, len: usize) {
+        w.u16(len as u16);
+    }
+    fn read_len(r: &mut PacketReader) -> usize {
+        let len = r.u16();
+        r.check_len(len as i64, 1)
+    }
+}
+
+impl LengthPrefix for u32 {
+    const MAX: usize = u32::MAX as usize;
+    fn write_len(w: &mut PacketWriter, len: usize) {
+        w.u32(len as u32);
+    }
+    fn read_len(r: &mut PacketReader) -> usize {
+        let len = r.u32();
+        r.check_len(len as i64, 1)
+    }
+}
+
+impl LengthPrefix for VarInt {
+    const MAX: usize = i32::MAX as usize;
+    fn write_len(w: &mut PacketWriter, len: usize) {
+        w.varint(&VarInt(len as i32));
+    }
+    fn read_len(r: &mut PacketReader) -> usize {
+        r.varint_len(1)
+    }
+}
+
+/// 带长度前缀的数组
+///
+/// 长度由 `data.len()` 决定,不单独保存。`MAX` 为允许的最大元素个数,
+/// 反序列化时长度超过 `MAX` 是解码错误。
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Array<L, T, const MAX: usize = { usize::MAX }> {
+    pub data: Vec<T>,
+    prefix: PhantomData<L>,
+}
+
+impl<L, T, const MAX: usize> Subdata for Array<L, T, MAX>
+where
+    L: LengthPrefix,
+    T: Subdata,
+{
+    fn new() -> Self {
+        Self::from_vec(Vec::new())
+    }
+
+    fn serialize(&self, w: &mut PacketWriter) {
+        let len = self.data.len();
+        assert!(
+            len <= L::MAX && len <= MAX,
+            "Array length {} exceeds limit {}",
+            len,
+            L::MAX.min(MAX)
+        );
+        L::write_len(w, len);
+        for item in &self.data {
+            item.serialize(w);
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        let len = L::read_len(r);
+        if len > MAX {
+            r.fail(format!("Array length {} exceeds limit {}", len, MAX));
+        }
+        self.data = r.prefixed_items(len);
+    }
+}
+
+impl<L, T, const MAX: usize> Array<L, T, MAX> {
+    /// 从 Vec 创建 Array
+    pub fn from_vec(data: Vec<T>) -> Self {
+        Self {
+            data,
+            prefix: PhantomData,
+        }
+    }
+
+    /// 获取数据引用
+    pub fn data(&self) -> &Vec<T> {
+        &self.data
+    }
+
+    /// 获取长度
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// 检查是否为空
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// 转换为内部的 Vec
+    pub fn into_inner(self) -> Vec<T> {
+        self.data
+    }
+}
+
+impl<L, T, const MAX: usize> From<Vec<T>> for Array<L, T, MAX> {
+    fn from(data: Vec<T>) -> Self {
+        Self::from_vec(data)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<L, T: serde::Serialize, const MAX: usize> serde::Serialize for Array<L, T, MAX> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+/// 与网络格式相同,长度超过前缀和 `MAX` 的上限时拒绝导入
+#[cfg(feature = "serde")]
+impl<'de, L: LengthPrefix, T: serde::Deserialize<'de>, const MAX: usize> serde::Deserialize<'de> for Array<L, T, MAX> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = Vec::<T>::deserialize(deserializer)?;
+        let limit = L::MAX.min(MAX);
+        if data.len() > limit {
+            return Err(serde::de::Error::invalid_length(
+                data.len(),
+                &format!("an array of at most {} elements", limit).as_str(),
+            ));
+        }
+        Ok(Self::from_vec(data))
+    }
+}
+
+// 便捷的类型别名
+pub type ArrayU8<T> = Array<u8, T>;
+pub type ArrayU16<T> = Array<u16, T>;
+pub type ArrayU32<T> = Array<u32, T>;
+pub type ArrayVarInt<T> = Array<VarInt, T>;
+
+/// 固定长度的数组,没有长度前缀
+#[derive(Debug, PartialEq, Clone)]
+pub struct FixedArray<T, const N: usize>(pub [T; N]);
+
+impl<T, const N: usize> Subdata for FixedArray<T, N>
+where
+    T: Subdata,
+{
+    fn new() -> Self {
+        FixedArray(std::array::from_fn(|_| T::new()))
+    }
+
+    fn serialize(&self, w: &mut PacketWriter) {
+        for item in &self.0 {
+            item.serialize(w);
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        for item in &mut self.0 {
+            *item = r.deserialize();
+        }
+    }
+}
+
+impl<T: Subdata, const N: usize> Default for FixedArray<T, N> {
+    fn default() -> Self {
+        <Self as Subdata>::new()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, const N: usize> serde::Serialize for FixedArray<T, N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, const N: usize> serde::Deserialize<'de> for FixedArray<T, N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = Vec::<T>::deserialize(deserializer)?;
+        let len = data.len();
+        data.try_into()
+            .map(FixedArray)
+            .map_err(|_| serde::de::Error::invalid_length(len, &format!("an array of length {}", N).as_str()))
+    }
+}
+
+/// 没有长度前缀的数组,一直读到数据包结束
+///
+/// 只能作为数据包的最后一个字段
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RestArray<T>(pub Vec<T>);
+
+impl<T> Subdata for RestArray<T>
+where
+    T: Subdata,
+{
+    fn new() -> Self {
+        RestArray(Vec::new())
+    }
+
+    fn serialize(&self, w: &mut PacketWriter) {
+        for item in &self.0 {
+            item.serialize(w);
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.0 = r.rest_vec();
+    }
+}
+
+/// 元素个数不超过长度前缀和 `MAX` 的上限
+#[cfg(feature = "arbitrary")]
+impl<L, T, const MAX: usize> crate::net_types::arbitrary_data::ArbitraryData for Array<L, T, MAX>
+where
+    L: LengthPrefix,
+    T: crate::net_types::arbitrary_data::ArbitraryData,
+{
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        crate::net_types::arbitrary_data::arbitrary_vec(u, L::MAX.min(MAX)).map(Self::from_vec)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<T, const N: usize> crate::net_types::arbitrary_data::ArbitraryData for FixedArray<T, N>
+where
+    T: crate::net_types::arbitrary_data::ArbitraryData,
+{
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        let items = crate::net_types::arbitrary_data::arbitrary_fixed_vec(u, N)?;
+        Ok(FixedArray(items.try_into().unwrap_or_else(|_| unreachable!())))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<T> crate::net_types::arbitrary_data::ArbitraryData for RestArray<T>
+where
+    T: crate::net_types::arbitrary_data::ArbitraryData,
+{
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        crate::net_types::arbitrary_data::arbitrary_vec(u, usize::MAX).map(RestArray)
+    }
+}
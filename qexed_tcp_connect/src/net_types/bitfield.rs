@@ -1,9 +1,52 @@
+use std::fmt::Debug;
+
 use crate::{
     net_types::{subdata::Subdata},
     packet::{decode::PacketReader, encode::PacketWriter},
 };
-#[derive(Debug, Default, PartialEq,Clone)]
+/// 变长字节位集合,VarInt 个数前缀 + 字节数组,第 i 位在第 i / 8 个字节的第 i % 8 位
+#[derive(Default, PartialEq,Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bitfield(pub Vec<u8>);
+impl Bitfield {
+    pub fn get(&self, i: usize) -> bool {
+        self.0.get(i / 8).is_some_and(|byte| byte >> (i % 8) & 1 == 1)
+    }
+    /// 置位,需要时扩展长度
+    pub fn set(&mut self, i: usize) {
+        if self.0.len() <= i / 8 {
+            self.0.resize(i / 8 + 1, 0);
+        }
+        self.0[i / 8] |= 1 << (i % 8);
+    }
+    pub fn clear(&mut self, i: usize) {
+        if let Some(byte) = self.0.get_mut(i / 8) {
+            *byte &= !(1 << (i % 8));
+        }
+    }
+    /// 位数 (字节数 * 8)
+    pub fn len_bits(&self) -> usize {
+        self.0.len() * 8
+    }
+    /// 所有置位的下标
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len_bits()).filter(|i| self.get(*i))
+    }
+}
+impl FromIterator<usize> for Bitfield {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut bitfield = Bitfield(vec![]);
+        for i in iter {
+            bitfield.set(i);
+        }
+        bitfield
+    }
+}
+impl Debug for Bitfield {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter_ones()).finish()
+    }
+}
 impl Subdata for Bitfield {
     fn new() -> Self {
         Bitfield(vec![])
@@ -13,6 +56,13 @@ impl Subdata for Bitfield {
     }
 
     fn deserialize(&mut self, r: &mut PacketReader) {
-        *self = r.deserialize()
+        self.0 = r.deserialize();
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for Bitfield {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(Bitfield(u.arbitrary()?))
     }
-}
\ No newline at end of file
+}
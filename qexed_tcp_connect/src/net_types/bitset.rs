@@ -1,9 +1,52 @@
+use std::fmt::Debug;
+
 use crate::{
     net_types::{subdata::Subdata},
     packet::{decode::PacketReader, encode::PacketWriter},
 };
-#[derive(Debug, Default, PartialEq,Clone)]
+/// 变长位集合,VarInt 个数前缀 + long 数组,第 i 位在第 i / 64 个 long 的第 i % 64 位
+#[derive(Default, PartialEq,Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bitset(pub Vec<u64>);
+impl Bitset {
+    pub fn get(&self, i: usize) -> bool {
+        self.0.get(i / 64).is_some_and(|long| long >> (i % 64) & 1 == 1)
+    }
+    /// 置位,需要时扩展长度
+    pub fn set(&mut self, i: usize) {
+        if self.0.len() <= i / 64 {
+            self.0.resize(i / 64 + 1, 0);
+        }
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+    pub fn clear(&mut self, i: usize) {
+        if let Some(long) = self.0.get_mut(i / 64) {
+            *long &= !(1 << (i % 64));
+        }
+    }
+    /// 位数 (long 个数 * 64)
+    pub fn len_bits(&self) -> usize {
+        self.0.len() * 64
+    }
+    /// 所有置位的下标
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len_bits()).filter(|i| self.get(*i))
+    }
+}
+impl FromIterator<usize> for Bitset {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut bitset = Bitset(vec![]);
+        for i in iter {
+            bitset.set(i);
+        }
+        bitset
+    }
+}
+impl Debug for Bitset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter_ones()).finish()
+    }
+}
 impl Subdata for Bitset {
     fn new() -> Self {
         Bitset(vec![])
@@ -13,6 +56,105 @@ impl Subdata for Bitset {
     }
 
     fn deserialize(&mut self, r: &mut PacketReader) {
-        *self = r.deserialize()
+        self.0 = r.deserialize();
+    }
+}
+
+/// 定长位集合,没有长度前缀,占 N / 8 向上取整个字节
+#[derive(PartialEq, Eq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<u8>")
+)]
+pub struct FixedBitSet<const N: usize>(Vec<u8>);
+impl<const N: usize> FixedBitSet<N> {
+    pub fn new() -> Self {
+        FixedBitSet(vec![0; N.div_ceil(8)])
+    }
+    /// 原始字节,第 i 位在第 i / 8 个字节的第 i % 8 位
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+    pub fn get(&self, i: usize) -> bool {
+        i < N && self.0[i / 8] >> (i % 8) & 1 == 1
+    }
+    pub fn set(&mut self, i: usize) {
+        assert!(i < N, "Bit {} out of range for FixedBitSet<{}>", i, N);
+        self.0[i / 8] |= 1 << (i % 8);
+    }
+    pub fn clear(&mut self, i: usize) {
+        assert!(i < N, "Bit {} out of range for FixedBitSet<{}>", i, N);
+        self.0[i / 8] &= !(1 << (i % 8));
+    }
+    pub fn len_bits(&self) -> usize {
+        N
+    }
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..N).filter(|i| self.get(*i))
+    }
+}
+impl<const N: usize> Default for FixedBitSet<N> {
+    fn default() -> Self {
+        FixedBitSet::new()
+    }
+}
+impl<const N: usize> FromIterator<usize> for FixedBitSet<N> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut bitset = FixedBitSet::new();
+        for i in iter {
+            bitset.set(i);
+        }
+        bitset
+    }
+}
+/// 字节数必须是 N / 8 向上取整
+impl<const N: usize> TryFrom<Vec<u8>> for FixedBitSet<N> {
+    type Error = String;
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        if bytes.len() != N.div_ceil(8) {
+            return Err(format!(
+                "FixedBitSet<{}> needs {} bytes, got {}",
+                N,
+                N.div_ceil(8),
+                bytes.len()
+            ));
+        }
+        Ok(FixedBitSet(bytes))
+    }
+}
+impl<const N: usize> Debug for FixedBitSet<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter_ones()).finish()
+    }
+}
+impl<const N: usize> Subdata for FixedBitSet<N> {
+    fn new() -> Self {
+        FixedBitSet::new()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        for byte in &self.0 {
+            w.u8(*byte);
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        for byte in &mut self.0 {
+            *byte = r.u8();
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for Bitset {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(Bitset(Vec::arbitrary_data(u)?))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<const N: usize> crate::net_types::arbitrary_data::ArbitraryData for FixedBitSet<N> {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(FixedBitSet(crate::net_types::arbitrary_data::arbitrary_fixed_vec(u, N.div_ceil(8))?))
     }
-}
\ No newline at end of file
+}
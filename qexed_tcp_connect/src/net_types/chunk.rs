@@ -0,0 +1,439 @@
+use bytes::BytesMut;
+
+use crate::{
+    net_types::{subdata::Subdata, var_int::VarInt},
+    packet::{
+        decode::{DecodeError, PacketReader},
+        encode::PacketWriter,
+    },
+};
+
+/// 调色板容器的种类,决定条目数和各种调色板的位数范围
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContainerKind {
+    /// 16x16x16 方块状态
+    Blocks,
+    /// 4x4x4 生物群系
+    Biomes,
+}
+
+impl ContainerKind {
+    /// 条目数
+    pub fn entries(&self) -> usize {
+        match self {
+            ContainerKind::Blocks => 4096,
+            ContainerKind::Biomes => 64,
+        }
+    }
+    /// 边长
+    pub fn size(&self) -> usize {
+        match self {
+            ContainerKind::Blocks => 16,
+            ContainerKind::Biomes => 4,
+        }
+    }
+    /// 间接调色板的最大位数,超过时使用直接调色板
+    pub fn max_indirect_bits(&self) -> u8 {
+        match self {
+            ContainerKind::Blocks => 8,
+            ContainerKind::Biomes => 3,
+        }
+    }
+    /// 间接调色板的最小位数 (方块至少 4 位)
+    pub fn min_indirect_bits(&self) -> u8 {
+        match self {
+            ContainerKind::Blocks => 4,
+            ContainerKind::Biomes => 1,
+        }
+    }
+    /// 新建直接调色板时使用的默认位数
+    pub fn default_direct_bits(&self) -> u8 {
+        match self {
+            ContainerKind::Blocks => 15,
+            ContainerKind::Biomes => 6,
+        }
+    }
+}
+
+/// 调色板
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Palette {
+    /// 整个容器只有一个值,没有数据数组
+    Single(i32),
+    /// 数据数组中存的是调色板下标
+    Indirect(Vec<i32>),
+    /// 数据数组中直接存全局ID
+    Direct,
+}
+
+/// 调色板容器 (1.18 - 1.21.4 格式,数据数组带 VarInt 长度前缀)
+///
+/// 每个 long 中从低位开始存放 `64 / bits` 个条目,条目不跨 long。
+/// 字段是公开的,读取条目时会检查数据数组长度和调色板下标,不合法时返回 None
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PalettedContainer {
+    pub kind: ContainerKind,
+    /// 网络上发送的每条目位数
+    pub bits_per_entry: u8,
+    pub palette: Palette,
+    pub data: Vec<u64>,
+}
+
+impl PalettedContainer {
+    /// 所有条目都是同一个值的容器
+    pub fn single(kind: ContainerKind, value: i32) -> Self {
+        PalettedContainer {
+            kind,
+            bits_per_entry: 0,
+            palette: Palette::Single(value),
+            data: vec![],
+        }
+    }
+    /// 从全部条目的全局ID构建,选择最小的编码
+    ///
+    /// 全局ID不能为负数
+    pub fn from_values(kind: ContainerKind, values: &[i32]) -> Self {
+        assert_eq!(values.len(), kind.entries(), "Wrong number of entries for {:?}", kind);
+        if let Some(value) = values.iter().find(|v| **v < 0) {
+            panic!("Negative global ID {} in paletted container", value);
+        }
+        let mut palette: Vec<i32> = vec![];
+        for value in values {
+            if !palette.contains(value) {
+                palette.push(*value);
+            }
+        }
+        if palette.len() == 1 {
+            return PalettedContainer::single(kind, palette[0]);
+        }
+        let bits = bits_for(palette.len() as u64 - 1).max(kind.min_indirect_bits());
+        if bits <= kind.max_indirect_bits() {
+            let indices: Vec<u64> = values
+                .iter()
+                .map(|v| palette.iter().position(|p| p == v).unwrap() as u64)
+                .collect();
+            PalettedContainer {
+                kind,
+                bits_per_entry: bits,
+                data: pack(&indices, bits),
+                palette: Palette::Indirect(palette),
+            }
+        } else {
+            let max = values.iter().copied().max().unwrap_or(0) as u64;
+            let bits = bits_for(max).max(kind.default_direct_bits());
+            let ids: Vec<u64> = values.iter().map(|v| *v as u64).collect();
+            PalettedContainer {
+                kind,
+                bits_per_entry: bits,
+                data: pack(&ids, bits),
+                palette: Palette::Direct,
+            }
+        }
+    }
+    /// 实际用于存储的位数 (方块的间接调色板至少 4 位)
+    pub fn storage_bits(&self) -> u8 {
+        match self.palette {
+            Palette::Single(_) => 0,
+            Palette::Indirect(_) => self.bits_per_entry.max(self.kind.min_indirect_bits()),
+            Palette::Direct => self.bits_per_entry,
+        }
+    }
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        let size = self.kind.size();
+        assert!(x < size && y < size && z < size, "Coordinates ({}, {}, {}) out of range", x, y, z);
+        (y * size + z) * size + x
+    }
+    fn raw(&self, index: usize) -> Option<u64> {
+        let bits = self.storage_bits() as usize;
+        let per_long = 64usize.checked_div(bits).filter(|n| *n > 0)?;
+        let long = self.data.get(index / per_long)?;
+        Some((long >> ((index % per_long) * bits)) & mask(bits))
+    }
+    fn set_raw(&mut self, index: usize, value: u64) -> Option<()> {
+        let bits = self.storage_bits() as usize;
+        let per_long = 64usize.checked_div(bits).filter(|n| *n > 0)?;
+        let shift = (index % per_long) * bits;
+        let mask = mask(bits) << shift;
+        let long = self.data.get_mut(index / per_long)?;
+        *long = (*long & !mask) | ((value << shift) & mask);
+        Some(())
+    }
+    /// 按下标读取全局ID,数据数组过短或调色板下标越界时返回 None
+    pub fn get_index(&self, index: usize) -> Option<i32> {
+        match &self.palette {
+            Palette::Single(value) => Some(*value),
+            Palette::Indirect(palette) => palette.get(self.raw(index)? as usize).copied(),
+            Palette::Direct => Some(self.raw(index)? as i32),
+        }
+    }
+    /// 读取 (x, y, z) 的全局ID,坐标是容器内的相对坐标
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Option<i32> {
+        self.get_index(self.index(x, y, z))
+    }
+    /// 设置 (x, y, z) 的全局ID,调色板放不下时重新编码整个容器
+    ///
+    /// 全局ID为负数、数据数组过短,或重新编码时有读不出或为负数的条目 (见 [`PalettedContainer::validate`])
+    /// 时不修改并返回 None
+    pub fn set(&mut self, x: usize, y: usize, z: usize, value: i32) -> Option<()> {
+        if value < 0 {
+            return None;
+        }
+        let index = self.index(x, y, z);
+        let raw = match &self.palette {
+            Palette::Single(current) if *current == value => return Some(()),
+            Palette::Single(_) => None,
+            Palette::Indirect(palette) => match palette.iter().position(|p| *p == value) {
+                Some(i) => Some(i as u64),
+                None if palette.len() < 1 << self.storage_bits() => Some(palette.len() as u64),
+                None => None,
+            },
+            Palette::Direct if bits_for(value as u64) <= self.bits_per_entry => Some(value as u64),
+            Palette::Direct => None,
+        };
+        match raw {
+            Some(raw) => {
+                self.set_raw(index, raw)?;
+                if let Palette::Indirect(palette) = &mut self.palette
+                    && raw as usize == palette.len()
+                {
+                    palette.push(value);
+                }
+                Some(())
+            }
+            None => {
+                let mut values = self.values()?;
+                if values.iter().any(|v| *v < 0) {
+                    return None;
+                }
+                values[index] = value;
+                *self = PalettedContainer::from_values(self.kind, &values);
+                Some(())
+            }
+        }
+    }
+    /// 全部条目的全局ID,容器数据不合法时返回 None
+    pub fn values(&self) -> Option<Vec<i32>> {
+        (0..self.kind.entries()).map(|i| self.get_index(i)).collect()
+    }
+    /// 检查数据数组长度和调色板下标
+    pub fn validate(&self) -> Result<(), String> {
+        let bits = self.storage_bits() as usize;
+        let Some(per_long) = 64usize.checked_div(bits) else {
+            return Ok(());
+        };
+        if per_long == 0 {
+            return Err(format!("Invalid paletted container: {} bits per entry", bits));
+        }
+        let expected = self.kind.entries().div_ceil(per_long);
+        if self.data.len() != expected {
+            return Err(format!(
+                "Invalid paletted container: {} longs for {} bits per entry, expected {}",
+                self.data.len(),
+                bits,
+                expected
+            ));
+        }
+        if let Palette::Indirect(palette) = &self.palette
+            && let Some(index) = (0..self.kind.entries()).find(|i| self.get_index(*i).is_none())
+        {
+            return Err(format!(
+                "Palette index {} at entry {} out of range for {} palette entries",
+                self.raw(index).unwrap_or_default(),
+                index,
+                palette.len()
+            ));
+        }
+        Ok(())
+    }
+    /// 读取指定种类的容器
+    pub fn read(r: &mut PacketReader, kind: ContainerKind) -> Self {
+        let bits_per_entry = r.u8();
+        let palette = if bits_per_entry == 0 {
+            Palette::Single(r.varint().0)
+        } else if bits_per_entry <= kind.max_indirect_bits() {
+            let len = r.varint_len(1);
+            Palette::Indirect(r.prefixed_items::<VarInt>(len).into_iter().map(|id| id.0).collect())
+        } else {
+            Palette::Direct
+        };
+        let len = r.varint_len(8);
+        let data = r.prefixed_items(len);
+        let container = PalettedContainer {
+            kind,
+            bits_per_entry,
+            palette,
+            data,
+        };
+        if !r.failed()
+            && let Err(e) = container.validate()
+        {
+            r.fail(e);
+        }
+        container
+    }
+    pub fn write(&self, w: &mut PacketWriter) {
+        w.u8(self.bits_per_entry);
+        match &self.palette {
+            Palette::Single(value) => w.varint(&VarInt(*value)),
+            Palette::Indirect(palette) => {
+                w.varint(&VarInt(palette.len() as i32));
+                for value in palette {
+                    w.varint(&VarInt(*value));
+                }
+            }
+            Palette::Direct => {}
+        }
+        w.varint(&VarInt(self.data.len() as i32));
+        for long in &self.data {
+            w.u64(*long);
+        }
+    }
+}
+
+/// 低 bits 位的掩码,bits 为 64 时不溢出
+fn mask(bits: usize) -> u64 {
+    u64::MAX >> (64 - bits)
+}
+
+/// 表示 `0..=max` 需要的位数
+fn bits_for(max: u64) -> u8 {
+    (64 - max.leading_zeros()).max(1) as u8
+}
+
+fn pack(values: &[u64], bits: u8) -> Vec<u64> {
+    let bits = bits as usize;
+    let per_long = 64 / bits;
+    let mut data = vec![0u64; values.len().div_ceil(per_long)];
+    for (i, value) in values.iter().enumerate() {
+        data[i / per_long] |= (value & mask(bits)) << ((i % per_long) * bits);
+    }
+    data
+}
+
+/// 区块段 (16x16x16)
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkSection {
+    /// 非空气方块数
+    pub block_count: i16,
+    pub blocks: PalettedContainer,
+    pub biomes: PalettedContainer,
+}
+
+impl ChunkSection {
+    /// 读取区块数据包中的全部区块段
+    pub fn read_all(data: &[u8]) -> Result<Vec<ChunkSection>, DecodeError> {
+        let mut data = data;
+        let mut r = PacketReader::new(Box::new(&mut data));
+        let sections = r.rest_vec();
+        r.finish()?;
+        Ok(sections)
+    }
+    /// 编码为区块数据包中的数据
+    pub fn write_all(sections: &[ChunkSection]) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        let mut w = PacketWriter::new(&mut buf);
+        for section in sections {
+            section.serialize(&mut w);
+        }
+        buf.to_vec()
+    }
+    pub fn block(&self, x: usize, y: usize, z: usize) -> Option<i32> {
+        self.blocks.get(x, y, z)
+    }
+    /// 设置方块状态,并按 0 为空气更新非空气方块数
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, state: i32) -> Option<()> {
+        let old = self.blocks.get(x, y, z)?;
+        self.blocks.set(x, y, z, state)?;
+        match (old == 0, state == 0) {
+            (true, false) => self.block_count += 1,
+            (false, true) => self.block_count -= 1,
+            _ => {}
+        }
+        Some(())
+    }
+    /// 按给定的空气判断重新计算非空气方块数
+    pub fn recount_blocks(&mut self, is_air: impl Fn(i32) -> bool) -> Option<()> {
+        self.block_count = self.blocks.values()?.into_iter().filter(|s| !is_air(*s)).count() as i16;
+        Some(())
+    }
+    /// 坐标为 4x4x4 生物群系格子的坐标
+    pub fn biome(&self, x: usize, y: usize, z: usize) -> Option<i32> {
+        self.biomes.get(x, y, z)
+    }
+    pub fn set_biome(&mut self, x: usize, y: usize, z: usize, biome: i32) -> Option<()> {
+        self.biomes.set(x, y, z, biome)
+    }
+}
+
+impl Subdata for ChunkSection {
+    fn new() -> Self {
+        ChunkSection {
+            block_count: 0,
+            blocks: PalettedContainer::single(ContainerKind::Blocks, 0),
+            biomes: PalettedContainer::single(ContainerKind::Biomes, 0),
+        }
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.i16(self.block_count);
+        self.blocks.write(w);
+        self.biomes.write(w);
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.block_count = r.i16();
+        self.blocks = PalettedContainer::read(r, ContainerKind::Blocks);
+        self.biomes = PalettedContainer::read(r, ContainerKind::Biomes);
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl PalettedContainer {
+    /// 生成能通过 [`PalettedContainer::validate`] 的容器,间接调色板的下标都在范围内
+    fn arbitrary_container(u: &mut arbitrary::Unstructured, kind: ContainerKind) -> arbitrary::Result<Self> {
+        let max_indirect = kind.max_indirect_bits();
+        Ok(match u.choose_index(3)? {
+            0 => PalettedContainer::single(kind, u.arbitrary()?),
+            1 => {
+                let bits_per_entry = u.int_in_range(1..=max_indirect)?;
+                let storage = bits_per_entry.max(kind.min_indirect_bits());
+                let mut palette = vec![u.arbitrary()?];
+                palette.extend(crate::net_types::arbitrary_data::arbitrary_items(u, (1 << storage) - 1, |u| {
+                    u.arbitrary::<i32>()
+                })?);
+                let last = palette.len() as u64 - 1;
+                let indices = (0..kind.entries()).map(|_| u.int_in_range(0..=last)).collect::<arbitrary::Result<Vec<_>>>()?;
+                PalettedContainer {
+                    kind,
+                    bits_per_entry,
+                    palette: Palette::Indirect(palette),
+                    data: pack(&indices, storage),
+                }
+            }
+            _ => {
+                let bits_per_entry = u.int_in_range(max_indirect + 1..=64)?;
+                let len = kind.entries().div_ceil(64 / bits_per_entry as usize);
+                PalettedContainer {
+                    kind,
+                    bits_per_entry,
+                    palette: Palette::Direct,
+                    data: (0..len).map(|_| u.arbitrary()).collect::<arbitrary::Result<_>>()?,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for ChunkSection {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(ChunkSection {
+            block_count: u.arbitrary()?,
+            blocks: PalettedContainer::arbitrary_container(u, ContainerKind::Blocks)?,
+            biomes: PalettedContainer::arbitrary_container(u, ContainerKind::Biomes)?,
+        })
+    }
+}
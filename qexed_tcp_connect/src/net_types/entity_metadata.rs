@@ -0,0 +1,343 @@
+use crate::{
+    net_types::{
+        identifier::Identifier, nbt::Nbt, particle::Particle, position::Position, slot::Slot,
+        subdata::Subdata, var_int::VarInt, var_long::VarLong,
+    },
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+
+/// 元数据列表的结束标记
+pub const TERMINATOR: u8 = 0xFF;
+
+/// 实体元数据 (1.20.5, 协议 766)
+///
+/// (下标, 类型, 值) 的列表,以下标 0xFF 结束
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntityMetadata(pub Vec<MetadataEntry>);
+
+/// 一条实体元数据
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetadataEntry {
+    pub index: u8,
+    pub value: MetadataValue,
+}
+
+/// 实体元数据的值,变体顺序与类型ID一致
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MetadataValue {
+    Byte(i8),
+    VarInt(i32),
+    VarLong(i64),
+    Float(f32),
+    String(String),
+    /// NBT 格式的文本组件
+    TextComponent(Nbt),
+    OptionalTextComponent(Option<Nbt>),
+    Slot(Slot),
+    Boolean(bool),
+    Rotations([f32; 3]),
+    Position(Position),
+    OptionalPosition(Option<Position>),
+    Direction(i32),
+    OptionalUuid(Option<uuid::Uuid>),
+    BlockState(i32),
+    /// 0 表示没有方块状态
+    OptionalBlockState(Option<i32>),
+    Nbt(Nbt),
+    Particle(Particle),
+    Particles(Vec<Particle>),
+    VillagerData {
+        kind: i32,
+        profession: i32,
+        level: i32,
+    },
+    /// 编码为值 + 1,0 表示没有值
+    OptionalVarInt(Option<i32>),
+    Pose(i32),
+    CatVariant(i32),
+    WolfVariant(i32),
+    FrogVariant(i32),
+    /// 维度 + 坐标
+    OptionalGlobalPosition(Option<(Identifier, Position)>),
+    PaintingVariant(i32),
+    SnifferState(i32),
+    ArmadilloState(i32),
+    Vector3([f32; 3]),
+    Quaternion([f32; 4]),
+    /// 未知类型: 无法知道值在哪里结束,之后的数据 (含结束标记) 原样保留
+    Unknown { type_id: i32, data: Vec<u8> },
+}
+
+impl MetadataValue {
+    /// 协议中的类型ID
+    pub fn type_id(&self) -> i32 {
+        match self {
+            MetadataValue::Byte(_) => 0,
+            MetadataValue::VarInt(_) => 1,
+            MetadataValue::VarLong(_) => 2,
+            MetadataValue::Float(_) => 3,
+            MetadataValue::String(_) => 4,
+            MetadataValue::TextComponent(_) => 5,
+            MetadataValue::OptionalTextComponent(_) => 6,
+            MetadataValue::Slot(_) => 7,
+            MetadataValue::Boolean(_) => 8,
+            MetadataValue::Rotations(_) => 9,
+            MetadataValue::Position(_) => 10,
+            MetadataValue::OptionalPosition(_) => 11,
+            MetadataValue::Direction(_) => 12,
+            MetadataValue::OptionalUuid(_) => 13,
+            MetadataValue::BlockState(_) => 14,
+            MetadataValue::OptionalBlockState(_) => 15,
+            MetadataValue::Nbt(_) => 16,
+            MetadataValue::Particle(_) => 17,
+            MetadataValue::Particles(_) => 18,
+            MetadataValue::VillagerData { .. } => 19,
+            MetadataValue::OptionalVarInt(_) => 20,
+            MetadataValue::Pose(_) => 21,
+            MetadataValue::CatVariant(_) => 22,
+            MetadataValue::WolfVariant(_) => 23,
+            MetadataValue::FrogVariant(_) => 24,
+            MetadataValue::OptionalGlobalPosition(_) => 25,
+            MetadataValue::PaintingVariant(_) => 26,
+            MetadataValue::SnifferState(_) => 27,
+            MetadataValue::ArmadilloState(_) => 28,
+            MetadataValue::Vector3(_) => 29,
+            MetadataValue::Quaternion(_) => 30,
+            MetadataValue::Unknown { type_id, .. } => *type_id,
+        }
+    }
+    /// 读取指定类型的值,类型未知时返回 `None`
+    pub fn read(r: &mut PacketReader, type_id: i32) -> Option<MetadataValue> {
+        let value = match type_id {
+            0 => MetadataValue::Byte(r.i8()),
+            1 => MetadataValue::VarInt(r.varint().0),
+            2 => MetadataValue::VarLong(r.varlong().0),
+            3 => MetadataValue::Float(r.f32()),
+            4 => MetadataValue::String(r.string()),
+            5 => MetadataValue::TextComponent(r.deserialize()),
+            6 => MetadataValue::OptionalTextComponent(r.option()),
+            7 => MetadataValue::Slot(r.deserialize()),
+            8 => MetadataValue::Boolean(r.bool()),
+            9 => MetadataValue::Rotations([r.f32(), r.f32(), r.f32()]),
+            10 => MetadataValue::Position(r.deserialize()),
+            11 => MetadataValue::OptionalPosition(r.option()),
+            12 => MetadataValue::Direction(r.varint().0),
+            13 => MetadataValue::OptionalUuid(if r.bool() { Some(r.uuid()) } else { None }),
+            14 => MetadataValue::BlockState(r.varint().0),
+            15 => MetadataValue::OptionalBlockState(match r.varint().0 {
+                0 => None,
+                state => Some(state),
+            }),
+            16 => MetadataValue::Nbt(r.deserialize()),
+            17 => MetadataValue::Particle(r.deserialize()),
+            18 => MetadataValue::Particles(r.vec()),
+            19 => MetadataValue::VillagerData {
+                kind: r.varint().0,
+                profession: r.varint().0,
+                level: r.varint().0,
+            },
+            20 => MetadataValue::OptionalVarInt(match r.varint().0 {
+                0 => None,
+                value => Some(value.wrapping_sub(1)),
+            }),
+            21 => MetadataValue::Pose(r.varint().0),
+            22 => MetadataValue::CatVariant(r.varint().0),
+            23 => MetadataValue::WolfVariant(r.varint().0),
+            24 => MetadataValue::FrogVariant(r.varint().0),
+            25 => MetadataValue::OptionalGlobalPosition(if r.bool() {
+                Some((r.deserialize(), r.deserialize()))
+            } else {
+                None
+            }),
+            26 => MetadataValue::PaintingVariant(r.varint().0),
+            27 => MetadataValue::SnifferState(r.varint().0),
+            28 => MetadataValue::ArmadilloState(r.varint().0),
+            29 => MetadataValue::Vector3([r.f32(), r.f32(), r.f32()]),
+            30 => MetadataValue::Quaternion([r.f32(), r.f32(), r.f32(), r.f32()]),
+            _ => return None,
+        };
+        Some(value)
+    }
+    /// 写入值 (不含类型ID)
+    pub fn write(&self, w: &mut PacketWriter) {
+        match self {
+            MetadataValue::Byte(value) => w.i8(*value),
+            MetadataValue::VarLong(value) => w.varlong(&VarLong(*value)),
+            MetadataValue::Float(value) => w.f32(*value),
+            MetadataValue::String(value) => w.string(value),
+            MetadataValue::TextComponent(value) | MetadataValue::Nbt(value) => value.serialize(w),
+            MetadataValue::OptionalTextComponent(value) => w.option(value.as_ref()),
+            MetadataValue::Slot(value) => value.serialize(w),
+            MetadataValue::Boolean(value) => w.bool(*value),
+            MetadataValue::Rotations(values) | MetadataValue::Vector3(values) => {
+                for value in values {
+                    w.f32(*value);
+                }
+            }
+            MetadataValue::Quaternion(values) => {
+                for value in values {
+                    w.f32(*value);
+                }
+            }
+            MetadataValue::Unknown { data, .. } => w.bytes(data),
+            MetadataValue::Position(value) => value.serialize(w),
+            MetadataValue::OptionalPosition(value) => w.option(value.as_ref()),
+            MetadataValue::OptionalUuid(value) => {
+                w.bool(value.is_some());
+                if let Some(value) = value {
+                    w.uuid(value);
+                }
+            }
+            MetadataValue::OptionalBlockState(value) => w.varint(&VarInt(value.unwrap_or(0))),
+            MetadataValue::Particle(value) => value.serialize(w),
+            MetadataValue::Particles(values) => w.vec(values),
+            MetadataValue::VillagerData {
+                kind,
+                profession,
+                level,
+            } => {
+                w.varint(&VarInt(*kind));
+                w.varint(&VarInt(*profession));
+                w.varint(&VarInt(*level));
+            }
+            MetadataValue::OptionalVarInt(value) => w.varint(&VarInt(value.map_or(0, |v| v.wrapping_add(1)))),
+            MetadataValue::OptionalGlobalPosition(value) => {
+                w.bool(value.is_some());
+                if let Some((dimension, position)) = value {
+                    dimension.serialize(w);
+                    position.serialize(w);
+                }
+            }
+            MetadataValue::VarInt(value)
+            | MetadataValue::Direction(value)
+            | MetadataValue::BlockState(value)
+            | MetadataValue::Pose(value)
+            | MetadataValue::CatVariant(value)
+            | MetadataValue::WolfVariant(value)
+            | MetadataValue::FrogVariant(value)
+            | MetadataValue::PaintingVariant(value)
+            | MetadataValue::SnifferState(value)
+            | MetadataValue::ArmadilloState(value) => w.varint(&VarInt(*value)),
+        }
+    }
+}
+
+impl EntityMetadata {
+    pub fn get(&self, index: u8) -> Option<&MetadataValue> {
+        self.0.iter().find(|e| e.index == index).map(|e| &e.value)
+    }
+    /// 设置下标的值,已存在时替换
+    pub fn set(&mut self, index: u8, value: MetadataValue) {
+        match self.0.iter_mut().find(|e| e.index == index) {
+            Some(entry) => entry.value = value,
+            None => self.0.push(MetadataEntry { index, value }),
+        }
+    }
+}
+
+impl Subdata for EntityMetadata {
+    fn new() -> Self {
+        EntityMetadata(vec![])
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        for entry in &self.0 {
+            w.u8(entry.index);
+            w.varint(&VarInt(entry.value.type_id()));
+            entry.value.write(w);
+            if let MetadataValue::Unknown { .. } = entry.value {
+                // 原始数据已包含结束标记
+                return;
+            }
+        }
+        w.u8(TERMINATOR);
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.0.clear();
+        loop {
+            let index = r.u8();
+            if index == TERMINATOR || r.failed() {
+                break;
+            }
+            let type_id = r.varint().0;
+            let value = match MetadataValue::read(r, type_id) {
+                Some(value) => value,
+                None => {
+                    let data = r.byte_all();
+                    r.stop(format!("Unknown entity metadata type {} at index {}", type_id, index));
+                    MetadataValue::Unknown { type_id, data }
+                }
+            };
+            self.0.push(MetadataEntry { index, value });
+            if r.failed() {
+                break;
+            }
+        }
+    }
+}
+
+/// 下标不是结束标记,不生成未知类型
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for EntityMetadata {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        let entries = crate::net_types::arbitrary_data::arbitrary_items(u, usize::MAX, |u| {
+            Ok(MetadataEntry {
+                index: u.int_in_range(0..=TERMINATOR - 1)?,
+                value: MetadataValue::arbitrary_data(u)?,
+            })
+        })?;
+        Ok(EntityMetadata(entries))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for MetadataValue {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0..=30)? {
+            0 => MetadataValue::Byte(u.arbitrary()?),
+            1 => MetadataValue::VarInt(u.arbitrary()?),
+            2 => MetadataValue::VarLong(u.arbitrary()?),
+            3 => MetadataValue::Float(f32::arbitrary_data(u)?),
+            4 => MetadataValue::String(u.arbitrary()?),
+            5 => MetadataValue::TextComponent(Nbt::arbitrary_data(u)?),
+            6 => MetadataValue::OptionalTextComponent(Option::arbitrary_data(u)?),
+            7 => MetadataValue::Slot(Slot::arbitrary_data(u)?),
+            8 => MetadataValue::Boolean(u.arbitrary()?),
+            9 => MetadataValue::Rotations(<[f32; 3]>::arbitrary_data(u)?),
+            10 => MetadataValue::Position(Position::arbitrary_data(u)?),
+            11 => MetadataValue::OptionalPosition(Option::arbitrary_data(u)?),
+            12 => MetadataValue::Direction(u.arbitrary()?),
+            13 => MetadataValue::OptionalUuid(Option::arbitrary_data(u)?),
+            14 => MetadataValue::BlockState(u.arbitrary()?),
+            // 0 读回为 None
+            15 => MetadataValue::OptionalBlockState(u.arbitrary::<Option<i32>>()?.filter(|state| *state != 0)),
+            16 => MetadataValue::Nbt(Nbt::arbitrary_data(u)?),
+            17 => MetadataValue::Particle(Particle::arbitrary_data(u)?),
+            18 => MetadataValue::Particles(Vec::arbitrary_data(u)?),
+            19 => MetadataValue::VillagerData {
+                kind: u.arbitrary()?,
+                profession: u.arbitrary()?,
+                level: u.arbitrary()?,
+            },
+            // -1 编码为 0,读回为 None
+            20 => MetadataValue::OptionalVarInt(u.arbitrary::<Option<i32>>()?.filter(|value| *value != -1)),
+            21 => MetadataValue::Pose(u.arbitrary()?),
+            22 => MetadataValue::CatVariant(u.arbitrary()?),
+            23 => MetadataValue::WolfVariant(u.arbitrary()?),
+            24 => MetadataValue::FrogVariant(u.arbitrary()?),
+            25 => MetadataValue::OptionalGlobalPosition(if u.arbitrary()? {
+                Some((Identifier::arbitrary_data(u)?, Position::arbitrary_data(u)?))
+            } else {
+                None
+            }),
+            26 => MetadataValue::PaintingVariant(u.arbitrary()?),
+            27 => MetadataValue::SnifferState(u.arbitrary()?),
+            28 => MetadataValue::ArmadilloState(u.arbitrary()?),
+            29 => MetadataValue::Vector3(<[f32; 3]>::arbitrary_data(u)?),
+            _ => MetadataValue::Quaternion(<[f32; 4]>::arbitrary_data(u)?),
+        })
+    }
+}
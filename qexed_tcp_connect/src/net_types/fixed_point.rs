@@ -0,0 +1,75 @@
+use crate::{
+    net_types::subdata::Subdata,
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+
+/// 定点数的小数位数 (旧版本协议使用 5 位小数)
+pub const FRACTION_BITS: u32 = 5;
+
+/// 32 位定点数
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FixedI32(pub i32);
+
+/// 8 位定点数
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FixedI8(pub i8);
+
+impl FixedI32 {
+    pub fn from_f64(value: f64) -> Self {
+        FixedI32((value * (1 << FRACTION_BITS) as f64) as i32)
+    }
+    pub fn to_f64(&self) -> f64 {
+        self.0 as f64 / (1 << FRACTION_BITS) as f64
+    }
+}
+
+impl FixedI8 {
+    pub fn from_f64(value: f64) -> Self {
+        FixedI8((value * (1 << FRACTION_BITS) as f64) as i8)
+    }
+    pub fn to_f64(&self) -> f64 {
+        self.0 as f64 / (1 << FRACTION_BITS) as f64
+    }
+}
+
+impl Subdata for FixedI32 {
+    fn new() -> Self {
+        FixedI32(0)
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.i32(self.0);
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.0 = r.i32();
+    }
+}
+
+impl Subdata for FixedI8 {
+    fn new() -> Self {
+        FixedI8(0)
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.i8(self.0);
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.0 = r.i8();
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for FixedI32 {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(FixedI32(u.arbitrary()?))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for FixedI8 {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(FixedI8(u.arbitrary()?))
+    }
+}
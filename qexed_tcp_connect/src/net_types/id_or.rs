@@ -0,0 +1,59 @@
+use crate::{
+    net_types::{subdata::Subdata, var_int::VarInt},
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+/// 注册表ID或内联数据
+///
+/// VarInt 为 0 时后跟内联数据,否则为注册表ID + 1
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IdOr<T> {
+    Id(i32),
+    Value(T),
+}
+impl<T> Default for IdOr<T> {
+    fn default() -> Self {
+        IdOr::Id(0)
+    }
+}
+impl<T> Subdata for IdOr<T>
+where
+    T: Subdata,
+{
+    fn new() -> Self {
+        IdOr::Id(0)
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        match self {
+            IdOr::Id(id) => w.varint(&VarInt(id + 1)),
+            IdOr::Value(value) => {
+                w.varint(&VarInt(0));
+                value.serialize(w);
+            }
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        let id = r.varint().0;
+        *self = if id == 0 {
+            IdOr::Value(r.deserialize())
+        } else {
+            IdOr::Id(id - 1)
+        };
+    }
+}
+
+/// 注册表ID加 1 后写入,只生成非负且不溢出的ID
+#[cfg(feature = "arbitrary")]
+impl<T> crate::net_types::arbitrary_data::ArbitraryData for IdOr<T>
+where
+    T: crate::net_types::arbitrary_data::ArbitraryData,
+{
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(if u.arbitrary()? {
+            IdOr::Value(T::arbitrary_data(u)?)
+        } else {
+            IdOr::Id(u.int_in_range(0..=i32::MAX - 1)?)
+        })
+    }
+}
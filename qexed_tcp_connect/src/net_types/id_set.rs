@@ -0,0 +1,59 @@
+use crate::{
+    net_types::{identifier::Identifier, subdata::Subdata, var_int::VarInt},
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+/// 注册表ID集合
+///
+/// VarInt 为 0 时后跟标签名,否则为 ID 个数 + 1,后跟对应个数的 VarInt ID
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IdSet {
+    Tag(Identifier),
+    Ids(Vec<i32>),
+}
+impl Default for IdSet {
+    fn default() -> Self {
+        IdSet::Ids(vec![])
+    }
+}
+impl Subdata for IdSet {
+    fn new() -> Self {
+        IdSet::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        match self {
+            IdSet::Tag(tag) => {
+                w.varint(&VarInt(0));
+                tag.serialize(w);
+            }
+            IdSet::Ids(ids) => {
+                w.varint(&VarInt(ids.len() as i32 + 1));
+                for id in ids {
+                    w.varint(&VarInt(*id));
+                }
+            }
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        let kind = r.varint().0;
+        *self = if kind == 0 {
+            IdSet::Tag(r.deserialize())
+        } else {
+            // 个数来自网络,按剩余数据限制预分配
+            let len = r.check_len(kind as i64 - 1, 1);
+            IdSet::Ids(r.prefixed_items::<VarInt>(len).into_iter().map(|id| id.0).collect())
+        };
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for IdSet {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(if u.arbitrary()? {
+            IdSet::Tag(Identifier::arbitrary_data(u)?)
+        } else {
+            IdSet::Ids(Vec::arbitrary_data(u)?)
+        })
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{
+    net_types::subdata::Subdata,
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+
+/// 默认命名空间
+pub const DEFAULT_NAMESPACE: &str = "minecraft";
+
+/// 命名空间标识符,例如 `minecraft:stone`
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Identifier {
+    pub namespace: String,
+    pub path: String,
+}
+
+impl Default for Identifier {
+    fn default() -> Self {
+        Identifier {
+            namespace: DEFAULT_NAMESPACE.to_owned(),
+            path: String::new(),
+        }
+    }
+}
+
+impl Identifier {
+    /// 创建标识符并校验字符
+    pub fn new(namespace: impl Into<String>, path: impl Into<String>) -> anyhow::Result<Self> {
+        let identifier = Identifier {
+            namespace: namespace.into(),
+            path: path.into(),
+        };
+        identifier.validate()?;
+        Ok(identifier)
+    }
+    /// `minecraft` 命名空间下的标识符
+    pub fn minecraft(path: impl Into<String>) -> anyhow::Result<Self> {
+        Identifier::new(DEFAULT_NAMESPACE, path)
+    }
+    /// 解析 `namespace:path`,没有命名空间时使用 `minecraft`
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        let identifier = Identifier::parse_unchecked(value);
+        identifier.validate()?;
+        Ok(identifier)
+    }
+    /// 与 [`Identifier::parse`] 相同,但不校验字符
+    ///
+    /// 从网络读取时使用,非法的标识符原样保留,由 [`Identifier::validate`] 报告
+    pub fn parse_unchecked(value: &str) -> Self {
+        match value.split_once(':') {
+            Some((namespace, path)) => Identifier {
+                namespace: namespace.to_owned(),
+                path: path.to_owned(),
+            },
+            None => Identifier {
+                namespace: DEFAULT_NAMESPACE.to_owned(),
+                path: value.to_owned(),
+            },
+        }
+    }
+    /// 命名空间只能包含 `a-z0-9_.-`,路径还可以包含 `/`
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(c) = self.namespace.chars().find(|c| !is_namespace_char(*c)) {
+            anyhow::bail!("Invalid character {:?} in identifier namespace: {}", c, self);
+        }
+        if let Some(c) = self.path.chars().find(|c| !is_path_char(*c)) {
+            anyhow::bail!("Invalid character {:?} in identifier path: {}", c, self);
+        }
+        Ok(())
+    }
+}
+
+fn is_namespace_char(c: char) -> bool {
+    matches!(c, 'a'..='z' | '0'..='9' | '_' | '-' | '.')
+}
+
+fn is_path_char(c: char) -> bool {
+    is_namespace_char(c) || c == '/'
+}
+
+impl Display for Identifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
+    }
+}
+
+impl FromStr for Identifier {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Identifier::parse(s)
+    }
+}
+
+/// 启用 serde 特性时序列化为 `namespace:path` 字符串
+#[cfg(feature = "serde")]
+impl serde::Serialize for Identifier {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Identifier {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+        Identifier::parse(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Subdata for Identifier {
+    fn new() -> Self {
+        Identifier::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.string(&self.to_string());
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        // 网络数据中的非法字符不是解码错误,需要时由调用方校验
+        *self = Identifier::parse_unchecked(&r.string());
+    }
+}
+
+/// 只使用命名空间和路径允许的字符
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for Identifier {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        const NAMESPACE_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789_-.";
+        const PATH_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789_-./";
+        let word = |u: &mut arbitrary::Unstructured, chars: &[u8]| {
+            crate::net_types::arbitrary_data::arbitrary_items(u, usize::MAX, |u| Ok(*u.choose(chars)? as char))
+                .map(|chars| chars.into_iter().collect::<String>())
+        };
+        let namespace = if u.arbitrary()? {
+            word(u, NAMESPACE_CHARS)?
+        } else {
+            DEFAULT_NAMESPACE.to_owned()
+        };
+        Ok(Identifier {
+            namespace,
+            path: word(u, PATH_CHARS)?,
+        })
+    }
+}
@@ -3,6 +3,7 @@ use crate::{
     packet::{decode::PacketReader, encode::PacketWriter},
 };
 #[derive(Debug, Default, PartialEq,Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Light {
     pub sky_light_mask: Bitset,
     pub block_light_mask: Bitset,
@@ -40,3 +41,17 @@ impl Subdata for Light {
         self.block_light_arrays = r.deserialize();
     }
 }
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for Light {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(Light {
+            sky_light_mask: Bitset::arbitrary_data(u)?,
+            block_light_mask: Bitset::arbitrary_data(u)?,
+            empty_sky_light_mask: Bitset::arbitrary_data(u)?,
+            empty_block_light_mask: Bitset::arbitrary_data(u)?,
+            sky_light_arrays: Vec::arbitrary_data(u)?,
+            block_light_arrays: Vec::arbitrary_data(u)?,
+        })
+    }
+}
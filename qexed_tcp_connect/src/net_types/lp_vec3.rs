@@ -0,0 +1,91 @@
+use crate::{
+    net_types::{subdata::Subdata, var_int::VarInt},
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+
+/// 绝对值小于此值的向量编码为单个 0 字节
+const ZERO_THRESHOLD: f64 = 3.051944088384301E-5;
+/// 分量的最大绝对值
+const MAX_VALUE: f64 = 1.7179869183E10;
+/// 每个分量 15 位
+const COMPONENT_MAX: f64 = 32766.0;
+
+/// 低精度三维向量 (1.21.9+ 实体速度)
+///
+/// 三个分量各 15 位,共用一个缩放系数,总共 6 字节,缩放系数过大时后跟一个 VarInt
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LpVec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl LpVec3 {
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        LpVec3 { x, y, z }
+    }
+}
+
+fn sanitize(value: f64) -> f64 {
+    if value.is_nan() {
+        0.0
+    } else {
+        value.clamp(-MAX_VALUE, MAX_VALUE)
+    }
+}
+
+fn pack(value: f64) -> u64 {
+    ((value * 0.5 + 0.5) * COMPONENT_MAX).round() as u64
+}
+
+fn unpack(value: u64) -> f64 {
+    ((value & 0x7FFF) as f64).min(COMPONENT_MAX) * 2.0 / COMPONENT_MAX - 1.0
+}
+
+impl Subdata for LpVec3 {
+    fn new() -> Self {
+        LpVec3::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        let x = sanitize(self.x);
+        let y = sanitize(self.y);
+        let z = sanitize(self.z);
+        let max = x.abs().max(y.abs()).max(z.abs());
+        if max < ZERO_THRESHOLD {
+            w.u8(0);
+            return;
+        }
+        let scale = max.ceil() as u64;
+        let big = scale & 3 != scale;
+        let flags = if big { (scale & 3) | 4 } else { scale };
+        let packed = flags | pack(x / scale as f64) << 3 | pack(y / scale as f64) << 18 | pack(z / scale as f64) << 33;
+        w.u8(packed as u8);
+        w.u8((packed >> 8) as u8);
+        w.u32((packed >> 16) as u32);
+        if big {
+            w.varint(&VarInt((scale >> 2) as i32));
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        let a = r.u8() as u64;
+        if a == 0 {
+            *self = LpVec3::default();
+            return;
+        }
+        let b = r.u8() as u64;
+        let c = r.u32() as u64;
+        let packed = c << 16 | b << 8 | a;
+        let mut scale = a & 3;
+        if a & 4 == 4 {
+            scale |= (r.varint().0 as u32 as u64) << 2;
+        }
+        let scale = scale as f64;
+        *self = LpVec3 {
+            x: unpack(packed >> 3) * scale,
+            y: unpack(packed >> 18) * scale,
+            z: unpack(packed >> 33) * scale,
+        };
+    }
+}
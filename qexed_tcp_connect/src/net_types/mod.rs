@@ -4,5 +4,22 @@ pub mod subdata;
 pub mod light;
 pub mod bitset;
 pub mod rest_buffer;
-pub mod arrey;
-pub mod bitfield;
\ No newline at end of file
+pub mod array;
+pub mod bitfield;
+pub mod text_component;
+pub mod nbt;
+pub mod var_long;
+pub mod position;
+pub mod angle;
+pub mod identifier;
+pub mod fixed_point;
+pub mod lp_vec3;
+pub mod id_or;
+pub mod id_set;
+pub mod teleport_flags;
+pub mod slot;
+pub mod chunk;
+pub mod particle;
+pub mod entity_metadata;
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary_data;
\ No newline at end of file
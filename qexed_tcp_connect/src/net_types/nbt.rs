@@ -0,0 +1,845 @@
+use std::fmt::{self, Display, Write};
+
+use crate::{
+    net_types::subdata::Subdata,
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+
+/// 最大嵌套深度,防止恶意数据导致栈溢出
+const MAX_DEPTH: usize = 512;
+
+pub const TAG_END: u8 = 0;
+pub const TAG_BYTE: u8 = 1;
+pub const TAG_SHORT: u8 = 2;
+pub const TAG_INT: u8 = 3;
+pub const TAG_LONG: u8 = 4;
+pub const TAG_FLOAT: u8 = 5;
+pub const TAG_DOUBLE: u8 = 6;
+pub const TAG_BYTE_ARRAY: u8 = 7;
+pub const TAG_STRING: u8 = 8;
+pub const TAG_LIST: u8 = 9;
+pub const TAG_COMPOUND: u8 = 10;
+pub const TAG_INT_ARRAY: u8 = 11;
+pub const TAG_LONG_ARRAY: u8 = 12;
+
+/// NBT 标签
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NbtTag {
+    /// 空标签,作为根标签时表示没有数据
+    #[default]
+    End,
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(NbtList),
+    Compound(NbtCompound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+/// NBT 列表,保留元素类型以便空列表也能原样写回
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NbtList {
+    pub element_type: u8,
+    pub items: Vec<NbtTag>,
+}
+
+/// NBT 复合标签,保留键的顺序以便原样写回
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NbtCompound(pub Vec<(String, NbtTag)>);
+
+/// 网络 NBT (1.20.2+),根标签没有名字
+#[derive(Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nbt(pub NbtTag);
+
+/// 带名字根标签的 NBT (1.20.2 之前的网络格式和文件格式)
+#[derive(Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NamedNbt {
+    pub name: String,
+    pub root: NbtTag,
+}
+
+impl NbtTag {
+    /// 标签类型ID
+    pub fn tag_type(&self) -> u8 {
+        match self {
+            NbtTag::End => TAG_END,
+            NbtTag::Byte(_) => TAG_BYTE,
+            NbtTag::Short(_) => TAG_SHORT,
+            NbtTag::Int(_) => TAG_INT,
+            NbtTag::Long(_) => TAG_LONG,
+            NbtTag::Float(_) => TAG_FLOAT,
+            NbtTag::Double(_) => TAG_DOUBLE,
+            NbtTag::ByteArray(_) => TAG_BYTE_ARRAY,
+            NbtTag::String(_) => TAG_STRING,
+            NbtTag::List(_) => TAG_LIST,
+            NbtTag::Compound(_) => TAG_COMPOUND,
+            NbtTag::IntArray(_) => TAG_INT_ARRAY,
+            NbtTag::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&NbtCompound> {
+        match self {
+            NbtTag::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+    pub fn as_compound_mut(&mut self) -> Option<&mut NbtCompound> {
+        match self {
+            NbtTag::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+    pub fn as_list(&self) -> Option<&NbtList> {
+        match self {
+            NbtTag::List(list) => Some(list),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            NbtTag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+    /// 整数类型 (Byte/Short/Int/Long) 统一转换为 i64
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            NbtTag::Byte(value) => Some(*value as i64),
+            NbtTag::Short(value) => Some(*value as i64),
+            NbtTag::Int(value) => Some(*value as i64),
+            NbtTag::Long(value) => Some(*value),
+            _ => None,
+        }
+    }
+    /// 浮点类型 (Float/Double) 统一转换为 f64
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            NbtTag::Float(value) => Some(*value as f64),
+            NbtTag::Double(value) => Some(*value),
+            _ => None,
+        }
+    }
+    /// Byte 标签作为布尔值
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            NbtTag::Byte(value) => Some(*value != 0),
+            _ => None,
+        }
+    }
+
+    /// 按路径查找子标签,路径以 `.` 分隔,列表使用数字下标,例如 `extra.0.text`
+    pub fn at(&self, path: &str) -> Option<&NbtTag> {
+        let mut current = self;
+        for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+            current = match current {
+                NbtTag::Compound(compound) => compound.get(segment)?,
+                NbtTag::List(list) => list.items.get(segment.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// 转换为 JSON,用于 NBT 格式的文本组件等
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value;
+        match self {
+            NbtTag::End => Value::Null,
+            NbtTag::Byte(value) => Value::from(*value),
+            NbtTag::Short(value) => Value::from(*value),
+            NbtTag::Int(value) => Value::from(*value),
+            NbtTag::Long(value) => Value::from(*value),
+            NbtTag::Float(value) => Value::from(*value),
+            NbtTag::Double(value) => Value::from(*value),
+            NbtTag::ByteArray(values) => Value::from(values.clone()),
+            NbtTag::String(value) => Value::from(value.clone()),
+            NbtTag::List(list) => Value::Array(list.items.iter().map(NbtTag::to_json).collect()),
+            NbtTag::Compound(compound) => Value::Object(
+                compound
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.to_json()))
+                    .collect(),
+            ),
+            NbtTag::IntArray(values) => Value::from(values.clone()),
+            NbtTag::LongArray(values) => Value::from(values.clone()),
+        }
+    }
+
+    /// 从 JSON 转换: 布尔值为 Byte,整数为 Int 或 Long,小数为 Double,
+    /// 元素类型不一致的数组中的元素包装为 `{"": value}`
+    pub fn from_json(value: &serde_json::Value) -> NbtTag {
+        use serde_json::Value;
+        match value {
+            Value::Null => NbtTag::End,
+            Value::Bool(value) => NbtTag::Byte(*value as i8),
+            Value::Number(number) => match number.as_i64() {
+                Some(value) => match i32::try_from(value) {
+                    Ok(value) => NbtTag::Int(value),
+                    Err(_) => NbtTag::Long(value),
+                },
+                None => NbtTag::Double(number.as_f64().unwrap_or(0.0)),
+            },
+            Value::String(value) => NbtTag::String(value.clone()),
+            Value::Array(values) => {
+                let items: Vec<NbtTag> = values.iter().map(NbtTag::from_json).collect();
+                let element_type = items.first().map_or(TAG_END, NbtTag::tag_type);
+                if items.iter().all(|item| item.tag_type() == element_type) {
+                    NbtTag::List(NbtList { element_type, items })
+                } else {
+                    let items = items
+                        .into_iter()
+                        .map(|item| match item {
+                            NbtTag::Compound(compound) => NbtTag::Compound(compound),
+                            other => NbtTag::Compound(NbtCompound(vec![(String::new(), other)])),
+                        })
+                        .collect();
+                    NbtTag::List(NbtList {
+                        element_type: TAG_COMPOUND,
+                        items,
+                    })
+                }
+            }
+            Value::Object(map) => NbtTag::Compound(NbtCompound(
+                map.iter().map(|(key, value)| (key.clone(), NbtTag::from_json(value))).collect(),
+            )),
+        }
+    }
+
+    /// 格式化为 SNBT
+    pub fn to_snbt(&self) -> String {
+        self.to_string()
+    }
+
+    /// 格式化为带缩进的 SNBT
+    pub fn to_snbt_pretty(&self) -> String {
+        format!("{:#}", self)
+    }
+
+    fn read_payload(tag_type: u8, r: &mut PacketReader, depth: usize) -> NbtTag {
+        if depth > MAX_DEPTH {
+            r.fail("NBT nesting too deep");
+            return NbtTag::End;
+        }
+        match tag_type {
+            TAG_END => NbtTag::End,
+            TAG_BYTE => NbtTag::Byte(r.i8()),
+            TAG_SHORT => NbtTag::Short(r.i16()),
+            TAG_INT => NbtTag::Int(r.i32()),
+            TAG_LONG => NbtTag::Long(r.i64()),
+            TAG_FLOAT => NbtTag::Float(r.f32()),
+            TAG_DOUBLE => NbtTag::Double(r.f64()),
+            TAG_BYTE_ARRAY => {
+                let len = read_length(r, 1);
+                NbtTag::ByteArray((0..len).map(|_| r.i8()).collect())
+            }
+            TAG_STRING => NbtTag::String(read_string(r)),
+            TAG_LIST => {
+                let element_type = r.u8();
+                let len = read_length(r, 1);
+                let mut items = r.preallocate(len);
+                for _ in 0..len {
+                    if r.failed() {
+                        break;
+                    }
+                    items.push(NbtTag::read_payload(element_type, r, depth + 1));
+                }
+                NbtTag::List(NbtList { element_type, items })
+            }
+            TAG_COMPOUND => {
+                let mut compound = NbtCompound::new();
+                loop {
+                    // 出错后读出的类型为 0 (TAG_End),同样结束
+                    let tag_type = r.u8();
+                    if tag_type == TAG_END {
+                        break;
+                    }
+                    let name = read_string(r);
+                    let value = NbtTag::read_payload(tag_type, r, depth + 1);
+                    compound.0.push((name, value));
+                }
+                NbtTag::Compound(compound)
+            }
+            TAG_INT_ARRAY => {
+                let len = read_length(r, 4);
+                NbtTag::IntArray((0..len).map(|_| r.i32()).collect())
+            }
+            TAG_LONG_ARRAY => {
+                let len = read_length(r, 8);
+                NbtTag::LongArray((0..len).map(|_| r.i64()).collect())
+            }
+            other => {
+                r.fail(format!("Invalid NBT tag type: {}", other));
+                NbtTag::End
+            }
+        }
+    }
+
+    fn write_payload(&self, w: &mut PacketWriter) {
+        match self {
+            NbtTag::End => {}
+            NbtTag::Byte(value) => w.i8(*value),
+            NbtTag::Short(value) => w.i16(*value),
+            NbtTag::Int(value) => w.i32(*value),
+            NbtTag::Long(value) => w.i64(*value),
+            NbtTag::Float(value) => w.f32(*value),
+            NbtTag::Double(value) => w.f64(*value),
+            NbtTag::ByteArray(values) => {
+                w.i32(values.len() as i32);
+                for value in values {
+                    w.i8(*value);
+                }
+            }
+            NbtTag::String(value) => write_string(w, value),
+            NbtTag::List(list) => {
+                // 与复合标签相同,End 元素 (例如 JSON 数组中的 null) 没有内容,直接省略
+                let items: Vec<&NbtTag> = list.items.iter().filter(|item| *item != &NbtTag::End).collect();
+                w.u8(list.element_type);
+                w.i32(items.len() as i32);
+                for item in items {
+                    item.write_payload(w);
+                }
+            }
+            NbtTag::Compound(compound) => {
+                // End 表示复合标签结束,不能作为值写入 (例如 JSON 对象中的 null),直接省略
+                for (name, value) in compound.iter().filter(|(_, value)| *value != &NbtTag::End) {
+                    w.u8(value.tag_type());
+                    write_string(w, name);
+                    value.write_payload(w);
+                }
+                w.u8(TAG_END);
+            }
+            NbtTag::IntArray(values) => {
+                w.i32(values.len() as i32);
+                for value in values {
+                    w.i32(*value);
+                }
+            }
+            NbtTag::LongArray(values) => {
+                w.i32(values.len() as i32);
+                for value in values {
+                    w.i64(*value);
+                }
+            }
+        }
+    }
+}
+
+impl NbtList {
+    pub fn new(element_type: u8) -> Self {
+        NbtList {
+            element_type,
+            items: vec![],
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, NbtTag> {
+        self.items.iter()
+    }
+    /// 添加元素,空列表会采用第一个元素的类型
+    pub fn push(&mut self, tag: NbtTag) {
+        if self.items.is_empty() {
+            self.element_type = tag.tag_type();
+        }
+        self.items.push(tag);
+    }
+}
+
+impl NbtCompound {
+    pub fn new() -> Self {
+        NbtCompound(vec![])
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &NbtTag)> {
+        self.0.iter().map(|(key, value)| (key, value))
+    }
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+    pub fn get(&self, key: &str) -> Option<&NbtTag> {
+        self.0.iter().find(|(name, _)| name == key).map(|(_, value)| value)
+    }
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut NbtTag> {
+        self.0.iter_mut().find(|(name, _)| name == key).map(|(_, value)| value)
+    }
+    /// 插入标签,已存在时替换并返回旧值
+    pub fn insert(&mut self, key: impl Into<String>, value: NbtTag) -> Option<NbtTag> {
+        let key = key.into();
+        match self.get_mut(&key) {
+            Some(old) => Some(std::mem::replace(old, value)),
+            None => {
+                self.0.push((key, value));
+                None
+            }
+        }
+    }
+    pub fn remove(&mut self, key: &str) -> Option<NbtTag> {
+        let index = self.0.iter().position(|(name, _)| name == key)?;
+        Some(self.0.remove(index).1)
+    }
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key)?.as_str()
+    }
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get(key)?.as_i64()
+    }
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.get(key)?.as_f64()
+    }
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key)?.as_bool()
+    }
+    pub fn get_compound(&self, key: &str) -> Option<&NbtCompound> {
+        self.get(key)?.as_compound()
+    }
+    pub fn get_list(&self, key: &str) -> Option<&NbtList> {
+        self.get(key)?.as_list()
+    }
+}
+
+impl Nbt {
+    /// 根标签为空 (TAG_End) 时表示没有数据
+    pub fn is_empty(&self) -> bool {
+        self.0 == NbtTag::End
+    }
+    pub fn root(&self) -> &NbtTag {
+        &self.0
+    }
+    pub fn at(&self, path: &str) -> Option<&NbtTag> {
+        self.0.at(path)
+    }
+}
+
+impl NamedNbt {
+    pub fn at(&self, path: &str) -> Option<&NbtTag> {
+        self.root.at(path)
+    }
+}
+
+/// 可以与 NBT 相互转换的类型,用于 packet 宏的 `#[nbt]` 字段
+pub trait NbtConvert: Sized {
+    /// 转换失败时返回错误信息,读取时记录为解码错误
+    fn from_nbt_tag(tag: NbtTag) -> Result<Self, String>;
+    fn to_nbt_tag(&self) -> NbtTag;
+}
+
+impl NbtConvert for NbtTag {
+    fn from_nbt_tag(tag: NbtTag) -> Result<Self, String> {
+        Ok(tag)
+    }
+    fn to_nbt_tag(&self) -> NbtTag {
+        self.clone()
+    }
+}
+
+impl NbtConvert for NbtCompound {
+    fn from_nbt_tag(tag: NbtTag) -> Result<Self, String> {
+        match tag {
+            NbtTag::Compound(compound) => Ok(compound),
+            NbtTag::End => Ok(NbtCompound::new()),
+            other => Err(format!("Expected NBT compound, got tag type {}", other.tag_type())),
+        }
+    }
+    fn to_nbt_tag(&self) -> NbtTag {
+        NbtTag::Compound(self.clone())
+    }
+}
+
+impl NbtConvert for serde_json::Value {
+    fn from_nbt_tag(tag: NbtTag) -> Result<Self, String> {
+        Ok(tag.to_json())
+    }
+    fn to_nbt_tag(&self) -> NbtTag {
+        NbtTag::from_json(self)
+    }
+}
+
+impl Subdata for Nbt {
+    fn new() -> Self {
+        Nbt(NbtTag::End)
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.u8(self.0.tag_type());
+        self.0.write_payload(w);
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        let tag_type = r.u8();
+        self.0 = NbtTag::read_payload(tag_type, r, 0);
+    }
+}
+
+impl Subdata for NamedNbt {
+    fn new() -> Self {
+        NamedNbt::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.u8(self.root.tag_type());
+        if self.root != NbtTag::End {
+            write_string(w, &self.name);
+            self.root.write_payload(w);
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        let tag_type = r.u8();
+        if tag_type == TAG_END {
+            *self = NamedNbt::default();
+            return;
+        }
+        self.name = read_string(r);
+        self.root = NbtTag::read_payload(tag_type, r, 0);
+    }
+}
+
+/// 读取 i32 长度并检查剩余数据是否足够
+fn read_length(r: &mut PacketReader, element_size: usize) -> usize {
+    let len = r.i32();
+    r.check_len(len as i64, element_size)
+}
+
+/// 读取 Modified UTF-8 字符串
+fn read_string(r: &mut PacketReader) -> String {
+    let len = r.u16() as usize;
+    let bytes = r.bytes(len);
+    match decode_mutf8(&bytes) {
+        Some(value) => value,
+        None => {
+            r.fail("Invalid Modified UTF-8 string");
+            String::new()
+        }
+    }
+}
+
+/// 长度前缀为 u16,编码后超过 65535 字节的字符串在字符边界处截断,使前缀与写出的字节一致
+fn write_string(w: &mut PacketWriter, value: &str) {
+    let bytes = encode_mutf8(value, u16::MAX as usize);
+    w.u16(bytes.len() as u16);
+    for byte in bytes {
+        w.u8(byte);
+    }
+}
+
+/// Modified UTF-8 编码: U+0000 编码为两字节,增补字符编码为代理对。
+/// 只编码能完整放入 `limit` 字节的前缀字符,代理对不会被拆开
+fn encode_mutf8(value: &str, limit: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(value.len().min(limit));
+    let mut units = [0; 2];
+    for c in value.chars() {
+        let units = c.encode_utf16(&mut units);
+        let len: usize = units
+            .iter()
+            .map(|unit| match unit {
+                0x0001..=0x007F => 1,
+                0x0000 | 0x0080..=0x07FF => 2,
+                _ => 3,
+            })
+            .sum();
+        if out.len() + len > limit {
+            break;
+        }
+        for &mut unit in units {
+            match unit {
+                0x0001..=0x007F => out.push(unit as u8),
+                0x0000 | 0x0080..=0x07FF => {
+                    out.push(0xC0 | (unit >> 6) as u8);
+                    out.push(0x80 | (unit & 0x3F) as u8);
+                }
+                _ => {
+                    out.push(0xE0 | (unit >> 12) as u8);
+                    out.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+                    out.push(0x80 | (unit & 0x3F) as u8);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Modified UTF-8 解码,截断的多字节序列、错误的后续字节和未配对的代理项返回 None
+fn decode_mutf8(bytes: &[u8]) -> Option<String> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i] as u16;
+        let continuation = |offset: usize| {
+            bytes
+                .get(i + offset)
+                .filter(|b| **b & 0xC0 == 0x80)
+                .map(|b| (*b as u16) & 0x3F)
+        };
+        if byte & 0x80 == 0 {
+            units.push(byte);
+            i += 1;
+        } else if byte & 0xE0 == 0xC0 {
+            units.push(((byte & 0x1F) << 6) | continuation(1)?);
+            i += 2;
+        } else if byte & 0xF0 == 0xE0 {
+            units.push(((byte & 0x0F) << 12) | (continuation(1)? << 6) | continuation(2)?);
+            i += 3;
+        } else {
+            return None;
+        }
+    }
+    String::from_utf16(&units).ok()
+}
+
+/// SNBT 中不需要加引号的字符串
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+'))
+}
+
+fn write_quoted(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+fn write_indent(f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+    for _ in 0..depth {
+        f.write_str("  ")?;
+    }
+    Ok(())
+}
+
+impl NbtTag {
+    fn fmt_snbt(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let pretty = f.alternate();
+        match self {
+            NbtTag::End => f.write_str("END"),
+            NbtTag::Byte(value) => write!(f, "{}b", value),
+            NbtTag::Short(value) => write!(f, "{}s", value),
+            NbtTag::Int(value) => write!(f, "{}", value),
+            NbtTag::Long(value) => write!(f, "{}L", value),
+            NbtTag::Float(value) => write!(f, "{:?}f", value),
+            NbtTag::Double(value) => write!(f, "{:?}d", value),
+            NbtTag::ByteArray(values) => fmt_array(f, "B", values.iter().map(|value| format!("{}b", value))),
+            NbtTag::String(value) => write_quoted(f, value),
+            NbtTag::List(list) => {
+                if list.items.is_empty() {
+                    return f.write_str("[]");
+                }
+                f.write_char('[')?;
+                for (index, item) in list.items.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    if pretty {
+                        f.write_char('\n')?;
+                        write_indent(f, depth + 1)?;
+                    }
+                    item.fmt_snbt(f, depth + 1)?;
+                }
+                if pretty {
+                    f.write_char('\n')?;
+                    write_indent(f, depth)?;
+                }
+                f.write_char(']')
+            }
+            NbtTag::Compound(compound) => {
+                if compound.is_empty() {
+                    return f.write_str("{}");
+                }
+                f.write_char('{')?;
+                for (index, (key, value)) in compound.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    if pretty {
+                        f.write_char('\n')?;
+                        write_indent(f, depth + 1)?;
+                    }
+                    if is_plain_key(key) {
+                        f.write_str(key)?;
+                    } else {
+                        write_quoted(f, key)?;
+                    }
+                    f.write_str(if pretty { ": " } else { ":" })?;
+                    value.fmt_snbt(f, depth + 1)?;
+                }
+                if pretty {
+                    f.write_char('\n')?;
+                    write_indent(f, depth)?;
+                }
+                f.write_char('}')
+            }
+            NbtTag::IntArray(values) => fmt_array(f, "I", values.iter().map(|value| value.to_string())),
+            NbtTag::LongArray(values) => fmt_array(f, "L", values.iter().map(|value| format!("{}L", value))),
+        }
+    }
+}
+
+fn fmt_array(f: &mut fmt::Formatter<'_>, prefix: &str, values: impl Iterator<Item = String>) -> fmt::Result {
+    write!(f, "[{};", prefix)?;
+    for (index, value) in values.enumerate() {
+        if index > 0 {
+            f.write_char(',')?;
+        }
+        f.write_str(&value)?;
+    }
+    f.write_char(']')
+}
+
+/// SNBT 格式,`{:#}` 输出带缩进的格式
+impl Display for NbtTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_snbt(f, 0)
+    }
+}
+
+impl fmt::Debug for Nbt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Nbt(")?;
+        Display::fmt(&self.0, f)?;
+        f.write_char(')')
+    }
+}
+
+impl fmt::Debug for NamedNbt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NamedNbt({:?}, ", self.name)?;
+        Display::fmt(&self.root, f)?;
+        f.write_char(')')
+    }
+}
+
+/// 生成任意 NBT 时的最大嵌套深度
+#[cfg(feature = "arbitrary")]
+const MAX_ARBITRARY_DEPTH: usize = 3;
+
+#[cfg(feature = "arbitrary")]
+impl NbtTag {
+    /// 生成标签类型,不包含 End,达到最大深度后不再生成列表和复合标签
+    fn arbitrary_tag_type(u: &mut arbitrary::Unstructured, depth: usize) -> arbitrary::Result<u8> {
+        const LEAVES: &[u8] = &[
+            TAG_BYTE, TAG_SHORT, TAG_INT, TAG_LONG, TAG_FLOAT, TAG_DOUBLE,
+            TAG_BYTE_ARRAY, TAG_STRING, TAG_INT_ARRAY, TAG_LONG_ARRAY,
+        ];
+        if depth < MAX_ARBITRARY_DEPTH && u.ratio(1, 4)? {
+            return Ok(*u.choose(&[TAG_LIST, TAG_COMPOUND])?);
+        }
+        Ok(*u.choose(LEAVES)?)
+    }
+
+    /// 字符串写入时长度前缀为 u16,按 Modified UTF-8 每个字符最多 6 字节限制长度
+    pub(crate) fn arbitrary_string(u: &mut arbitrary::Unstructured) -> arbitrary::Result<String> {
+        let value: String = u.arbitrary()?;
+        Ok(value.chars().take(u16::MAX as usize / 6).collect())
+    }
+
+    fn arbitrary_payload(tag_type: u8, u: &mut arbitrary::Unstructured, depth: usize) -> arbitrary::Result<NbtTag> {
+        use crate::net_types::arbitrary_data::{arbitrary_items, ArbitraryData};
+        Ok(match tag_type {
+            TAG_BYTE => NbtTag::Byte(u.arbitrary()?),
+            TAG_SHORT => NbtTag::Short(u.arbitrary()?),
+            TAG_INT => NbtTag::Int(u.arbitrary()?),
+            TAG_LONG => NbtTag::Long(u.arbitrary()?),
+            TAG_FLOAT => NbtTag::Float(f32::arbitrary_data(u)?),
+            TAG_DOUBLE => NbtTag::Double(f64::arbitrary_data(u)?),
+            TAG_BYTE_ARRAY => NbtTag::ByteArray(u.arbitrary()?),
+            TAG_STRING => NbtTag::String(NbtTag::arbitrary_string(u)?),
+            TAG_LIST => {
+                let element_type = NbtTag::arbitrary_tag_type(u, depth + 1)?;
+                let items = arbitrary_items(u, usize::MAX, |u| NbtTag::arbitrary_payload(element_type, u, depth + 1))?;
+                NbtTag::List(NbtList { element_type, items })
+            }
+            TAG_COMPOUND => NbtTag::Compound(NbtCompound::arbitrary_payload(u, depth)?),
+            TAG_INT_ARRAY => NbtTag::IntArray(u.arbitrary()?),
+            TAG_LONG_ARRAY => NbtTag::LongArray(u.arbitrary()?),
+            _ => NbtTag::End,
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl NbtCompound {
+    fn arbitrary_payload(u: &mut arbitrary::Unstructured, depth: usize) -> arbitrary::Result<NbtCompound> {
+        let entries = crate::net_types::arbitrary_data::arbitrary_items(u, usize::MAX, |u| {
+            let tag_type = NbtTag::arbitrary_tag_type(u, depth + 1)?;
+            Ok((NbtTag::arbitrary_string(u)?, NbtTag::arbitrary_payload(tag_type, u, depth + 1)?))
+        })?;
+        Ok(NbtCompound(entries))
+    }
+}
+
+/// 作为根标签生成,可能是 End (没有数据)
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for NbtTag {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        if u.ratio(1, 8)? {
+            return Ok(NbtTag::End);
+        }
+        let tag_type = NbtTag::arbitrary_tag_type(u, 0)?;
+        NbtTag::arbitrary_payload(tag_type, u, 0)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for NbtCompound {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        NbtCompound::arbitrary_payload(u, 0)
+    }
+}
+
+/// NBT 值原样写入,与 [`crate::net_types::arbitrary_data::ArbitraryData`] 生成方式相同
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryNbt for NbtTag {
+    fn arbitrary_nbt(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        crate::net_types::arbitrary_data::ArbitraryData::arbitrary_data(u)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryNbt for NbtCompound {
+    fn arbitrary_nbt(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        crate::net_types::arbitrary_data::ArbitraryData::arbitrary_data(u)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for Nbt {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(Nbt(crate::net_types::arbitrary_data::ArbitraryData::arbitrary_data(u)?))
+    }
+}
+
+/// 根标签为 End 时不写入名字,此时名字为空
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for NamedNbt {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        let root: NbtTag = crate::net_types::arbitrary_data::ArbitraryData::arbitrary_data(u)?;
+        let name = if root == NbtTag::End { String::new() } else { NbtTag::arbitrary_string(u)? };
+        Ok(NamedNbt { name, root })
+    }
+}
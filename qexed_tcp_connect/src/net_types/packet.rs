@@ -5,7 +5,131 @@ use crate::packet::encode::PacketWriter;
 
 pub trait Packet: std::fmt::Debug + Send + Sync{
     fn id(&self)->u32;
+    /// 数据包名称(结构体名)
+    fn name(&self)->&'static str;
     fn serialize(&self, w: &mut PacketWriter);
     fn deserialize(&mut self, r: &mut PacketReader);
     fn as_any(&self) -> &dyn std::any::Any;
+    /// 转为 `Box<dyn Any>`,用于取得具体数据包的所有权
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any + Send + Sync>;
+    /// 克隆为新的 `Box<dyn Packet>`,宏生成的数据包需要实现 `Clone`
+    fn box_clone(&self) -> Box<dyn Packet>;
+    /// 与另一个数据包比较,类型不同时为 false,宏生成的数据包需要实现 `PartialEq`
+    fn dyn_eq(&self, other: &dyn Packet) -> bool;
+    /// 该数据包是否存在于指定协议版本,由 `#[packet(since = .., until = ..)]` 生成
+    fn supports_version(&self, _protocol_version: i32) -> bool {
+        true
+    }
+    /// 字段描述,宏生成的数据包与 `PacketMeta::FIELDS` 相同
+    fn fields(&self) -> &'static [FieldDescriptor] {
+        &[]
+    }
+    /// 转为 JSON,宏生成的数据包在 qexed_tcp_connect 启用 `serde` 特性时才有内容
+    fn to_json(&self) -> Option<serde_json::Value> {
+        None
+    }
+}
+
+impl Clone for Box<dyn Packet> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+impl PartialEq for dyn Packet {
+    fn eq(&self, other: &dyn Packet) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl dyn Packet {
+    /// 是否为数据包类型 T
+    pub fn is<T: Packet + 'static>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+    pub fn downcast_ref<T: Packet + 'static>(&self) -> Option<&T> {
+        self.as_any().downcast_ref::<T>()
+    }
+    /// 转为具体的数据包类型,类型不符时原样返回
+    pub fn downcast<T: Packet + 'static>(self: Box<Self>) -> Result<Box<T>, Box<dyn Packet>> {
+        if self.is::<T>() {
+            Ok(self.into_any().downcast::<T>().expect("type checked above"))
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// 数据包的静态信息,由 packet 宏生成
+///
+/// 关联常量不能放在 `Packet` 上(`Packet` 需要作为 `dyn Packet` 使用),所以单独作为一个 trait
+pub trait PacketMeta: Packet {
+    const ID: u32;
+    /// 数据包名称(结构体名)
+    const NAME: &'static str;
+    /// 数据包出现的第一个协议版本
+    const SINCE: Option<i32>;
+    /// 数据包存在的最后一个协议版本
+    const UNTIL: Option<i32>;
+    /// 所属的连接状态,`#[packet(state = ..)]`
+    const STATE: Option<ConnectionState>;
+    /// 发送方向,`#[packet(direction = ..)]`
+    const DIRECTION: Option<PacketDirection>;
+    /// 按网络顺序排列的字段
+    const FIELDS: &'static [FieldDescriptor];
+    /// 生成任意实例的函数,`id_to_packet!` 用它生成 `ARBITRARY_PACKETS`
+    #[cfg(feature = "arbitrary")]
+    const ARBITRARY: crate::packet::round_trip::ArbitraryPacket;
+}
+
+/// 连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    Handshaking,
+    Status,
+    Login,
+    Configuration,
+    Play,
+}
+
+/// 数据包方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PacketDirection {
+    /// 客户端发往服务端
+    Serverbound,
+    /// 服务端发往客户端
+    Clientbound,
+}
+
+/// 字段在网络上的编码,对应 packet 宏的字段属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldEncoding {
+    /// 字段类型的 Subdata 实现
+    Default,
+    VarInt,
+    VarLong,
+    /// 长度前缀类型
+    Prefixed(&'static str),
+    /// 元素个数表达式
+    Fixed(&'static str),
+    Rest,
+    Json,
+    Nbt,
+    /// `#[skip]`,不参与编码
+    Skip,
+}
+
+/// 字段描述
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldDescriptor {
+    pub name: &'static str,
+    /// Rust 类型
+    pub rust_type: &'static str,
+    pub encoding: FieldEncoding,
+    /// `#[optional_if = ..]` 的条件
+    pub optional_if: Option<&'static str>,
+    /// `#[since(..)]`
+    pub since: Option<i32>,
+    /// `#[until(..)]`
+    pub until: Option<i32>,
 }
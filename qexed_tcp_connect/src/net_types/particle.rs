@@ -0,0 +1,210 @@
+use crate::{
+    net_types::{position::Position, slot::Slot, subdata::Subdata, var_int::VarInt},
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+
+// 1.20.5 (协议 766) 中带额外数据的粒子类型ID
+pub const BLOCK: i32 = 1;
+pub const BLOCK_MARKER: i32 = 2;
+pub const DUST: i32 = 13;
+pub const DUST_COLOR_TRANSITION: i32 = 14;
+pub const ENTITY_EFFECT: i32 = 20;
+pub const FALLING_DUST: i32 = 28;
+pub const SCULK_CHARGE: i32 = 35;
+pub const ITEM: i32 = 44;
+pub const VIBRATION: i32 = 45;
+pub const SHRIEK: i32 = 99;
+pub const DUST_PILLAR: i32 = 105;
+
+/// 粒子 (类型ID + 该类型的数据)
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Particle {
+    pub id: i32,
+    pub data: ParticleData,
+}
+
+/// 粒子数据
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParticleData {
+    /// 没有额外数据
+    #[default]
+    None,
+    /// block, block_marker, falling_dust, dust_pillar
+    BlockState(i32),
+    Dust {
+        color: [f32; 3],
+        scale: f32,
+    },
+    DustColorTransition {
+        from: [f32; 3],
+        to: [f32; 3],
+        scale: f32,
+    },
+    /// entity_effect 的 ARGB 颜色
+    Color(i32),
+    /// sculk_charge 的旋转角度
+    Roll(f32),
+    Item(Slot),
+    Vibration {
+        source: PositionSource,
+        ticks: i32,
+    },
+    /// shriek 的延迟
+    Delay(i32),
+}
+
+/// 振动粒子的位置来源
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PositionSource {
+    Block(Position),
+    Entity { id: i32, eye_height: f32 },
+    /// 未知类型: 无法知道数据在哪里结束,之后的数据 (含 ticks) 原样保留
+    Unknown { kind: i32, data: Vec<u8> },
+}
+
+fn read_color(r: &mut PacketReader) -> [f32; 3] {
+    [r.f32(), r.f32(), r.f32()]
+}
+
+fn write_color(w: &mut PacketWriter, color: &[f32; 3]) {
+    for c in color {
+        w.f32(*c);
+    }
+}
+
+impl Particle {
+    pub fn new(id: i32) -> Self {
+        Particle {
+            id,
+            data: ParticleData::None,
+        }
+    }
+}
+
+impl Subdata for Particle {
+    fn new() -> Self {
+        Particle::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.varint(&VarInt(self.id));
+        match &self.data {
+            ParticleData::None => {}
+            ParticleData::BlockState(state) => w.varint(&VarInt(*state)),
+            ParticleData::Dust { color, scale } => {
+                write_color(w, color);
+                w.f32(*scale);
+            }
+            ParticleData::DustColorTransition { from, to, scale } => {
+                write_color(w, from);
+                write_color(w, to);
+                w.f32(*scale);
+            }
+            ParticleData::Color(color) => w.i32(*color),
+            ParticleData::Roll(roll) => w.f32(*roll),
+            ParticleData::Item(item) => item.serialize(w),
+            ParticleData::Vibration { source, ticks } => {
+                match source {
+                    PositionSource::Block(position) => {
+                        w.varint(&VarInt(0));
+                        position.serialize(w);
+                    }
+                    PositionSource::Entity { id, eye_height } => {
+                        w.varint(&VarInt(1));
+                        w.varint(&VarInt(*id));
+                        w.f32(*eye_height);
+                    }
+                    PositionSource::Unknown { kind, data } => {
+                        w.varint(&VarInt(*kind));
+                        w.bytes(data);
+                        return;
+                    }
+                }
+                w.varint(&VarInt(*ticks));
+            }
+            ParticleData::Delay(delay) => w.varint(&VarInt(*delay)),
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.id = r.varint().0;
+        self.data = match self.id {
+            BLOCK | BLOCK_MARKER | FALLING_DUST | DUST_PILLAR => ParticleData::BlockState(r.varint().0),
+            DUST => ParticleData::Dust {
+                color: read_color(r),
+                scale: r.f32(),
+            },
+            DUST_COLOR_TRANSITION => ParticleData::DustColorTransition {
+                from: read_color(r),
+                to: read_color(r),
+                scale: r.f32(),
+            },
+            ENTITY_EFFECT => ParticleData::Color(r.i32()),
+            SCULK_CHARGE => ParticleData::Roll(r.f32()),
+            ITEM => ParticleData::Item(r.deserialize()),
+            VIBRATION => {
+                let source = match r.varint().0 {
+                    0 => PositionSource::Block(r.deserialize()),
+                    1 => PositionSource::Entity {
+                        id: r.varint().0,
+                        eye_height: r.f32(),
+                    },
+                    kind => {
+                        let data = r.byte_all();
+                        r.stop(format!("Unknown position source type {}", kind));
+                        PositionSource::Unknown { kind, data }
+                    }
+                };
+                ParticleData::Vibration {
+                    source,
+                    ticks: r.varint().0,
+                }
+            }
+            SHRIEK => ParticleData::Delay(r.varint().0),
+            _ => ParticleData::None,
+        };
+    }
+}
+
+/// 数据的形状由粒子类型ID决定,振动粒子的位置来源只生成已知类型
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for Particle {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        const WITH_DATA: &[i32] = &[
+            BLOCK, BLOCK_MARKER, DUST, DUST_COLOR_TRANSITION, ENTITY_EFFECT, FALLING_DUST,
+            SCULK_CHARGE, ITEM, VIBRATION, SHRIEK, DUST_PILLAR,
+        ];
+        let id = if u.arbitrary()? { *u.choose(WITH_DATA)? } else { u.arbitrary()? };
+        let data = match id {
+            BLOCK | BLOCK_MARKER | FALLING_DUST | DUST_PILLAR => ParticleData::BlockState(u.arbitrary()?),
+            DUST => ParticleData::Dust {
+                color: <[f32; 3]>::arbitrary_data(u)?,
+                scale: f32::arbitrary_data(u)?,
+            },
+            DUST_COLOR_TRANSITION => ParticleData::DustColorTransition {
+                from: <[f32; 3]>::arbitrary_data(u)?,
+                to: <[f32; 3]>::arbitrary_data(u)?,
+                scale: f32::arbitrary_data(u)?,
+            },
+            ENTITY_EFFECT => ParticleData::Color(u.arbitrary()?),
+            SCULK_CHARGE => ParticleData::Roll(f32::arbitrary_data(u)?),
+            ITEM => ParticleData::Item(Slot::arbitrary_data(u)?),
+            VIBRATION => ParticleData::Vibration {
+                source: if u.arbitrary()? {
+                    PositionSource::Block(Position::arbitrary_data(u)?)
+                } else {
+                    PositionSource::Entity {
+                        id: u.arbitrary()?,
+                        eye_height: f32::arbitrary_data(u)?,
+                    }
+                },
+                ticks: u.arbitrary()?,
+            },
+            SHRIEK => ParticleData::Delay(u.arbitrary()?),
+            _ => ParticleData::None,
+        };
+        Ok(Particle { id, data })
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    net_types::subdata::Subdata,
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+/// 方块坐标,打包为一个 i64: x (26 位) | z (26 位) | y (12 位)
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+impl Position {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Position { x, y, z }
+    }
+    /// 打包为协议使用的 i64
+    pub fn to_packed(&self) -> i64 {
+        ((self.x as i64 & 0x3FF_FFFF) << 38) | ((self.z as i64 & 0x3FF_FFFF) << 12) | (self.y as i64 & 0xFFF)
+    }
+    /// 从协议使用的 i64 解包,带符号扩展
+    pub fn from_packed(value: i64) -> Self {
+        Position {
+            x: (value >> 38) as i32,
+            y: ((value << 52) >> 52) as i32,
+            z: ((value << 26) >> 38) as i32,
+        }
+    }
+}
+impl Subdata for Position {
+    fn new() -> Self {
+        Position::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.i64(self.to_packed());
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        *self = Position::from_packed(r.i64());
+    }
+}
+
+/// 从打包后的值生成,保证每个坐标都在可编码的范围内
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for Position {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(Position::from_packed(u.arbitrary()?))
+    }
+}
@@ -1,7 +1,72 @@
+use bytes::Bytes;
+
+/// 数据包剩余的全部字节,解码时直接引用原始帧,不复制
 #[derive(Debug, Default, PartialEq,Clone)]
-pub struct RestBuffer(pub Vec<u8>);
+pub struct RestBuffer(pub Bytes);
 impl RestBuffer{
     pub fn new() -> Self {
-        RestBuffer(Vec::new())
+        RestBuffer(Bytes::new())
     }
-}
\ No newline at end of file
+    /// 小写十六进制字符串
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+    /// 从十六进制字符串解析
+    pub fn from_hex(hex: &str) -> anyhow::Result<Self> {
+        if !hex.len().is_multiple_of(2) {
+            anyhow::bail!("Hex string has odd length: {}", hex.len());
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                    .ok_or_else(|| anyhow::anyhow!("Invalid hex byte at offset {}", i))
+            })
+            .collect::<anyhow::Result<Vec<u8>>>()?;
+        Ok(RestBuffer(bytes.into()))
+    }
+}
+
+impl From<Vec<u8>> for RestBuffer {
+    fn from(value: Vec<u8>) -> Self {
+        RestBuffer(value.into())
+    }
+}
+
+impl From<Bytes> for RestBuffer {
+    fn from(value: Bytes) -> Self {
+        RestBuffer(value)
+    }
+}
+
+/// 启用 serde 特性时,JSON 等文本格式中序列化为十六进制字符串,二进制格式中序列化为字节
+#[cfg(feature = "serde")]
+impl serde::Serialize for RestBuffer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_hex())
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RestBuffer {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let hex = String::deserialize(deserializer)?;
+            RestBuffer::from_hex(&hex).map_err(serde::de::Error::custom)
+        } else {
+            Vec::<u8>::deserialize(deserializer).map(|bytes| RestBuffer(bytes.into()))
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for RestBuffer {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(RestBuffer::from(u.arbitrary::<Vec<u8>>()?))
+    }
+}
@@ -0,0 +1,871 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+use crate::{
+    net_types::{id_set::IdSet, identifier::Identifier, nbt::Nbt, subdata::Subdata, var_int::VarInt},
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+
+/// 1.21 (协议 767) 起属性修饰符使用命名空间ID,食物可以指定食用后转换成的物品
+const V1_21: i32 = 767;
+
+/// 物品堆 (1.20.5+ 数据组件格式)
+///
+/// 数量为 0 时表示空槽位,后面没有其他数据
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Slot {
+    pub count: i32,
+    pub item_id: i32,
+    /// 添加的组件
+    pub components: Vec<Component>,
+    /// 移除的组件类型
+    pub removed: Vec<i32>,
+    /// 遇到未注册的组件后保存的剩余数据
+    pub unknown: Option<UnknownComponents>,
+}
+
+/// 一个数据组件
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Component {
+    pub id: i32,
+    pub value: ComponentValue,
+}
+
+/// 组件数据,按数据形状而不是组件类型区分
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ComponentValue {
+    /// 没有数据的标记组件
+    Empty,
+    Bool(bool),
+    VarInt(i32),
+    Int(i32),
+    Nbt(Nbt),
+    NbtList(Vec<Nbt>),
+    Enchantments {
+        levels: Vec<(i32, i32)>,
+        show_in_tooltip: bool,
+    },
+    DyedColor {
+        color: i32,
+        show_in_tooltip: bool,
+    },
+    /// can_place_on / can_break
+    BlockPredicates {
+        predicates: Vec<BlockPredicate>,
+        show_in_tooltip: bool,
+    },
+    AttributeModifiers {
+        modifiers: Vec<AttributeModifier>,
+        show_in_tooltip: bool,
+    },
+    Food(Food),
+    Tool(Tool),
+}
+
+/// 方块谓词,各条件都为空时匹配任意方块
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockPredicate {
+    pub blocks: Option<IdSet>,
+    pub properties: Option<Vec<PropertyMatcher>>,
+    pub nbt: Option<Nbt>,
+}
+
+/// 方块状态属性条件
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PropertyMatcher {
+    pub name: String,
+    pub value: PropertyValue,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PropertyValue {
+    Exact(String),
+    Range { min: Option<String>, max: Option<String> },
+}
+
+impl Default for PropertyValue {
+    fn default() -> Self {
+        PropertyValue::Exact(String::new())
+    }
+}
+
+/// 属性修饰符
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttributeModifier {
+    /// 属性类型ID
+    pub attribute: i32,
+    pub id: ModifierId,
+    pub amount: f64,
+    /// 0 加法, 1 乘以基础值, 2 乘以总值
+    pub operation: i32,
+    /// 装备槽位组
+    pub slot: i32,
+}
+
+/// 修饰符的标识: 1.21 之前为 UUID 加名字,之后为命名空间ID
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ModifierId {
+    Uuid { uuid: uuid::Uuid, name: String },
+    Identifier(Identifier),
+}
+
+impl Default for ModifierId {
+    fn default() -> Self {
+        ModifierId::Identifier(Identifier::default())
+    }
+}
+
+/// 食物属性
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Food {
+    pub nutrition: i32,
+    pub saturation: f32,
+    pub can_always_eat: bool,
+    pub eat_seconds: f32,
+    /// 食用后转换成的物品 (1.21 起)
+    pub using_converts_to: Option<Box<Slot>>,
+    pub effects: Vec<FoodEffect>,
+}
+
+/// 食用后按概率获得的状态效果
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FoodEffect {
+    pub effect: PotionEffect,
+    pub probability: f32,
+}
+
+/// 状态效果
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PotionEffect {
+    pub id: i32,
+    /// 第一个是效果本身,之后依次是隐藏的效果 (网络格式中逐层嵌套)
+    pub details: Vec<EffectDetails>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EffectDetails {
+    pub amplifier: i32,
+    pub duration: i32,
+    pub ambient: bool,
+    pub show_particles: bool,
+    pub show_icon: bool,
+}
+
+/// 工具属性
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tool {
+    pub rules: Vec<ToolRule>,
+    pub default_mining_speed: f32,
+    pub damage_per_block: i32,
+}
+
+#[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ToolRule {
+    pub blocks: IdSet,
+    pub speed: Option<f32>,
+    pub correct_for_drops: Option<bool>,
+}
+
+/// 未注册的组件
+///
+/// 网络格式中组件数据没有长度前缀,遇到不认识的组件时无法跳过,也无法知道
+/// 物品在哪里结束。此时保存从该组件开始直到数据包结尾的全部数据并停止解析
+/// (见 [`PacketReader::stop`]),数据包之后的字段都没有读取,只能按原始字节转发。
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnknownComponents {
+    /// 原始的添加组件数量
+    pub added: i32,
+    /// 原始的移除组件数量
+    pub removed: i32,
+    /// 第一个未注册组件的类型
+    pub id: i32,
+    /// 该组件类型之后的全部数据
+    pub data: Vec<u8>,
+}
+
+/// 组件解码函数
+pub type ComponentDecoder = fn(&mut PacketReader) -> ComponentValue;
+
+/// 组件类型注册表,组件类型ID -> (名字, 解码函数)
+#[derive(Clone)]
+pub struct ComponentRegistry {
+    decoders: HashMap<i32, (&'static str, ComponentDecoder)>,
+}
+
+static GLOBAL_REGISTRY: LazyLock<RwLock<ComponentRegistry>> =
+    LazyLock::new(|| RwLock::new(ComponentRegistry::default()));
+
+impl ComponentRegistry {
+    /// 空注册表,所有组件都会作为未知数据保存
+    pub fn empty() -> Self {
+        ComponentRegistry {
+            decoders: HashMap::new(),
+        }
+    }
+    /// 1.20.5 - 1.21.1 的组件类型
+    ///
+    /// 两个版本从 42 (1.21 加入的 jukebox_playable) 开始ID不同,这里只注册之前共有的组件
+    pub fn v1_20_5() -> Self {
+        let mut registry = ComponentRegistry::empty();
+        registry.register(0, "minecraft:custom_data", decode_nbt);
+        registry.register(1, "minecraft:max_stack_size", decode_varint);
+        registry.register(2, "minecraft:max_damage", decode_varint);
+        registry.register(3, "minecraft:damage", decode_varint);
+        registry.register(4, "minecraft:unbreakable", decode_bool);
+        registry.register(5, "minecraft:custom_name", decode_nbt);
+        registry.register(6, "minecraft:item_name", decode_nbt);
+        registry.register(7, "minecraft:lore", decode_nbt_list);
+        registry.register(8, "minecraft:rarity", decode_varint);
+        registry.register(9, "minecraft:enchantments", decode_enchantments);
+        registry.register(10, "minecraft:can_place_on", decode_block_predicates);
+        registry.register(11, "minecraft:can_break", decode_block_predicates);
+        registry.register(12, "minecraft:attribute_modifiers", decode_attribute_modifiers);
+        registry.register(13, "minecraft:custom_model_data", decode_varint);
+        registry.register(14, "minecraft:hide_additional_tooltip", decode_empty);
+        registry.register(15, "minecraft:hide_tooltip", decode_empty);
+        registry.register(16, "minecraft:repair_cost", decode_varint);
+        registry.register(17, "minecraft:creative_slot_lock", decode_empty);
+        registry.register(18, "minecraft:enchantment_glint_override", decode_bool);
+        registry.register(19, "minecraft:intangible_projectile", decode_nbt);
+        registry.register(20, "minecraft:food", decode_food);
+        registry.register(21, "minecraft:fire_resistant", decode_empty);
+        registry.register(22, "minecraft:tool", decode_tool);
+        registry.register(23, "minecraft:stored_enchantments", decode_enchantments);
+        registry.register(24, "minecraft:dyed_color", decode_dyed_color);
+        registry.register(25, "minecraft:map_color", decode_int);
+        registry.register(26, "minecraft:map_id", decode_varint);
+        registry.register(27, "minecraft:map_decorations", decode_nbt);
+        registry.register(28, "minecraft:map_post_processing", decode_varint);
+        registry.register(36, "minecraft:debug_stick_state", decode_nbt);
+        registry.register(37, "minecraft:entity_data", decode_nbt);
+        registry.register(38, "minecraft:bucket_entity_data", decode_nbt);
+        registry.register(39, "minecraft:block_entity_data", decode_nbt);
+        registry.register(41, "minecraft:ominous_bottle_amplifier", decode_varint);
+        registry
+    }
+    /// 注册组件解码函数,已存在时覆盖
+    pub fn register(&mut self, id: i32, name: &'static str, decoder: ComponentDecoder) {
+        self.decoders.insert(id, (name, decoder));
+    }
+    pub fn name(&self, id: i32) -> Option<&'static str> {
+        self.decoders.get(&id).map(|(name, _)| *name)
+    }
+    pub fn decoder(&self, id: i32) -> Option<ComponentDecoder> {
+        self.decoders.get(&id).map(|(_, decoder)| *decoder)
+    }
+    /// `Slot` 作为 `Subdata` 解码时使用的全局注册表
+    pub fn global() -> &'static RwLock<ComponentRegistry> {
+        &GLOBAL_REGISTRY
+    }
+}
+
+impl Default for ComponentRegistry {
+    fn default() -> Self {
+        ComponentRegistry::v1_20_5()
+    }
+}
+
+pub fn decode_empty(_r: &mut PacketReader) -> ComponentValue {
+    ComponentValue::Empty
+}
+pub fn decode_bool(r: &mut PacketReader) -> ComponentValue {
+    ComponentValue::Bool(r.bool())
+}
+pub fn decode_varint(r: &mut PacketReader) -> ComponentValue {
+    ComponentValue::VarInt(r.varint().0)
+}
+pub fn decode_int(r: &mut PacketReader) -> ComponentValue {
+    ComponentValue::Int(r.i32())
+}
+pub fn decode_nbt(r: &mut PacketReader) -> ComponentValue {
+    ComponentValue::Nbt(r.deserialize())
+}
+pub fn decode_nbt_list(r: &mut PacketReader) -> ComponentValue {
+    ComponentValue::NbtList(r.vec())
+}
+pub fn decode_enchantments(r: &mut PacketReader) -> ComponentValue {
+    let len = r.varint_len(2);
+    let mut levels = r.preallocate(len);
+    for _ in 0..len {
+        if r.failed() {
+            break;
+        }
+        levels.push((r.varint().0, r.varint().0));
+    }
+    ComponentValue::Enchantments {
+        levels,
+        show_in_tooltip: r.bool(),
+    }
+}
+pub fn decode_dyed_color(r: &mut PacketReader) -> ComponentValue {
+    ComponentValue::DyedColor {
+        color: r.i32(),
+        show_in_tooltip: r.bool(),
+    }
+}
+pub fn decode_block_predicates(r: &mut PacketReader) -> ComponentValue {
+    ComponentValue::BlockPredicates {
+        predicates: r.vec(),
+        show_in_tooltip: r.bool(),
+    }
+}
+pub fn decode_attribute_modifiers(r: &mut PacketReader) -> ComponentValue {
+    ComponentValue::AttributeModifiers {
+        modifiers: r.vec(),
+        show_in_tooltip: r.bool(),
+    }
+}
+pub fn decode_food(r: &mut PacketReader) -> ComponentValue {
+    ComponentValue::Food(r.deserialize())
+}
+pub fn decode_tool(r: &mut PacketReader) -> ComponentValue {
+    ComponentValue::Tool(r.deserialize())
+}
+
+impl ComponentValue {
+    pub fn serialize(&self, w: &mut PacketWriter) {
+        match self {
+            ComponentValue::Empty => {}
+            ComponentValue::Bool(value) => w.bool(*value),
+            ComponentValue::VarInt(value) => w.varint(&VarInt(*value)),
+            ComponentValue::Int(value) => w.i32(*value),
+            ComponentValue::Nbt(value) => value.serialize(w),
+            ComponentValue::NbtList(values) => w.vec(values),
+            ComponentValue::Enchantments {
+                levels,
+                show_in_tooltip,
+            } => {
+                w.varint(&VarInt(levels.len() as i32));
+                for (id, level) in levels {
+                    w.varint(&VarInt(*id));
+                    w.varint(&VarInt(*level));
+                }
+                w.bool(*show_in_tooltip);
+            }
+            ComponentValue::DyedColor {
+                color,
+                show_in_tooltip,
+            } => {
+                w.i32(*color);
+                w.bool(*show_in_tooltip);
+            }
+            ComponentValue::BlockPredicates {
+                predicates,
+                show_in_tooltip,
+            } => {
+                w.vec(predicates);
+                w.bool(*show_in_tooltip);
+            }
+            ComponentValue::AttributeModifiers {
+                modifiers,
+                show_in_tooltip,
+            } => {
+                w.vec(modifiers);
+                w.bool(*show_in_tooltip);
+            }
+            ComponentValue::Food(food) => food.serialize(w),
+            ComponentValue::Tool(tool) => tool.serialize(w),
+        }
+    }
+}
+
+impl Subdata for BlockPredicate {
+    fn new() -> Self {
+        BlockPredicate::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.option(self.blocks.as_ref());
+        w.option(self.properties.as_ref());
+        w.option(self.nbt.as_ref());
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.blocks = r.option();
+        self.properties = r.option();
+        self.nbt = r.option();
+    }
+}
+
+impl Subdata for PropertyMatcher {
+    fn new() -> Self {
+        PropertyMatcher::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.string(&self.name);
+        match &self.value {
+            PropertyValue::Exact(value) => {
+                w.bool(true);
+                w.string(value);
+            }
+            PropertyValue::Range { min, max } => {
+                w.bool(false);
+                w.option(min.as_ref());
+                w.option(max.as_ref());
+            }
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.name = r.string();
+        self.value = if r.bool() {
+            PropertyValue::Exact(r.string())
+        } else {
+            PropertyValue::Range {
+                min: r.option(),
+                max: r.option(),
+            }
+        };
+    }
+}
+
+impl Subdata for AttributeModifier {
+    fn new() -> Self {
+        AttributeModifier::default()
+    }
+    /// 按读取时的格式写回,与写入的协议版本无关
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.varint(&VarInt(self.attribute));
+        match &self.id {
+            ModifierId::Uuid { uuid, name } => {
+                w.uuid(uuid);
+                w.string(name);
+            }
+            ModifierId::Identifier(id) => id.serialize(w),
+        }
+        w.f64(self.amount);
+        w.varint(&VarInt(self.operation));
+        w.varint(&VarInt(self.slot));
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.attribute = r.varint().0;
+        self.id = if r.supports(Some(V1_21), None) {
+            ModifierId::Identifier(r.deserialize())
+        } else {
+            ModifierId::Uuid {
+                uuid: r.uuid(),
+                name: r.string(),
+            }
+        };
+        self.amount = r.f64();
+        self.operation = r.varint().0;
+        self.slot = r.varint().0;
+    }
+}
+
+impl Subdata for Food {
+    fn new() -> Self {
+        Food::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.varint(&VarInt(self.nutrition));
+        w.f32(self.saturation);
+        w.bool(self.can_always_eat);
+        w.f32(self.eat_seconds);
+        if w.supports(Some(V1_21), None) {
+            w.option(self.using_converts_to.as_deref());
+        }
+        w.vec(&self.effects);
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.nutrition = r.varint().0;
+        self.saturation = r.f32();
+        self.can_always_eat = r.bool();
+        self.eat_seconds = r.f32();
+        if r.supports(Some(V1_21), None) {
+            self.using_converts_to = r.option().map(Box::new);
+        }
+        self.effects = r.vec();
+    }
+}
+
+impl Subdata for FoodEffect {
+    fn new() -> Self {
+        FoodEffect::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        self.effect.serialize(w);
+        w.f32(self.probability);
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.effect = r.deserialize();
+        self.probability = r.f32();
+    }
+}
+
+impl Subdata for PotionEffect {
+    fn new() -> Self {
+        PotionEffect::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.varint(&VarInt(self.id));
+        let default = [EffectDetails::default()];
+        let details = if self.details.is_empty() { &default[..] } else { &self.details[..] };
+        for (index, details) in details.iter().enumerate() {
+            w.varint(&VarInt(details.amplifier));
+            w.varint(&VarInt(details.duration));
+            w.bool(details.ambient);
+            w.bool(details.show_particles);
+            w.bool(details.show_icon);
+            w.bool(index + 1 < self.details.len().max(1));
+        }
+    }
+
+    // 隐藏的效果逐层嵌套,循环读取避免递归
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.id = r.varint().0;
+        self.details.clear();
+        loop {
+            self.details.push(EffectDetails {
+                amplifier: r.varint().0,
+                duration: r.varint().0,
+                ambient: r.bool(),
+                show_particles: r.bool(),
+                show_icon: r.bool(),
+            });
+            if !r.bool() {
+                break;
+            }
+        }
+    }
+}
+
+impl Subdata for Tool {
+    fn new() -> Self {
+        Tool::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.vec(&self.rules);
+        w.f32(self.default_mining_speed);
+        w.varint(&VarInt(self.damage_per_block));
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.rules = r.vec();
+        self.default_mining_speed = r.f32();
+        self.damage_per_block = r.varint().0;
+    }
+}
+
+impl Subdata for ToolRule {
+    fn new() -> Self {
+        ToolRule::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        self.blocks.serialize(w);
+        w.option(self.speed.as_ref());
+        w.option(self.correct_for_drops.as_ref());
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.blocks = r.deserialize();
+        self.speed = r.option();
+        self.correct_for_drops = r.option();
+    }
+}
+
+impl Slot {
+    pub fn new(item_id: i32, count: i32) -> Self {
+        Slot {
+            count,
+            item_id,
+            ..Default::default()
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.count <= 0
+    }
+    pub fn component(&self, id: i32) -> Option<&ComponentValue> {
+        self.components.iter().find(|c| c.id == id).map(|c| &c.value)
+    }
+    /// 使用指定的注册表解码
+    pub fn read_with(r: &mut PacketReader, registry: &ComponentRegistry) -> Slot {
+        let count = r.varint().0;
+        if count <= 0 {
+            return Slot {
+                count,
+                ..Default::default()
+            };
+        }
+        let item_id = r.varint().0;
+        let added = r.varint().0;
+        let removed = r.varint().0;
+        let mut slot = Slot::new(item_id, count);
+        // 每个组件至少有一个字节的类型
+        for _ in 0..r.check_len(added as i64, 1) {
+            if r.failed() {
+                return slot;
+            }
+            let id = r.varint().0;
+            match registry.decoder(id) {
+                Some(decoder) => slot.components.push(Component {
+                    id,
+                    value: decoder(r),
+                }),
+                None => {
+                    slot.unknown = Some(UnknownComponents {
+                        added,
+                        removed,
+                        id,
+                        data: r.byte_all(),
+                    });
+                    r.stop(format!("Unknown item component {}", id));
+                    return slot;
+                }
+            }
+        }
+        let len = r.check_len(removed as i64, 1);
+        slot.removed = r.prefixed_items::<VarInt>(len).into_iter().map(|id| id.0).collect();
+        slot
+    }
+}
+
+impl Subdata for Slot {
+    fn new() -> Self {
+        Slot::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.varint(&VarInt(self.count));
+        if self.is_empty() {
+            return;
+        }
+        w.varint(&VarInt(self.item_id));
+        match &self.unknown {
+            Some(unknown) => {
+                w.varint(&VarInt(unknown.added));
+                w.varint(&VarInt(unknown.removed));
+            }
+            None => {
+                w.varint(&VarInt(self.components.len() as i32));
+                w.varint(&VarInt(self.removed.len() as i32));
+            }
+        }
+        for component in &self.components {
+            w.varint(&VarInt(component.id));
+            component.value.serialize(w);
+        }
+        match &self.unknown {
+            Some(unknown) => {
+                w.varint(&VarInt(unknown.id));
+                w.byte_all(unknown.data.clone());
+            }
+            None => {
+                for id in &self.removed {
+                    w.varint(&VarInt(*id));
+                }
+            }
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        let registry = ComponentRegistry::global().read().unwrap();
+        *self = Slot::read_with(r, &registry);
+    }
+}
+
+/// 默认注册表 ([`ComponentRegistry::v1_20_5`]) 中的组件类型
+#[cfg(feature = "arbitrary")]
+const REGISTERED_COMPONENTS: &[i32] = &[
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28,
+    36, 37, 38, 39, 41,
+];
+
+/// 按最新协议版本和默认注册表生成,每个组件的数据形状与注册的解码函数一致,
+/// 不生成未注册的组件
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for Slot {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Slot::arbitrary_slot(u, false)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl Slot {
+    /// nested 为 true 时是食物转换成的物品,其中的食物不再有转换成的物品
+    fn arbitrary_slot(u: &mut arbitrary::Unstructured, nested: bool) -> arbitrary::Result<Self> {
+        use crate::net_types::arbitrary_data::{arbitrary_items, ArbitraryData};
+        // 数量不大于 0 时只写入数量
+        if u.ratio(1, 8)? {
+            return Ok(Slot::new(0, -u.int_in_range(0..=i32::MAX)?));
+        }
+        let mut slot = Slot::new(u.arbitrary()?, u.int_in_range(1..=i32::MAX)?);
+        slot.components = arbitrary_items(u, usize::MAX, |u| {
+            let id = *u.choose(REGISTERED_COMPONENTS)?;
+            Ok(Component {
+                id,
+                value: ComponentValue::arbitrary_for(id, u, nested)?,
+            })
+        })?;
+        slot.removed = Vec::arbitrary_data(u)?;
+        Ok(slot)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl ComponentValue {
+    /// 生成 [`ComponentRegistry::v1_20_5`] 中 id 对应的解码函数读取的数据
+    fn arbitrary_for(id: i32, u: &mut arbitrary::Unstructured, nested: bool) -> arbitrary::Result<Self> {
+        use crate::net_types::arbitrary_data::{arbitrary_items, ArbitraryData};
+        Ok(match id {
+            0 | 5 | 6 | 19 | 27 | 36..=39 => ComponentValue::Nbt(Nbt::arbitrary_data(u)?),
+            1 | 2 | 3 | 8 | 13 | 16 | 26 | 28 | 41 => ComponentValue::VarInt(u.arbitrary()?),
+            4 | 18 => ComponentValue::Bool(u.arbitrary()?),
+            7 => ComponentValue::NbtList(Vec::arbitrary_data(u)?),
+            9 | 23 => ComponentValue::Enchantments {
+                levels: arbitrary_items(u, usize::MAX, |u| Ok((u.arbitrary()?, u.arbitrary()?)))?,
+                show_in_tooltip: u.arbitrary()?,
+            },
+            10 | 11 => ComponentValue::BlockPredicates {
+                predicates: Vec::arbitrary_data(u)?,
+                show_in_tooltip: u.arbitrary()?,
+            },
+            12 => ComponentValue::AttributeModifiers {
+                modifiers: Vec::arbitrary_data(u)?,
+                show_in_tooltip: u.arbitrary()?,
+            },
+            20 => ComponentValue::Food(Food::arbitrary_food(u, nested)?),
+            22 => ComponentValue::Tool(Tool::arbitrary_data(u)?),
+            24 => ComponentValue::DyedColor {
+                color: u.arbitrary()?,
+                show_in_tooltip: u.arbitrary()?,
+            },
+            25 => ComponentValue::Int(u.arbitrary()?),
+            // 14, 15, 17, 21 是没有数据的标记组件
+            _ => ComponentValue::Empty,
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for BlockPredicate {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(BlockPredicate {
+            blocks: Option::arbitrary_data(u)?,
+            properties: Option::arbitrary_data(u)?,
+            nbt: Option::arbitrary_data(u)?,
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for PropertyMatcher {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(PropertyMatcher {
+            name: u.arbitrary()?,
+            value: if u.arbitrary()? {
+                PropertyValue::Exact(u.arbitrary()?)
+            } else {
+                PropertyValue::Range {
+                    min: u.arbitrary()?,
+                    max: u.arbitrary()?,
+                }
+            },
+        })
+    }
+}
+
+/// 1.21 起修饰符只有命名空间ID
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for AttributeModifier {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(AttributeModifier {
+            attribute: u.arbitrary()?,
+            id: ModifierId::Identifier(Identifier::arbitrary_data(u)?),
+            amount: f64::arbitrary_data(u)?,
+            operation: u.arbitrary()?,
+            slot: u.arbitrary()?,
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl Food {
+    fn arbitrary_food(u: &mut arbitrary::Unstructured, nested: bool) -> arbitrary::Result<Self> {
+        use crate::net_types::arbitrary_data::ArbitraryData;
+        Ok(Food {
+            nutrition: u.arbitrary()?,
+            saturation: f32::arbitrary_data(u)?,
+            can_always_eat: u.arbitrary()?,
+            eat_seconds: f32::arbitrary_data(u)?,
+            using_converts_to: if !nested && u.arbitrary()? {
+                Some(Box::new(Slot::arbitrary_slot(u, true)?))
+            } else {
+                None
+            },
+            effects: Vec::arbitrary_data(u)?,
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for FoodEffect {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(FoodEffect {
+            effect: PotionEffect::arbitrary_data(u)?,
+            probability: f32::arbitrary_data(u)?,
+        })
+    }
+}
+
+/// details 为空时按一层默认效果写入,生成时至少有一层
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for PotionEffect {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        let id = u.arbitrary()?;
+        let mut details = vec![EffectDetails::arbitrary_data(u)?];
+        details.extend(Vec::<EffectDetails>::arbitrary_data(u)?);
+        Ok(PotionEffect { id, details })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for EffectDetails {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(EffectDetails {
+            amplifier: u.arbitrary()?,
+            duration: u.arbitrary()?,
+            ambient: u.arbitrary()?,
+            show_particles: u.arbitrary()?,
+            show_icon: u.arbitrary()?,
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for Tool {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(Tool {
+            rules: Vec::arbitrary_data(u)?,
+            default_mining_speed: f32::arbitrary_data(u)?,
+            damage_per_block: u.arbitrary()?,
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for ToolRule {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(ToolRule {
+            blocks: IdSet::arbitrary_data(u)?,
+            speed: Option::arbitrary_data(u)?,
+            correct_for_drops: u.arbitrary()?,
+        })
+    }
+}
@@ -145,7 +145,7 @@ impl Subdata for String {
         "".to_owned()
     }
     fn serialize(&self, w: &mut PacketWriter) {
-        w.string(&self);
+        w.string(self);
     }
 
     fn deserialize(&mut self, r: &mut PacketReader) {
@@ -157,7 +157,7 @@ impl Subdata for serde_json::Value {
         serde_json::Value::Null
     }
     fn serialize(&self, w: &mut PacketWriter) {
-        w.json(&self);
+        w.json(self);
     }
 
     fn deserialize(&mut self, r: &mut PacketReader) {
@@ -169,7 +169,7 @@ impl Subdata for uuid::Uuid {
         uuid::Uuid::nil()
     }
     fn serialize(&self, w: &mut PacketWriter) {
-        w.uuid(&self);
+        w.uuid(self);
     }
 
     fn deserialize(&mut self, r: &mut PacketReader) {
@@ -181,7 +181,7 @@ impl Subdata for VarInt {
         net_types::var_int::VarInt(0)
     }
     fn serialize(&self, w: &mut PacketWriter) {
-        w.varint(&self);
+        w.varint(self);
     }
 
     fn deserialize(&mut self, r: &mut PacketReader) {
@@ -191,17 +191,20 @@ impl Subdata for VarInt {
 
 impl Subdata for RestBuffer {
     fn new() -> Self {
-        RestBuffer(Vec::new())
+        RestBuffer::new()
     }
     fn serialize(&self, w: &mut crate::packet::encode::PacketWriter) {
-        w.byte_all(self.0.clone());
+        w.bytes(&self.0);
     }
 
     fn deserialize(&mut self, r: &mut crate::packet::decode::PacketReader) {
-        self.0 = r.byte_all();
+        self.0 = r.rest();
     }
 }
 
+/// 布尔值前缀的可选字段,与 `Option<T>` 的编码相同
+pub type PrefixedOptional<T> = Option<T>;
+
 impl<T> Subdata for Option<T> where T: Subdata{
     fn new() -> Self {
         None
@@ -212,16 +215,7 @@ impl<T> Subdata for Option<T> where T: Subdata{
     }
 
     fn deserialize(&mut self, r: &mut PacketReader) {
-        
-        let mut is_true:bool = false;
-        is_true.deserialize(r);
-        if !is_true{
-            *self = None;
-            return
-        }
-        let mut v =  T::new();
-        v.deserialize(r);
-        *self=Some(v);
+        *self = r.option();
     }
 }
 impl<T> Subdata for Vec<T> where T: Subdata,{
@@ -0,0 +1,50 @@
+use crate::{
+    net_types::subdata::Subdata,
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+/// 传送标志 (1.21.2+ 为 i32),置位表示对应的值是相对值
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TeleportFlags(pub i32);
+impl TeleportFlags {
+    pub const RELATIVE_X: i32 = 0x0001;
+    pub const RELATIVE_Y: i32 = 0x0002;
+    pub const RELATIVE_Z: i32 = 0x0004;
+    pub const RELATIVE_YAW: i32 = 0x0008;
+    pub const RELATIVE_PITCH: i32 = 0x0010;
+    pub const RELATIVE_VELOCITY_X: i32 = 0x0020;
+    pub const RELATIVE_VELOCITY_Y: i32 = 0x0040;
+    pub const RELATIVE_VELOCITY_Z: i32 = 0x0080;
+    /// 先按旋转变化量旋转速度
+    pub const ROTATE_VELOCITY: i32 = 0x0100;
+
+    pub fn has(&self, flag: i32) -> bool {
+        self.0 & flag == flag
+    }
+    pub fn set(&mut self, flag: i32, value: bool) {
+        if value {
+            self.0 |= flag;
+        } else {
+            self.0 &= !flag;
+        }
+    }
+}
+impl Subdata for TeleportFlags {
+    fn new() -> Self {
+        TeleportFlags(0)
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.i32(self.0);
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        self.0 = r.i32();
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for TeleportFlags {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(TeleportFlags(u.arbitrary()?))
+    }
+}
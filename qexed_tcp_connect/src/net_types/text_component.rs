@@ -0,0 +1,475 @@
+use serde::de::Error;
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::{
+    net_types::{
+        nbt::{NbtConvert, NbtTag},
+        subdata::Subdata,
+    },
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+
+/// 聊天文本组件
+///
+/// 可以从字符串、数组(第一个元素为父组件,其余为 extra)和对象三种 JSON 形式解析,
+/// 序列化时按 [`TextShape`] 输出解析时的形式;未知的键保留在 `extra_fields` 中。
+/// 对象的键按字段声明顺序输出,不保留原始顺序
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
+pub struct TextComponent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translate: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub with: Vec<TextComponent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keybind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub underlined: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obfuscated: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insertion: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<TextComponent>,
+    /// 1.21.5 起字段名为 click_event
+    #[serde(rename = "clickEvent", alias = "click_event", default, skip_serializing_if = "Option::is_none")]
+    pub click_event: Option<ClickEvent>,
+    /// 1.21.5 起字段名为 hover_event
+    #[serde(rename = "hoverEvent", alias = "hover_event", default, skip_serializing_if = "Option::is_none")]
+    pub hover_event: Option<HoverEvent>,
+    /// 其他键 (score, selector, nbt, type, shadow_color ...)
+    #[serde(flatten)]
+    pub extra_fields: Map<String, Value>,
+    /// 解析时的形式
+    #[serde(skip)]
+    pub shape: TextShape,
+}
+
+/// 文本组件在 JSON 中的形式
+///
+/// 只在组件仍能以该形式表示时使用,例如字符串形式的组件设置了颜色后输出对象
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub enum TextShape {
+    /// `{"text": ...}`
+    #[default]
+    Object,
+    /// `"text"`,数字和布尔值也按字符串处理
+    String,
+    /// `[父组件, extra...]`
+    Array {
+        /// 父组件的形式
+        head: Box<TextShape>,
+        /// 父组件自身的 extra 数量,其余 extra 来自数组元素
+        head_extra: usize,
+    },
+    /// 不是 JSON 的纯文本,只出现在数据包的 JSON 文本字段中
+    Plain,
+}
+
+/// 点击事件
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ClickEvent {
+    /// open_url / run_command / suggest_command / change_page / copy_to_clipboard ...
+    pub action: String,
+    /// 1.21.5 之前的事件参数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    /// 1.21.5 起按动作区分的参数 (url, command, page ...)
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+/// 悬停事件
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct HoverEvent {
+    /// show_text / show_item / show_entity
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contents: Option<Value>,
+    /// 旧版本的事件参数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    /// 1.21.5 起按动作区分的参数
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+impl TextComponent {
+    /// 纯文本组件
+    pub fn text(text: impl Into<String>) -> Self {
+        TextComponent {
+            text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+    /// 翻译组件
+    pub fn translate(key: impl Into<String>, with: Vec<TextComponent>) -> Self {
+        TextComponent {
+            translate: Some(key.into()),
+            with,
+            ..Default::default()
+        }
+    }
+    pub fn color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+    pub fn bold(mut self, bold: bool) -> Self {
+        self.bold = Some(bold);
+        self
+    }
+    pub fn italic(mut self, italic: bool) -> Self {
+        self.italic = Some(italic);
+        self
+    }
+    pub fn extra(mut self, extra: TextComponent) -> Self {
+        self.extra.push(extra);
+        self
+    }
+    /// 设置序列化时使用的形式
+    pub fn shape(mut self, shape: TextShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// 只有文本、没有样式和其他内容时返回文本,可以用字符串形式表示
+    fn as_plain_text(&self) -> Option<&str> {
+        let text = self.text.as_deref()?;
+        let bare = TextComponent {
+            text: self.text.clone(),
+            shape: self.shape.clone(),
+            ..Default::default()
+        };
+        (*self == bare).then_some(text)
+    }
+
+    /// 从 JSON 解析,支持字符串、数组和对象形式
+    pub fn from_value(value: Value) -> serde_json::Result<Self> {
+        match value {
+            Value::String(text) => Ok(TextComponent::text(text).shape(TextShape::String)),
+            Value::Bool(_) | Value::Number(_) => Ok(TextComponent::text(value.to_string()).shape(TextShape::String)),
+            Value::Array(list) => {
+                let mut list = list.into_iter();
+                let mut parent = match list.next() {
+                    Some(first) => TextComponent::from_value(first)?,
+                    None => return Err(serde_json::Error::custom("empty text component list")),
+                };
+                let head = std::mem::take(&mut parent.shape);
+                let head_extra = parent.extra.len();
+                for item in list {
+                    parent.extra.push(TextComponent::from_value(item)?);
+                }
+                Ok(parent.shape(TextShape::Array {
+                    head: Box::new(head),
+                    head_extra,
+                }))
+            }
+            Value::Object(_) => TextComponent::deserialize(value),
+            Value::Null => Err(serde_json::Error::custom("null text component")),
+        }
+    }
+
+    /// 从 NBT 解析 (1.20.3 起文本组件使用网络 NBT)
+    pub fn from_nbt(tag: &NbtTag) -> serde_json::Result<Self> {
+        TextComponent::from_value(nbt_to_json(tag))
+    }
+
+    /// 转换为 NBT (1.20.3 起的网络格式)
+    pub fn to_nbt(&self) -> NbtTag {
+        NbtTag::from_json(&self.to_value())
+    }
+
+    /// 转换为 JSON 对象
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    /// 拼接所有文本,忽略样式 (翻译组件输出翻译键)
+    pub fn plain_text(&self) -> String {
+        let mut out = String::new();
+        self.push_plain_text(&mut out);
+        out
+    }
+
+    fn push_plain_text(&self, out: &mut String) {
+        if let Some(text) = &self.text {
+            out.push_str(text);
+        } else if let Some(translate) = &self.translate {
+            out.push_str(translate);
+        } else if let Some(keybind) = &self.keybind {
+            out.push_str(keybind);
+        }
+        for extra in &self.extra {
+            extra.push_plain_text(out);
+        }
+    }
+}
+
+/// NBT 转 JSON: 样式字段中的 Byte 转为布尔值,混合列表中的 `{"": value}` 展开为 value
+fn nbt_to_json(tag: &NbtTag) -> Value {
+    match tag {
+        NbtTag::List(list) => Value::Array(list.iter().map(nbt_to_json).collect()),
+        NbtTag::Compound(compound) => {
+            if compound.len() == 1
+                && let Some(inner) = compound.get("")
+            {
+                return nbt_to_json(inner);
+            }
+            Value::Object(
+                compound
+                    .iter()
+                    .map(|(key, value)| {
+                        let value = match (key.as_str(), value) {
+                            ("bold" | "italic" | "underlined" | "strikethrough" | "obfuscated", NbtTag::Byte(flag)) => {
+                                Value::Bool(*flag != 0)
+                            }
+                            _ => nbt_to_json(value),
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect(),
+            )
+        }
+        other => other.to_json(),
+    }
+}
+
+impl Serialize for TextComponent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match &self.shape {
+            TextShape::String | TextShape::Plain => {
+                if let Some(text) = self.as_plain_text() {
+                    return serializer.serialize_str(text);
+                }
+            }
+            TextShape::Array { head, head_extra } if *head_extra < self.extra.len() => {
+                let mut parent = self.clone();
+                let items = parent.extra.split_off(*head_extra);
+                parent.shape = (**head).clone();
+                let mut seq = serializer.serialize_seq(Some(items.len() + 1))?;
+                seq.serialize_element(&parent)?;
+                for item in &items {
+                    seq.serialize_element(item)?;
+                }
+                return seq.end();
+            }
+            _ => {}
+        }
+        TextComponent::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TextComponent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = <Value as Deserialize>::deserialize(deserializer)?;
+        TextComponent::from_value(value).map_err(D::Error::custom)
+    }
+}
+
+impl From<&str> for TextComponent {
+    fn from(value: &str) -> Self {
+        TextComponent::text(value)
+    }
+}
+
+impl From<String> for TextComponent {
+    fn from(value: String) -> Self {
+        TextComponent::text(value)
+    }
+}
+
+impl NbtConvert for TextComponent {
+    fn from_nbt_tag(tag: NbtTag) -> Result<Self, String> {
+        TextComponent::from_nbt(&tag).map_err(|e| format!("Invalid NBT text component: {}", e))
+    }
+    fn to_nbt_tag(&self) -> NbtTag {
+        self.to_nbt()
+    }
+}
+
+impl Subdata for TextComponent {
+    fn new() -> Self {
+        TextComponent::default()
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        match self.as_plain_text().filter(|_| self.shape == TextShape::Plain) {
+            Some(text) => w.string(text),
+            // 直接序列化,键按字段声明顺序输出
+            None => w.json_as(self),
+        }
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        let word = r.string();
+        if r.failed() {
+            return;
+        }
+        *self = match serde_json::from_str(&word) {
+            Ok(value) => match TextComponent::from_value(value) {
+                Ok(component) => component,
+                Err(e) => {
+                    r.fail(format!("Invalid text component: {}", e));
+                    return;
+                }
+            },
+            // 不是 JSON 时(例如纯文本的断开原因)作为纯文本
+            Err(_) => TextComponent::text(word).shape(TextShape::Plain),
+        };
+    }
+}
+
+/// with / extra / 数组形式的最大嵌套深度
+#[cfg(feature = "arbitrary")]
+const MAX_ARBITRARY_DEPTH: usize = 2;
+
+#[cfg(feature = "arbitrary")]
+impl TextComponent {
+    /// 对象形式中有对应字段的键,extra_fields 不能使用
+    const KNOWN_KEYS: &[&str] = &[
+        "text", "translate", "with", "keybind", "color", "font", "bold", "italic", "underlined",
+        "strikethrough", "obfuscated", "insertion", "extra", "clickEvent", "click_event", "hoverEvent",
+        "hover_event",
+    ];
+
+    /// 生成字符串、对象或数组形式的组件
+    ///
+    /// nbt 为 true 时只生成转换为 NBT 再转回后不变的组件:
+    /// extra_fields 和事件参数中没有布尔值、null 和空键
+    fn arbitrary_component(u: &mut arbitrary::Unstructured, nbt: bool, depth: usize) -> arbitrary::Result<Self> {
+        let shapes = if depth < MAX_ARBITRARY_DEPTH { 3 } else { 2 };
+        match u.choose_index(shapes)? {
+            0 => Ok(TextComponent::text(NbtTag::arbitrary_string(u)?).shape(TextShape::String)),
+            1 => TextComponent::arbitrary_object(u, nbt, depth),
+            _ => {
+                // 数组的第一个元素是父组件,其余元素追加到父组件的 extra 之后
+                let mut parent = TextComponent::arbitrary_component(u, nbt, depth + 1)?;
+                let head = std::mem::take(&mut parent.shape);
+                let head_extra = parent.extra.len();
+                parent.extra.push(TextComponent::arbitrary_component(u, nbt, depth + 1)?);
+                parent.extra.extend(TextComponent::arbitrary_children(u, nbt, depth)?);
+                Ok(parent.shape(TextShape::Array {
+                    head: Box::new(head),
+                    head_extra,
+                }))
+            }
+        }
+    }
+
+    fn arbitrary_object(u: &mut arbitrary::Unstructured, nbt: bool, depth: usize) -> arbitrary::Result<Self> {
+        const COLORS: &[&str] = &["white", "red", "gold", "#55FF55"];
+        const CLICK_ACTIONS: &[&str] = &["open_url", "run_command", "suggest_command", "change_page", "copy_to_clipboard"];
+        const HOVER_ACTIONS: &[&str] = &["show_text", "show_item", "show_entity"];
+        let mut component = TextComponent {
+            text: arbitrary_text(u)?,
+            translate: arbitrary_text(u)?,
+            keybind: arbitrary_text(u)?,
+            font: arbitrary_text(u)?,
+            insertion: arbitrary_text(u)?,
+            bold: u.arbitrary()?,
+            italic: u.arbitrary()?,
+            underlined: u.arbitrary()?,
+            strikethrough: u.arbitrary()?,
+            obfuscated: u.arbitrary()?,
+            ..Default::default()
+        };
+        if u.arbitrary()? {
+            component.color = Some(u.choose(COLORS)?.to_string());
+        }
+        if component.translate.is_some() {
+            component.with = TextComponent::arbitrary_children(u, nbt, depth)?;
+        }
+        component.extra = TextComponent::arbitrary_children(u, nbt, depth)?;
+        if u.arbitrary()? {
+            component.click_event = Some(ClickEvent {
+                action: u.choose(CLICK_ACTIONS)?.to_string(),
+                value: arbitrary_event_value(u, nbt)?,
+                fields: arbitrary_fields(u, nbt, &["action", "value"])?,
+            });
+        }
+        if u.arbitrary()? {
+            component.hover_event = Some(HoverEvent {
+                action: u.choose(HOVER_ACTIONS)?.to_string(),
+                contents: arbitrary_event_value(u, nbt)?,
+                value: arbitrary_event_value(u, nbt)?,
+                fields: arbitrary_fields(u, nbt, &["action", "contents", "value"])?,
+            });
+        }
+        component.extra_fields = arbitrary_fields(u, nbt, TextComponent::KNOWN_KEYS)?;
+        Ok(component)
+    }
+
+    /// 生成 with / extra,达到最大深度后为空
+    fn arbitrary_children(u: &mut arbitrary::Unstructured, nbt: bool, depth: usize) -> arbitrary::Result<Vec<Self>> {
+        if depth >= MAX_ARBITRARY_DEPTH {
+            return Ok(Vec::new());
+        }
+        crate::net_types::arbitrary_data::arbitrary_items(u, usize::MAX, |u| {
+            TextComponent::arbitrary_component(u, nbt, depth + 1)
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+fn arbitrary_text(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Option<String>> {
+    Ok(if u.arbitrary()? { Some(NbtTag::arbitrary_string(u)?) } else { None })
+}
+
+#[cfg(feature = "arbitrary")]
+fn arbitrary_value(u: &mut arbitrary::Unstructured, nbt: bool) -> arbitrary::Result<Value> {
+    use crate::net_types::arbitrary_data::{arbitrary_nbt_json, ArbitraryData};
+    if nbt { arbitrary_nbt_json(u, 1) } else { Value::arbitrary_data(u) }
+}
+
+/// 事件参数为 null 时读回为 None
+#[cfg(feature = "arbitrary")]
+fn arbitrary_event_value(u: &mut arbitrary::Unstructured, nbt: bool) -> arbitrary::Result<Option<Value>> {
+    Ok(if u.arbitrary()? { Some(arbitrary_value(u, nbt)?).filter(|value| !value.is_null()) } else { None })
+}
+
+/// 生成展开到对象中的其他键,与 reserved 中的键重名时加上后缀
+#[cfg(feature = "arbitrary")]
+fn arbitrary_fields(u: &mut arbitrary::Unstructured, nbt: bool, reserved: &[&str]) -> arbitrary::Result<Map<String, Value>> {
+    let fields = crate::net_types::arbitrary_data::arbitrary_items(u, usize::MAX, |u| {
+        let mut key = crate::net_types::arbitrary_data::arbitrary_nbt_key(u)?;
+        if reserved.contains(&key.as_str()) {
+            key.push('_');
+        }
+        Ok((key, arbitrary_value(u, nbt)?))
+    })?;
+    Ok(fields.into_iter().collect())
+}
+
+/// 不生成 [`TextShape::Plain`]: `#[json]` 字段会把它读回为字符串形式
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for TextComponent {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        TextComponent::arbitrary_component(u, false, 0)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryNbt for TextComponent {
+    fn arbitrary_nbt(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        TextComponent::arbitrary_component(u, true, 0)
+    }
+}
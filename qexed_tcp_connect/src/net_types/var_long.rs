@@ -0,0 +1,27 @@
+use crate::{
+    net_types::subdata::Subdata,
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+/// 变长 64 位整数,最多 10 字节
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VarLong(pub i64);
+impl Subdata for VarLong {
+    fn new() -> Self {
+        VarLong(0)
+    }
+    fn serialize(&self, w: &mut PacketWriter) {
+        w.varlong(self);
+    }
+
+    fn deserialize(&mut self, r: &mut PacketReader) {
+        *self = r.varlong();
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl crate::net_types::arbitrary_data::ArbitraryData for VarLong {
+    fn arbitrary_data(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Self> {
+        Ok(VarLong(u.arbitrary()?))
+    }
+}
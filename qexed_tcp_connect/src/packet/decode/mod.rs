@@ -1,118 +1,409 @@
-use bytes::Buf;
+use std::fmt;
 
-use crate::net_types::{subdata::Subdata, var_int::VarInt};
+use bytes::{Buf, Bytes};
 
+use crate::net_types::{
+    array::LengthPrefix, nbt::NbtConvert, subdata::Subdata, var_int::VarInt, var_long::VarLong,
+};
+use crate::packet::{version_in_range, UNKNOWN_PROTOCOL_VERSION};
+
+/// 结构体的最大嵌套层数,防止互相包含的类型 (例如物品中的物品) 导致栈溢出
+const MAX_NESTING: usize = 128;
+
+/// 解码失败: 数据不完整或格式错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    /// 错误信息
+    pub message: String,
+    /// 出错时正在读取的字段 (由 packet 宏记录)
+    pub field: Option<&'static str>,
+    /// 出错时剩余的字节数
+    pub remaining: usize,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.field {
+            Some(field) => write!(f, "{} in field `{}` ({} bytes left)", self.message, field, self.remaining),
+            None => write!(f, "{} ({} bytes left)", self.message, self.remaining),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// 一个字段的边界,用剩余字节数表示
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMark {
+    pub name: &'static str,
+    /// 读取该字段前的剩余字节数
+    pub before: usize,
+    /// 读取该字段后的剩余字节数
+    pub after: usize,
+    /// 字段为结构体 (或结构体的数组、可选值) 时,其中各字段的边界
+    pub children: Vec<FieldMark>,
+}
+
+/// 数据包读取器
+///
+/// 网络数据不可信,读取不会 panic: 数据不足或格式错误时记录第一个错误,
+/// 之后的读取不再消耗数据,只返回默认值。调用方在反序列化结束后用
+/// [`PacketReader::finish`] 取出错误,出错后读出的值没有意义。
+///
+/// 数据合法但无法继续解析时 (例如没有长度前缀的未知物品组件),读取方保存原始字节后调用
+/// [`PacketReader::stop`],之后的读取同样只返回默认值,数据包需要按原始字节转发。
 pub struct PacketReader<'a> {
     pub buf: Box<&'a mut dyn Buf>,
+    /// 字段边界记录,每一层嵌套的结构体一个列表
+    fields: Option<Vec<Vec<FieldMark>>>,
+    /// 最近开始读取的字段
+    current_field: Option<&'static str>,
+    error: Option<DecodeError>,
+    /// 停止解析的原因
+    opaque: Option<String>,
+    /// 当前的结构体嵌套层数
+    depth: usize,
+    protocol_version: i32,
 }
 
 impl<'a> PacketReader<'a> {
     pub fn new(buf: Box<&'a mut dyn Buf>) -> Self {
-        Self { buf }
+        Self {
+            buf,
+            fields: None,
+            current_field: None,
+            error: None,
+            opaque: None,
+            depth: 0,
+            protocol_version: UNKNOWN_PROTOCOL_VERSION,
+        }
+    }
+    /// 设置协议版本,带 `#[since]` / `#[until]` 的字段按它决定是否读取
+    pub fn with_protocol_version(mut self, protocol_version: i32) -> Self {
+        self.protocol_version = protocol_version;
+        self
+    }
+    pub fn protocol_version(&self) -> i32 {
+        self.protocol_version
+    }
+    /// 当前协议版本是否在 `[since, until]` 范围内
+    pub fn supports(&self, since: Option<i32>, until: Option<i32>) -> bool {
+        version_in_range(self.protocol_version, since, until)
+    }
+    /// 开启字段边界记录,用于数据包转储
+    pub fn track_fields(&mut self) {
+        self.fields = Some(vec![vec![]]);
+    }
+    /// 记录即将读取的字段,由 packet 宏在每个字段前调用
+    pub fn field(&mut self, name: &'static str) {
+        self.current_field = Some(name);
+        let remaining = self.buf.remaining();
+        if let Some(level) = self.fields.as_mut().and_then(|levels| levels.last_mut()) {
+            if let Some(previous) = level.last_mut() {
+                previous.after = remaining;
+            }
+            level.push(FieldMark {
+                name,
+                before: remaining,
+                after: remaining,
+                children: vec![],
+            });
+        }
+    }
+    /// 取出已记录的字段边界,嵌套结构体的字段在 `children` 中
+    pub fn take_fields(&mut self) -> Vec<FieldMark> {
+        let remaining = self.buf.remaining();
+        let Some(levels) = &mut self.fields else {
+            return vec![];
+        };
+        let mut fields = std::mem::replace(levels, vec![vec![]]).swap_remove(0);
+        if let Some(last) = fields.last_mut() {
+            last.after = remaining;
+        }
+        fields
+    }
+    /// 开始读取嵌套的结构体,之后记录的字段属于当前字段
+    fn enter(&mut self) {
+        if let Some(levels) = &mut self.fields {
+            levels.push(vec![]);
+        }
+    }
+    /// 嵌套的结构体读取结束
+    fn leave(&mut self) {
+        let remaining = self.buf.remaining();
+        let Some(levels) = &mut self.fields else {
+            return;
+        };
+        let Some(mut children) = levels.pop().filter(|_| !levels.is_empty()) else {
+            return;
+        };
+        if let Some(last) = children.last_mut() {
+            last.after = remaining;
+        }
+        if let Some(parent) = levels.last_mut() {
+            match parent.last_mut() {
+                Some(field) => field.children.append(&mut children),
+                // 没有所属的字段 (直接读取的结构体),作为同一层的字段
+                None => parent.append(&mut children),
+            }
+        }
+    }
+    /// 记录解码错误,只保留第一个
+    pub fn fail(&mut self, message: impl fmt::Display) {
+        // 停止解析后读出的都是默认值,不再记录错误
+        if self.error.is_none() && self.opaque.is_none() {
+            self.error = Some(DecodeError {
+                message: message.to_string(),
+                field: self.current_field,
+                remaining: self.buf.remaining(),
+            });
+        }
+    }
+    /// 停止解析: 之后的读取不再消耗数据,但不算解码错误
+    pub fn stop(&mut self, reason: impl fmt::Display) {
+        if self.error.is_none() && self.opaque.is_none() {
+            self.opaque = Some(reason.to_string());
+        }
+    }
+    /// 停止解析的原因,见 [`PacketReader::stop`]
+    pub fn opaque(&self) -> Option<&str> {
+        self.opaque.as_deref()
+    }
+    /// 是否已经出错或停止解析
+    pub fn failed(&self) -> bool {
+        self.error.is_some() || self.opaque.is_some()
+    }
+    /// 已记录的解码错误
+    pub fn error(&self) -> Option<&DecodeError> {
+        self.error.as_ref()
+    }
+    /// 取出解码错误,没有出错时返回 `Ok`
+    pub fn finish(&mut self) -> Result<(), DecodeError> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+    /// 检查是否还剩 n 个字节,不够时记录错误;出错后总是返回 false
+    pub fn ensure(&mut self, n: usize) -> bool {
+        if self.failed() {
+            return false;
+        }
+        if self.buf.remaining() < n {
+            let message = format!("Unexpected end of data: need {} bytes", n);
+            self.fail(message);
+            return false;
+        }
+        true
+    }
+    /// 检查来自网络的元素个数: 不能为负数,按每个元素至少 `element_size`
+    /// 个字节也不能超过剩余数据。不合法时记录错误并返回 0
+    pub fn check_len(&mut self, len: i64, element_size: usize) -> usize {
+        if self.failed() {
+            return 0;
+        }
+        if len < 0 {
+            self.fail(format!("Negative length: {}", len));
+            return 0;
+        }
+        let needed = (len as u64).saturating_mul(element_size as u64);
+        if needed > self.buf.remaining() as u64 {
+            self.fail(format!("Length {} exceeds remaining data", len));
+            return 0;
+        }
+        len as usize
+    }
+    /// 读取 VarInt 长度前缀,检查方式同 [`PacketReader::check_len`]
+    pub fn varint_len(&mut self, element_size: usize) -> usize {
+        let len = self.varint().0;
+        self.check_len(len as i64, element_size)
+    }
+    /// 按网络数据中的元素个数创建 Vec,预分配的内存不超过剩余字节数
+    pub fn preallocate<T>(&self, len: usize) -> Vec<T> {
+        Vec::with_capacity(len.min(self.buf.remaining() / std::mem::size_of::<T>().max(1)))
     }
     pub fn u8(&mut self) -> u8 {
-        self.buf.get_u8()
+        if self.ensure(1) {
+            self.buf.get_u8()
+        } else {
+            0
+        }
     }
     pub fn i8(&mut self) -> i8 {
-        self.buf.get_i8()
+        if self.ensure(1) {
+            self.buf.get_i8()
+        } else {
+            0
+        }
     }
     pub fn bool(&mut self) -> bool {
         self.u8() != 0
     }
     pub fn u16(&mut self) -> u16 {
-        self.buf.get_u16()
+        if self.ensure(2) {
+            self.buf.get_u16()
+        } else {
+            0
+        }
     }
     pub fn u32(&mut self) -> u32 {
-        self.buf.get_u32()
+        if self.ensure(4) {
+            self.buf.get_u32()
+        } else {
+            0
+        }
     }
     pub fn u64(&mut self) -> u64 {
-        self.buf.get_u64()
+        if self.ensure(8) {
+            self.buf.get_u64()
+        } else {
+            0
+        }
     }
     pub fn i16(&mut self) -> i16 {
-        self.buf.get_i16()
+        if self.ensure(2) {
+            self.buf.get_i16()
+        } else {
+            0
+        }
     }
     pub fn i32(&mut self) -> i32 {
-        self.buf.get_i32()
+        if self.ensure(4) {
+            self.buf.get_i32()
+        } else {
+            0
+        }
     }
     pub fn i64(&mut self) -> i64 {
-        self.buf.get_i64()
+        if self.ensure(8) {
+            self.buf.get_i64()
+        } else {
+            0
+        }
     }
     pub fn f32(&mut self) -> f32 {
-        self.buf.get_f32()
+        if self.ensure(4) {
+            self.buf.get_f32()
+        } else {
+            0.0
+        }
     }
     pub fn f64(&mut self) -> f64 {
-        self.buf.get_f64()
+        if self.ensure(8) {
+            self.buf.get_f64()
+        } else {
+            0.0
+        }
     }
     pub fn string(&mut self) -> String {
-        let len = self.varint().0 as usize;
-        let bytes = self.buf.copy_to_bytes(len);
-        String::from_utf8_lossy(&bytes).to_string()
+        let len = self.varint_len(1);
+        let bytes = self.bytes(len);
+        // 合法 UTF-8 时直接复用字节,只在非法时做有损转换
+        String::from_utf8(Vec::from(bytes))
+            .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned())
     }
-    pub fn byte_all(&mut self) -> Vec<u8> {
+    /// 读取 len 个字节,底层是 `Bytes` / `BytesMut` 时不复制,直接引用原始帧
+    pub fn bytes(&mut self, len: usize) -> Bytes {
+        if self.ensure(len) {
+            self.buf.copy_to_bytes(len)
+        } else {
+            Bytes::new()
+        }
+    }
+    /// 读取剩余的全部字节,不复制
+    pub fn rest(&mut self) -> Bytes {
         let len = self.buf.remaining();
-        let bytes = self.buf.copy_to_bytes(len);
-        bytes.to_vec()
+        self.bytes(len)
+    }
+    pub fn byte_all(&mut self) -> Vec<u8> {
+        self.rest().to_vec()
     }
     pub fn option_string(&mut self) -> Option<String> {
         let is_have = self.bool();
         if is_have {
-            return Some(self.string());
+            Some(self.string())
         } else {
-            return None;
+            None
         }
     }
+    /// 读取 JSON 文本,不是合法 JSON 时(例如纯文本的断开原因)作为字符串返回
     pub fn json(&mut self) -> serde_json::Value {
         let word = self.string();
-        return serde_json::json!(word);
+        serde_json::from_str(&word).unwrap_or(serde_json::Value::String(word))
     }
     pub fn uuid(&mut self) -> uuid::Uuid {
-        // 创建 16 字节数组
-        let mut bytes = [0u8; 16];
-        self.buf.copy_to_slice(&mut bytes);
-        uuid::Uuid::from_bytes(bytes)
+        uuid::Uuid::from_bytes(self.fixed_bytes())
     }
     pub fn varint(&mut self) -> VarInt {
         let mut value = 0;
-        let mut position = 0;
 
-        for _ in 0..5 {
+        for position in 0..5 {
+            if !self.ensure(1) {
+                return VarInt(0);
+            }
             let byte = self.buf.get_u8();
             value |= (byte as i32 & 0x7F) << (7 * position);
 
             if (byte & 0x80) == 0 {
                 return VarInt(value);
             }
+        }
+
+        self.fail("VarInt too big");
+        VarInt(0)
+    }
+    pub fn varlong(&mut self) -> VarLong {
+        let mut value = 0;
+
+        for position in 0..10 {
+            if !self.ensure(1) {
+                return VarLong(0);
+            }
+            let byte = self.buf.get_u8();
+            value |= (byte as i64 & 0x7F) << (7 * position);
 
-            position += 1;
+            if (byte & 0x80) == 0 {
+                return VarLong(value);
+            }
         }
 
-        panic!("Invalid VarInt");
+        self.fail("VarLong too big");
+        VarLong(0)
     }
     /// 读取固定长度的字节数组
     pub fn fixed_bytes<const N: usize>(&mut self) -> [u8; N] {
-        // 检查是否有足够的数据
         let mut result = [0u8; N];
-        for i in 0..N {
-            result[i] = self.u8();
+        if self.ensure(N) {
+            self.buf.copy_to_slice(&mut result);
         }
         result
     }
     pub fn vec<T: Subdata>(&mut self) -> Vec<T> {
-        let len = self.varint().0 as usize; // 获取 VarInt 的值
-        // 清空现有属性并预分配空间
-        let mut value: Vec<T> = vec![];
-        value.reserve(len);
-        // 遍历读取每个属性
+        let len = self.varint_len(1);
+        self.prefixed_items(len)
+    }
+    /// 读取 len 个元素,len 来自已读出的长度前缀;出错后不再继续读取
+    pub fn prefixed_items<T: Subdata>(&mut self, len: usize) -> Vec<T> {
+        let mut value = self.preallocate(len);
         for _ in 0..len {
-            let mut a = T::new();
-            a.deserialize(self);
-            value.push(a);
+            if self.failed() {
+                break;
+            }
+            value.push(self.deserialize());
         }
         value
     }
     pub fn deserialize<T: Subdata>(&mut self) -> T {
         let mut t = T::new();
+        if self.depth >= MAX_NESTING {
+            self.fail("Nesting too deep");
+            return t;
+        }
+        self.depth += 1;
+        self.enter();
         t.deserialize(self);
+        self.leave();
+        self.depth -= 1;
         t
     }
     pub fn option<T: Subdata>(&mut self) -> Option<T> {
@@ -120,8 +411,59 @@ impl<'a> PacketReader<'a> {
         if !is_true {
             return None;
         }
-        let mut v = T::new();
-        v.deserialize(self);
-        return Some(v);
+        Some(self.deserialize())
+    }
+    /// 读取以 `L` 为长度前缀的数组
+    pub fn prefixed_vec<L: LengthPrefix, T: Subdata>(&mut self) -> Vec<T> {
+        let len = L::read_len(self);
+        self.prefixed_items(len)
+    }
+    /// 读取固定 len 个元素,没有长度前缀
+    pub fn fixed_vec<T: Subdata>(&mut self, len: usize) -> Vec<T> {
+        // 个数表达式可能引用前面读出的字段,同样不可信
+        let len = self.check_len(len as i64, 1);
+        self.prefixed_items(len)
+    }
+    /// 一直读取元素直到数据结束
+    ///
+    /// 元素没有读取任何数据时记录解码错误,否则会无限循环
+    pub fn rest_vec<T: Subdata>(&mut self) -> Vec<T> {
+        let mut value = Vec::new();
+        while self.buf.has_remaining() && !self.failed() {
+            let remaining = self.buf.remaining();
+            value.push(self.deserialize());
+            if self.buf.remaining() == remaining {
+                self.fail(format!("Element {} of rest array read no data", value.len() - 1));
+            }
+        }
+        value
+    }
+    /// 读取 JSON 文本并解析为 T
+    pub fn json_as<T: serde::de::DeserializeOwned + Default>(&mut self) -> T {
+        let word = self.string();
+        if self.failed() {
+            return T::default();
+        }
+        match serde_json::from_str(&word) {
+            Ok(value) => value,
+            Err(e) => {
+                self.fail(format!("Invalid JSON: {}", e));
+                T::default()
+            }
+        }
+    }
+    /// 读取网络 NBT 并转换为 T
+    pub fn nbt_as<T: NbtConvert + Default>(&mut self) -> T {
+        let nbt: crate::net_types::nbt::Nbt = self.deserialize();
+        if self.failed() {
+            return T::default();
+        }
+        match T::from_nbt_tag(nbt.0) {
+            Ok(value) => value,
+            Err(e) => {
+                self.fail(e);
+                T::default()
+            }
+        }
     }
 }
@@ -1,14 +1,37 @@
 use bytes::{BufMut, BytesMut};
 
-use crate::net_types::{subdata::Subdata, var_int::VarInt};
+use crate::net_types::{
+    array::LengthPrefix,
+    nbt::{Nbt, NbtConvert},
+    subdata::Subdata,
+    var_int::VarInt,
+    var_long::VarLong,
+};
+use crate::packet::{version_in_range, UNKNOWN_PROTOCOL_VERSION};
 
 pub struct PacketWriter<'a> {
     buf: &'a mut BytesMut,
+    protocol_version: i32,
 }
 
 impl<'a> PacketWriter<'a> {
     pub fn new(buf: &'a mut BytesMut) -> Self {
-        Self { buf }
+        Self {
+            buf,
+            protocol_version: UNKNOWN_PROTOCOL_VERSION,
+        }
+    }
+    /// 设置协议版本,带 `#[since]` / `#[until]` 的字段按它决定是否写入
+    pub fn with_protocol_version(mut self, protocol_version: i32) -> Self {
+        self.protocol_version = protocol_version;
+        self
+    }
+    pub fn protocol_version(&self) -> i32 {
+        self.protocol_version
+    }
+    /// 当前协议版本是否在 `[since, until]` 范围内
+    pub fn supports(&self, since: Option<i32>, until: Option<i32>) -> bool {
+        version_in_range(self.protocol_version, since, until)
     }
 
     pub fn u8(&mut self, value: u8) {
@@ -54,6 +77,10 @@ impl<'a> PacketWriter<'a> {
     pub fn byte_all(&mut self, value: Vec<u8>) {
         self.buf.put_slice(&value);
     }
+    /// 写入字节,不带长度前缀
+    pub fn bytes(&mut self, value: &[u8]) {
+        self.buf.put_slice(value);
+    }
 
     pub fn option_string(&mut self, value: Option<&str>) {
         if let Some(v) = value {
@@ -86,11 +113,25 @@ impl<'a> PacketWriter<'a> {
             }
         }
     }
+    pub fn varlong(&mut self, value: &VarLong) {
+        let mut val = value.0 as u64;
+        loop {
+            let mut temp = (val & 0x7F) as u8;
+            val >>= 7;
+            if val != 0 {
+                temp |= 0x80;
+            }
+            self.buf.put_u8(temp);
+            if val == 0 {
+                break;
+            }
+        }
+    }
     /// 读取固定长度的字节数组
     pub fn fixed_bytes<const N: usize>(&mut self,value:&[u8; N]) {
         // 检查是否有足够的数据
-        for i in 0..N {
-            self.u8(value[i]);
+        for byte in value {
+            self.u8(*byte);
         }
     }
     pub fn vec<T:Subdata>(&mut self,value: &Vec<T>){
@@ -111,4 +152,34 @@ impl<'a> PacketWriter<'a> {
             self.bool(false);
         }
     }
+    /// 写入以 `L` 为长度前缀的数组
+    pub fn prefixed_vec<L: LengthPrefix, T: Subdata>(&mut self, value: &[T]) {
+        assert!(value.len() <= L::MAX, "Array length {} exceeds limit {}", value.len(), L::MAX);
+        L::write_len(self, value.len());
+        for item in value {
+            item.serialize(self);
+        }
+    }
+    /// 写入固定 len 个元素,没有长度前缀
+    pub fn fixed_vec<T: Subdata>(&mut self, value: &[T], len: usize) {
+        assert_eq!(value.len(), len, "Fixed array length mismatch");
+        self.rest_vec(value);
+    }
+    /// 写入全部元素,没有长度前缀
+    pub fn rest_vec<T: Subdata>(&mut self, value: &[T]) {
+        for item in value {
+            item.serialize(self);
+        }
+    }
+    /// 将 T 序列化为 JSON 文本写入
+    pub fn json_as<T: serde::Serialize>(&mut self, value: &T) {
+        match serde_json::to_string(value) {
+            Ok(word) => self.string(&word),
+            Err(e) => panic!("Invalid JSON field: {}", e),
+        }
+    }
+    /// 将 T 转换为网络 NBT 写入
+    pub fn nbt_as<T: NbtConvert>(&mut self, value: &T) {
+        Nbt(value.to_nbt_tag()).serialize(self);
+    }
 }
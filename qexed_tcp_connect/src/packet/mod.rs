@@ -1,2 +1,37 @@
 pub mod decode;
-pub mod encode;
\ No newline at end of file
+pub mod encode;
+#[cfg(feature = "arbitrary")]
+pub mod round_trip;
+
+/// 协议版本未知 (例如握手之前)
+pub const UNKNOWN_PROTOCOL_VERSION: i32 = -1;
+
+/// 协议版本是否在 `[since, until]` 范围内 (两端都包含)
+///
+/// 版本未知时按最新版本处理: 只要求没有 `until` 上限
+pub fn version_in_range(protocol_version: i32, since: Option<i32>, until: Option<i32>) -> bool {
+    if protocol_version == UNKNOWN_PROTOCOL_VERSION {
+        return until.is_none();
+    }
+    since.is_none_or(|since| protocol_version >= since) && until.is_none_or(|until| protocol_version <= until)
+}
+
+/// 两个协议版本范围是否有重叠,用于在编译期检查数据包ID冲突
+pub const fn version_ranges_overlap(
+    a_since: Option<i32>,
+    a_until: Option<i32>,
+    b_since: Option<i32>,
+    b_until: Option<i32>,
+) -> bool {
+    let since = match (a_since, b_since) {
+        (Some(a), Some(b)) => if a > b { a } else { b },
+        (Some(v), None) | (None, Some(v)) => v,
+        (None, None) => i32::MIN,
+    };
+    let until = match (a_until, b_until) {
+        (Some(a), Some(b)) => if a < b { a } else { b },
+        (Some(v), None) | (None, Some(v)) => v,
+        (None, None) => i32::MAX,
+    };
+    since <= until
+}
@@ -0,0 +1,96 @@
+use arbitrary::Unstructured;
+use bytes::BytesMut;
+
+use crate::net_types::{
+    arbitrary_data::ArbitraryData,
+    packet::{Packet, PacketMeta},
+};
+use crate::packet::{decode::PacketReader, encode::PacketWriter, UNKNOWN_PROTOCOL_VERSION};
+
+/// 包池中的一个数据包: 生成任意实例的函数和读写它使用的协议版本
+///
+/// 启用 `arbitrary` 特性时,`id_to_packet!` 会生成包含所有数据包的 `ARBITRARY_PACKETS`
+#[derive(Clone, Copy)]
+pub struct ArbitraryPacket {
+    pub name: &'static str,
+    /// 数据包有 `until` 时为 `until`,否则为最新版本
+    pub protocol_version: i32,
+    pub generate: fn(&mut Unstructured) -> arbitrary::Result<Box<dyn Packet>>,
+}
+
+impl ArbitraryPacket {
+    pub const fn of<P: PacketMeta + ArbitraryData + 'static>() -> Self {
+        ArbitraryPacket {
+            name: P::NAME,
+            protocol_version: match P::UNTIL {
+                Some(until) => until,
+                None => UNKNOWN_PROTOCOL_VERSION,
+            },
+            generate: generate::<P>,
+        }
+    }
+}
+
+fn generate<P: Packet + ArbitraryData + 'static>(u: &mut Unstructured) -> arbitrary::Result<Box<dyn Packet>> {
+    Ok(Box::new(P::arbitrary_data(u)?))
+}
+
+/// 用 data 为每个数据包生成实例,序列化后用 id_to_packet 按同一协议版本创建数据包并反序列化,
+/// 检查解码没有出错或停止解析、读完了全部数据且与原数据包相等
+pub fn assert_round_trip(
+    packets: &[ArbitraryPacket],
+    id_to_packet: fn(u32, i32) -> Box<dyn Packet>,
+    data: &[u8],
+) {
+    for entry in packets {
+        let mut u = Unstructured::new(data);
+        // 数据不足以生成该数据包
+        let Ok(packet) = (entry.generate)(&mut u) else {
+            continue;
+        };
+
+        let mut buf = BytesMut::new();
+        let mut writer = PacketWriter::new(&mut buf).with_protocol_version(entry.protocol_version);
+        packet.serialize(&mut writer);
+        let mut bytes = buf.freeze();
+
+        let mut decoded = id_to_packet(packet.id(), entry.protocol_version);
+        let mut reader = PacketReader::new(Box::new(&mut bytes)).with_protocol_version(entry.protocol_version);
+        decoded.deserialize(&mut reader);
+        assert_eq!(reader.opaque(), None, "{}: decoding stopped", entry.name);
+        if let Err(e) = reader.finish() {
+            panic!("{}: decode error: {}", entry.name, e);
+        }
+        drop(reader);
+
+        assert!(bytes.is_empty(), "{}: {} bytes left unread", entry.name, bytes.len());
+        assert!(
+            decoded.dyn_eq(packet.as_ref()),
+            "{} did not round trip:\n  written: {:?}\n  read:    {:?}",
+            entry.name,
+            packet,
+            decoded
+        );
+    }
+}
+
+/// 用 cases 组伪随机数据调用 [`assert_round_trip`],每次运行的数据相同,失败可以重现
+pub fn assert_round_trips(
+    packets: &[ArbitraryPacket],
+    id_to_packet: fn(u32, i32) -> Box<dyn Packet>,
+    cases: usize,
+) {
+    // xorshift64
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for _ in 0..cases {
+        let len = (next() % 1024) as usize;
+        let data: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+        assert_round_trip(packets, id_to_packet, &data);
+    }
+}
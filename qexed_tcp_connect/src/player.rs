@@ -23,4 +23,9 @@ impl Player {
             view_distance:21,
         }
     }
-}
\ No newline at end of file
+}
+impl Default for Player {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,214 @@
+#![cfg(feature = "arbitrary")]
+
+use qexed_tcp_connect::{
+    arbitrary::Unstructured,
+    net_types::{
+        array::{Array, FixedArray, RestArray},
+        bitset::{Bitset, FixedBitSet},
+        chunk::ChunkSection,
+        entity_metadata::EntityMetadata,
+        id_or::IdOr,
+        identifier::Identifier,
+        light::Light,
+        nbt::{NamedNbt, Nbt, NbtCompound},
+        particle::Particle,
+        position::Position,
+        slot::Slot,
+        text_component::TextComponent,
+        var_int::VarInt,
+    },
+    packet::round_trip::assert_round_trips,
+};
+
+mod pool {
+    use super::*;
+
+    #[qexed_packet_macros::substruct]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct Property {
+        pub name: String,
+        pub has_signature: bool,
+        #[optional_if = "has_signature"]
+        pub signature: Option<String>,
+    }
+
+    #[qexed_packet_macros::packet(id = 0x00)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct Everything {
+        #[varint]
+        pub id: u32,
+        #[varlong]
+        pub seed: i64,
+        pub scale: f32,
+        pub uuid: uuid::Uuid,
+        pub name: Identifier,
+        pub position: Position,
+        pub motd: TextComponent,
+        #[prefixed(u8)]
+        pub small: Vec<u16>,
+        #[fixed(3)]
+        pub rgb: Vec<u8>,
+        pub limited: Array<VarInt, Property, 2>,
+        pub pair: FixedArray<i8, 2>,
+        #[json]
+        pub status: serde_json::Value,
+        #[nbt]
+        pub tag: NbtCompound,
+        #[nbt]
+        pub data: serde_json::Value,
+        #[nbt]
+        pub title: TextComponent,
+        pub raw: Nbt,
+        #[skip]
+        pub cached: u64,
+        pub mode: u8,
+        #[optional_if = "mode % 2 == 1"]
+        pub target: Option<VarInt>,
+        pub tail: RestArray<u8>,
+    }
+
+    #[qexed_packet_macros::substruct(tag = identifier)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub enum Action {
+        #[default]
+        Stop,
+        Move(#[varint] i32, Position),
+        Say {
+            has_text: bool,
+            #[optional_if = "has_text"]
+            text: Option<String>,
+        },
+    }
+
+    #[qexed_packet_macros::packet(id = 0x01)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct Actions {
+        pub actions: Vec<Action>,
+    }
+
+    // 只在旧版本存在的数据包按 until 版本读写
+    #[qexed_packet_macros::packet(id = 0x02, until = 766)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct Legacy {
+        #[varint]
+        pub entity: i32,
+        #[since(765)]
+        pub added: u8,
+        #[until(764)]
+        pub removed: u8,
+    }
+
+    #[qexed_packet_macros::packet(id = 0x02, since = 767)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct Modern {
+        #[until(800)]
+        pub removed_later: u8,
+        pub properties: Vec<Property>,
+    }
+
+    #[qexed_packet_macros::packet(id = 0x03)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct World {
+        pub item: Slot,
+        pub metadata: EntityMetadata,
+        pub particle: Particle,
+        pub sound: IdOr<Identifier>,
+        pub sections: Vec<ChunkSection>,
+        pub light: Light,
+        pub mask: Bitset,
+        pub flags: FixedBitSet<20>,
+        pub legacy: NamedNbt,
+    }
+
+    #[qexed_packet_macros::packet(id = 0xFFF)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct Unknown {}
+
+    impl Unknown {
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    qexed_packet_macros::id_to_packet!([Everything, Actions, Legacy, Modern, World], Unknown);
+}
+
+// 解码时停止解析的数据包即使读完了数据、值也相等,往返测试也应失败
+mod stopping {
+    use qexed_tcp_connect::net_types::{arbitrary_data::ArbitraryData, subdata::Subdata};
+    use qexed_tcp_connect::packet::{decode::PacketReader, encode::PacketWriter};
+
+    #[derive(Debug, Default, PartialEq, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Opaque;
+
+    impl Subdata for Opaque {
+        fn new() -> Self {
+            Opaque
+        }
+        fn serialize(&self, _w: &mut PacketWriter) {}
+        fn deserialize(&mut self, r: &mut PacketReader) {
+            r.stop("opaque data");
+        }
+    }
+
+    impl ArbitraryData for Opaque {
+        fn arbitrary_data(_u: &mut qexed_tcp_connect::arbitrary::Unstructured) -> qexed_tcp_connect::arbitrary::Result<Self> {
+            Ok(Opaque)
+        }
+    }
+
+    #[qexed_packet_macros::packet(id = 0x00)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct Stops {
+        pub value: Opaque,
+    }
+
+    #[qexed_packet_macros::packet(id = 0xFFF)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct Unknown {}
+
+    impl Unknown {
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    qexed_packet_macros::id_to_packet!([Stops], Unknown);
+}
+
+#[test]
+#[should_panic(expected = "Stops: decoding stopped")]
+fn stopped_decode_fails_round_trip() {
+    assert_round_trips(stopping::ARBITRARY_PACKETS, stopping::id_to_packet, 1);
+}
+
+#[test]
+fn registry_round_trips() {
+    assert_eq!(pool::ARBITRARY_PACKETS.len(), 5);
+    assert_eq!(pool::ARBITRARY_PACKETS[2].protocol_version, 766);
+    assert_round_trips(pool::ARBITRARY_PACKETS, pool::id_to_packet, 512);
+}
+
+#[test]
+fn generated_values_respect_limits() {
+    let data: Vec<u8> = (0..4096u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8 | 1).collect();
+    let mut u = Unstructured::new(&data);
+    for _ in 0..16 {
+        let packet: pool::Everything = u.arbitrary().unwrap();
+        assert_eq!(packet.rgb.len(), 3);
+        assert!(packet.limited.len() <= 2);
+        assert!(packet.small.len() <= u8::MAX as usize);
+        assert_eq!(packet.cached, 0);
+        assert_eq!(packet.target.is_some(), packet.mode % 2 == 1);
+        for property in packet.limited.data() {
+            assert_eq!(property.signature.is_some(), property.has_signature);
+        }
+    }
+
+    let legacy: pool::Legacy = Unstructured::new(&data).arbitrary().unwrap();
+    assert_eq!(legacy.removed, 0);
+    let modern: pool::Modern = Unstructured::new(&data).arbitrary().unwrap();
+    assert_eq!(modern.removed_later, 0);
+}
+
@@ -0,0 +1,67 @@
+use std::io::{ErrorKind, Write};
+
+use bytes::{Bytes, BytesMut};
+use flate2::{write::ZlibEncoder, Compression};
+use qexed_tcp_connect::{decompress_packet, read_varint, try_parse_packet, MAX_PACKET_SIZE};
+
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn varint_errors() {
+    assert_eq!(read_varint(&mut &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F][..]).unwrap(), -1);
+    assert_eq!(read_varint(&mut &[0x80][..]).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(read_varint(&mut &[0xFF; 6][..]).unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn frames_split_across_reads() {
+    let mut buffer = BytesMut::from(&[0x03, 0x00, 0x01][..]);
+    assert_eq!(try_parse_packet(&mut buffer, false).unwrap(), None);
+    buffer.extend_from_slice(&[0x02, 0x00]);
+    assert_eq!(&try_parse_packet(&mut buffer, false).unwrap().unwrap()[..], [0x00, 0x01, 0x02]);
+    assert!(try_parse_packet(&mut buffer, false).unwrap().unwrap().is_empty());
+    assert!(buffer.is_empty());
+
+    let mut negative = BytesMut::from(&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F][..]);
+    assert_eq!(try_parse_packet(&mut negative, false).unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn frame_length_limit() {
+    // 上限本身只是等待更多数据
+    let mut largest = BytesMut::from(&[0xFF, 0xFF, 0x7F][..]);
+    assert_eq!(MAX_PACKET_SIZE, 0x1F_FFFF);
+    assert_eq!(try_parse_packet(&mut largest, false).unwrap(), None);
+
+    // 超过上限时不等待数据,直接拒绝
+    let mut oversized = BytesMut::from(&[0x80, 0x80, 0x80, 0x01][..]);
+    assert_eq!(try_parse_packet(&mut oversized, false).unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn decompression() {
+    let payload = [0x42; 300];
+    let mut frame = vec![0xAC, 0x02];
+    frame.extend(zlib(&payload));
+    assert_eq!(&decompress_packet(Bytes::from(frame)).unwrap()[..], payload);
+    // 未压缩
+    assert_eq!(&decompress_packet(Bytes::from_static(&[0x00, 0x01, 0x02])).unwrap()[..], [0x01, 0x02]);
+}
+
+#[test]
+fn decompression_limits() {
+    // 声明长度超过协议上限
+    let mut oversized = vec![0x80, 0x80, 0x80, 0x05];
+    oversized.extend(zlib(&[0]));
+    assert_eq!(decompress_packet(Bytes::from(oversized)).unwrap_err().kind(), ErrorKind::InvalidData);
+
+    // 实际数据远大于声明长度,只解压到声明长度加一个字节
+    let mut bomb = vec![0x01];
+    bomb.extend(zlib(&vec![0; 1 << 20]));
+    let error = decompress_packet(Bytes::from(bomb)).unwrap_err();
+    assert_eq!(error.to_string(), "Decompressed size mismatch: expected 1, got 2");
+}
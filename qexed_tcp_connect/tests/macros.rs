@@ -0,0 +1,526 @@
+use bytes::BytesMut;
+use qexed_tcp_connect::{
+    net_types::{
+        nbt::{NbtCompound, NbtTag},
+        packet::{
+            ConnectionState, FieldDescriptor, FieldEncoding, Packet, PacketDirection, PacketMeta,
+        },
+        subdata::Subdata,
+        text_component::TextComponent,
+    },
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+
+fn encode<T: Subdata>(value: &T) -> Vec<u8> {
+    let mut buf = BytesMut::new();
+    let mut w = PacketWriter::new(&mut buf);
+    value.serialize(&mut w);
+    buf.to_vec()
+}
+
+fn decode<T: Subdata>(bytes: &[u8]) -> T {
+    let mut data = bytes;
+    let mut r = PacketReader::new(Box::new(&mut data));
+    let value = r.deserialize();
+    assert!(data.is_empty(), "{} bytes left unread", data.len());
+    value
+}
+
+#[qexed_packet_macros::substruct]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct Encodings {
+    #[varint]
+    id: i32,
+    #[varlong]
+    seed: i64,
+    #[prefixed(u16)]
+    shorts: Vec<u8>,
+    #[prefixed(varint)]
+    ints: Vec<i32>,
+    #[fixed(3)]
+    rgb: Vec<u8>,
+    #[json]
+    motd: TextComponent,
+    #[nbt]
+    tag: NbtCompound,
+    #[skip]
+    cached: u32,
+    #[rest]
+    tail: Vec<u8>,
+}
+
+#[test]
+fn field_encodings() {
+    let value = Encodings {
+        id: 300,
+        seed: -1,
+        shorts: vec![7],
+        ints: vec![1, 2],
+        rgb: vec![1, 2, 3],
+        motd: TextComponent::text("hi"),
+        tag: NbtCompound(vec![("a".into(), NbtTag::Byte(1))]),
+        cached: 42,
+        tail: vec![9, 9],
+    };
+    let bytes = encode(&value);
+    let mut expected = vec![0xAC, 0x02];
+    expected.extend([0xFF; 9]);
+    expected.push(0x01);
+    expected.extend([0x00, 0x01, 7]);
+    expected.extend([2, 0, 0, 0, 1, 0, 0, 0, 2]);
+    expected.extend([1, 2, 3]);
+    expected.push(13);
+    expected.extend(br#"{"text":"hi"}"#);
+    expected.extend([10, 1, 0, 1, b'a', 1, 0]);
+    expected.extend([9, 9]);
+    assert_eq!(bytes, expected);
+
+    let decoded: Encodings = decode(&bytes);
+    assert_eq!(decoded, Encodings { cached: 0, ..value });
+}
+
+#[qexed_packet_macros::packet(id = 0x2A)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct Conditional {
+    has_reason: bool,
+    #[optional_if = "has_reason"]
+    reason: Option<String>,
+    mode: u8,
+    #[varint]
+    #[optional_if = "mode == 2 || mode == 3"]
+    target: Option<i32>,
+}
+
+fn round_trip_packet(value: &Conditional) -> Vec<u8> {
+    let mut buf = BytesMut::new();
+    value.serialize(&mut PacketWriter::new(&mut buf));
+    let bytes = buf.to_vec();
+    let mut data = bytes.as_slice();
+    let mut decoded = Conditional::default();
+    decoded.deserialize(&mut PacketReader::new(Box::new(&mut data)));
+    assert!(data.is_empty());
+    assert_eq!(&decoded, value);
+    bytes
+}
+
+#[test]
+fn optional_if_fields() {
+    let present = Conditional {
+        has_reason: true,
+        reason: Some("x".into()),
+        mode: 2,
+        target: Some(5),
+    };
+    assert_eq!(round_trip_packet(&present), [1, 1, b'x', 2, 5]);
+
+    let absent = Conditional {
+        has_reason: false,
+        reason: None,
+        mode: 0,
+        target: None,
+    };
+    assert_eq!(round_trip_packet(&absent), [0, 0]);
+}
+
+#[test]
+fn optional_if_missing_value() {
+    // 条件成立但没有值时不写入也不 panic,读取时缺少数据
+    let value = Conditional {
+        mode: 3,
+        ..Default::default()
+    };
+    let mut buf = BytesMut::new();
+    value.serialize(&mut PacketWriter::new(&mut buf));
+    assert_eq!(buf.to_vec(), [0, 3]);
+
+    let mut data = &buf[..];
+    let mut reader = PacketReader::new(Box::new(&mut data));
+    Conditional::default().deserialize(&mut reader);
+    assert_eq!(reader.finish().unwrap_err().field, Some("target"));
+}
+
+#[qexed_packet_macros::substruct]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct Wrapper(#[varint] i32, String);
+
+#[qexed_packet_macros::substruct]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct Marker;
+
+#[test]
+fn tuple_and_unit_structs() {
+    let value = Wrapper(300, "a".into());
+    let bytes = encode(&value);
+    assert_eq!(bytes, [0xAC, 0x02, 1, b'a']);
+    assert_eq!(decode::<Wrapper>(&bytes), value);
+    assert!(encode(&Marker).is_empty());
+    assert_eq!(decode::<Marker>(&[]), Marker);
+}
+
+#[qexed_packet_macros::substruct]
+#[derive(Debug, Default, PartialEq, Clone)]
+enum Shape {
+    #[default]
+    Empty,
+    Circle(#[varint] i32),
+    #[id = 5]
+    Rect {
+        width: u8,
+        height: u8,
+    },
+    Named {
+        has_label: bool,
+        #[optional_if = "has_label"]
+        label: Option<String>,
+        #[skip]
+        cached: u32,
+    },
+}
+
+#[qexed_packet_macros::substruct(tag = u8)]
+#[derive(Debug, Default, PartialEq, Clone)]
+enum Small {
+    #[default]
+    #[id = 9]
+    Nine,
+}
+
+#[qexed_packet_macros::substruct(tag = identifier)]
+#[derive(Debug, Default, PartialEq, Clone)]
+enum Display {
+    #[default]
+    Empty,
+    CraftingShapeless(String),
+    #[id = "custom:thing"]
+    Thing,
+}
+
+#[test]
+fn enum_tags() {
+    assert_eq!(encode(&Shape::Empty), [0]);
+    assert_eq!(encode(&Shape::Circle(300)), [1, 0xAC, 0x02]);
+    assert_eq!(encode(&Shape::Rect { width: 2, height: 3 }), [5, 2, 3]);
+    let named = Shape::Named {
+        has_label: true,
+        label: Some("x".into()),
+        cached: 7,
+    };
+    assert_eq!(encode(&named), [6, 1, 1, b'x']);
+    for value in [Shape::Empty, Shape::Circle(300), Shape::Rect { width: 2, height: 3 }] {
+        assert_eq!(decode::<Shape>(&encode(&value)), value);
+    }
+    assert_eq!(
+        decode::<Shape>(&encode(&named)),
+        Shape::Named {
+            has_label: true,
+            label: Some("x".into()),
+            cached: 0,
+        }
+    );
+
+    assert_eq!(encode(&Small::Nine), [9]);
+    assert_eq!(decode::<Small>(&[9]), Small::Nine);
+
+    let display = Display::CraftingShapeless("a".into());
+    let mut expected = vec![28];
+    expected.extend(b"minecraft:crafting_shapeless");
+    expected.extend([1, b'a']);
+    assert_eq!(encode(&display), expected);
+    assert_eq!(decode::<Display>(&expected), display);
+    // 省略 minecraft 命名空间也能匹配
+    let mut short = vec![5];
+    short.extend(b"empty");
+    assert_eq!(decode::<Display>(&short), Display::Empty);
+    let mut custom = vec![12];
+    custom.extend(b"custom:thing");
+    assert_eq!(decode::<Display>(&custom), Display::Thing);
+}
+
+#[test]
+fn enum_unknown_tag() {
+    let mut data = &[9, 1][..];
+    let mut r = PacketReader::new(Box::new(&mut data));
+    r.deserialize::<Shape>();
+    let error = r.finish().unwrap_err();
+    assert_eq!(error.message, "Unknown Shape tag: 9");
+    assert_eq!(error.field, Some("tag"));
+}
+
+#[qexed_packet_macros::packet(id = 0x10, since = 764, until = 766)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct Versioned {
+    #[varint]
+    entity: i32,
+    #[since(765)]
+    added: u8,
+    #[until(764)]
+    removed: u8,
+}
+
+fn encode_version(value: &Versioned, protocol_version: i32) -> Vec<u8> {
+    let mut buf = BytesMut::new();
+    value.serialize(&mut PacketWriter::new(&mut buf).with_protocol_version(protocol_version));
+    buf.to_vec()
+}
+
+fn decode_version(bytes: &[u8], protocol_version: i32) -> Versioned {
+    let mut data = bytes;
+    let mut r = PacketReader::new(Box::new(&mut data)).with_protocol_version(protocol_version);
+    let mut value = Versioned::default();
+    value.deserialize(&mut r);
+    assert!(data.is_empty(), "{} bytes left unread", data.len());
+    value
+}
+
+#[test]
+fn version_ranges() {
+    let value = Versioned {
+        entity: 1,
+        added: 2,
+        removed: 3,
+    };
+    assert_eq!(encode_version(&value, 764), [1, 3]);
+    assert_eq!(encode_version(&value, 766), [1, 2]);
+    // 版本未知时按最新版本处理
+    assert_eq!(encode_version(&value, -1), [1, 2]);
+    assert_eq!(
+        decode_version(&[1, 3], 764),
+        Versioned {
+            added: 0,
+            ..value.clone()
+        }
+    );
+    assert_eq!(
+        decode_version(&[1, 2], 765),
+        Versioned {
+            removed: 0,
+            ..value
+        }
+    );
+
+    assert!(!value.supports_version(763));
+    assert!(value.supports_version(764));
+    assert!(value.supports_version(766));
+    assert!(!value.supports_version(767));
+    assert!(Conditional::default().supports_version(1));
+}
+
+#[qexed_packet_macros::substruct]
+#[derive(Debug, Default, PartialEq, Clone)]
+enum VersionedVariant {
+    #[default]
+    Empty,
+    Data {
+        #[since(765)]
+        extra: u8,
+        value: u8,
+    },
+}
+
+#[test]
+fn version_ranges_in_variants() {
+    let value = VersionedVariant::Data { extra: 1, value: 2 };
+    let mut buf = BytesMut::new();
+    value.serialize(&mut PacketWriter::new(&mut buf).with_protocol_version(764));
+    assert_eq!(buf.to_vec(), [1, 2]);
+
+    let mut data = &[1u8, 2][..];
+    let mut r = PacketReader::new(Box::new(&mut data)).with_protocol_version(764);
+    let decoded: VersionedVariant = r.deserialize();
+    assert_eq!(decoded, VersionedVariant::Data { extra: 0, value: 2 });
+}
+
+mod registry {
+    #[qexed_packet_macros::packet(id = 0x00)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct First {
+        pub value: u8,
+    }
+
+    #[qexed_packet_macros::packet(id = 0x05, until = 764)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct OldSecond {}
+
+    // 与 OldSecond 的ID相同,但版本范围不重叠
+    #[qexed_packet_macros::packet(id = 0x05, since = 765)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct NewSecond {
+        pub value: u8,
+    }
+
+    #[qexed_packet_macros::packet(id = 0xFFF)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct Unknown {}
+
+    impl Unknown {
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    qexed_packet_macros::id_to_packet!([First, OldSecond, NewSecond], Unknown, enum RegistryPacket);
+}
+
+#[test]
+fn registry_uses_declared_ids() {
+    assert_eq!(registry::ID_First, 0x00);
+    assert_eq!(registry::ID_OldSecond, 0x05);
+    assert_eq!(registry::ID_NewSecond, 0x05);
+
+    assert_eq!(registry::id_to_packet(0x00, 764).name(), "First");
+    assert_eq!(registry::id_to_packet(0x05, 764).name(), "OldSecond");
+    assert_eq!(registry::id_to_packet(0x05, 766).name(), "NewSecond");
+    assert_eq!(registry::id_to_packet(0x01, 766).name(), "Unknown");
+
+    assert_eq!(registry::packet_name(0x05, 700), Some("OldSecond"));
+    assert_eq!(registry::packet_name(0x05, -1), Some("NewSecond"));
+    assert_eq!(registry::packet_name(0x02, 766), None);
+}
+
+#[qexed_packet_macros::packet(id = 0x03, state = play, direction = clientbound)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct Described {
+    #[varint]
+    entity: i32,
+    #[prefixed(u8)]
+    names: Vec<String>,
+    #[since(765)]
+    #[optional_if = "entity > 0"]
+    extra: Option<u8>,
+    #[skip]
+    cached: u32,
+}
+
+#[test]
+fn packet_metadata() {
+    assert_eq!(Described::ID, 0x03);
+    assert_eq!(Described::NAME, "Described");
+    assert_eq!(Described::STATE, Some(ConnectionState::Play));
+    assert_eq!(Described::DIRECTION, Some(PacketDirection::Clientbound));
+    assert_eq!(Described::SINCE, None);
+    assert_eq!(Conditional::STATE, None);
+    assert_eq!(Versioned::UNTIL, Some(766));
+
+    let field = |name, rust_type, encoding| FieldDescriptor {
+        name,
+        rust_type,
+        encoding,
+        optional_if: None,
+        since: None,
+        until: None,
+    };
+    assert_eq!(
+        Described::FIELDS,
+        [
+            field("entity", "i32", FieldEncoding::VarInt),
+            field("names", "Vec<String>", FieldEncoding::Prefixed("u8")),
+            FieldDescriptor {
+                optional_if: Some("entity > 0"),
+                since: Some(765),
+                ..field("extra", "Option<u8>", FieldEncoding::Default)
+            },
+            field("cached", "u32", FieldEncoding::Skip),
+        ]
+    );
+    let packet: Box<dyn Packet> = Box::new(Described::default());
+    assert_eq!(packet.fields().len(), 4);
+    assert_eq!(packet.name(), Described::NAME);
+}
+
+#[test]
+fn owned_downcast() {
+    let packet = registry::id_to_packet(0x00, 766);
+    assert!(packet.is::<registry::First>());
+    assert!(packet.downcast_ref::<registry::NewSecond>().is_none());
+    let packet = packet.downcast::<registry::NewSecond>().unwrap_err();
+    let first: Box<registry::First> = packet.downcast().unwrap();
+    assert_eq!(*first, registry::First { value: 0 });
+}
+
+#[test]
+fn clone_and_eq_through_trait_object() {
+    let packet: Box<dyn Packet> = Box::new(registry::NewSecond { value: 7 });
+    let copy = packet.clone();
+    assert!(*copy == *packet);
+
+    let mut changed = copy.downcast::<registry::NewSecond>().unwrap();
+    changed.value = 8;
+    let changed: Box<dyn Packet> = changed;
+    assert!(*changed != *packet);
+
+    // 类型不同时即使字段相同也不相等
+    let first: Box<dyn Packet> = Box::new(registry::First { value: 7 });
+    assert!(!first.dyn_eq(packet.as_ref()));
+}
+
+#[test]
+fn packet_enum() {
+    use registry::RegistryPacket;
+
+    let packet: Box<dyn Packet> = Box::new(registry::NewSecond { value: 3 });
+    match RegistryPacket::from(packet) {
+        RegistryPacket::NewSecond(packet) => assert_eq!(packet.value, 3),
+        other => panic!("unexpected {:?}", other),
+    }
+    let other = RegistryPacket::from_packet(Box::new(Described::default()));
+    assert!(matches!(other, RegistryPacket::Other(_)));
+    assert_eq!(other.as_packet().name(), "Described");
+    assert_eq!(
+        RegistryPacket::First(registry::First { value: 1 }).into_packet().id(),
+        registry::ID_First
+    );
+}
+
+mod base {
+    pub mod pool {
+        pub use crate::registry::{ID_First, ID_NewSecond};
+    }
+    pub use crate::registry::First;
+}
+
+#[test]
+fn packet_match_falls_back_to_default() {
+    let handle = |packet: Box<dyn Packet>| -> &'static str {
+        let result;
+        qexed_packet_macros::packet_match!(packet, base, {
+            case! ID_First => First as first {
+                assert_eq!(first.value, 0);
+                result = "first";
+            },
+            // ID 对应 NewSecond,类型却写成 First
+            case! ID_NewSecond => First as _wrong {
+                result = "wrong";
+            },
+            default! {
+                result = "default";
+            }
+        });
+        result
+    };
+    assert_eq!(handle(Box::<registry::First>::default()), "first");
+    assert_eq!(handle(Box::<registry::NewSecond>::default()), "default");
+    assert_eq!(handle(Box::<Described>::default()), "default");
+}
+
+#[test]
+fn packet_match_without_id() {
+    let handle = |packet: Box<dyn Packet>| -> &'static str {
+        let result;
+        qexed_packet_macros::packet_match!(packet, registry, {
+            case! First as _first {
+                result = "first";
+            },
+            case! NewSecond as second {
+                assert_eq!(second.value, 0);
+                result = "second";
+            },
+            default! {
+                result = "default";
+            }
+        });
+        result
+    };
+    assert_eq!(handle(Box::<registry::First>::default()), "first");
+    assert_eq!(handle(Box::<registry::NewSecond>::default()), "second");
+    assert_eq!(handle(Box::<registry::OldSecond>::default()), "default");
+}
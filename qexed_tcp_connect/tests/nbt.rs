@@ -0,0 +1,153 @@
+use bytes::BytesMut;
+use qexed_tcp_connect::{
+    net_types::{
+        nbt::{NamedNbt, Nbt, NbtCompound, NbtList, NbtTag, TAG_BYTE, TAG_LIST},
+        subdata::Subdata,
+    },
+    packet::{
+        decode::{DecodeError, PacketReader},
+        encode::PacketWriter,
+    },
+};
+
+fn encode<T: Subdata>(value: &T) -> Vec<u8> {
+    let mut buf = BytesMut::new();
+    value.serialize(&mut PacketWriter::new(&mut buf));
+    buf.to_vec()
+}
+
+fn decode<T: Subdata>(bytes: &[u8]) -> Result<T, DecodeError> {
+    let mut data = bytes;
+    let mut r = PacketReader::new(Box::new(&mut data));
+    let value = r.deserialize();
+    r.finish().map(|_| value)
+}
+
+/// 网络 NBT 中的一个字符串根标签
+fn string_root(mutf8: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0x08, 0x00, mutf8.len() as u8];
+    bytes.extend(mutf8);
+    bytes
+}
+
+#[test]
+fn network_root_is_unnamed() {
+    let compound = NbtCompound(vec![("a".into(), NbtTag::Byte(1))]);
+    let nbt = Nbt(NbtTag::Compound(compound.clone()));
+    let bytes = [0x0a, 0x01, 0x00, 0x01, b'a', 0x01, 0x00];
+    assert_eq!(encode(&nbt), bytes);
+    assert_eq!(decode::<Nbt>(&bytes).unwrap(), nbt);
+    // 空的根标签只有类型
+    assert_eq!(decode::<Nbt>(&[0x00]).unwrap(), Nbt(NbtTag::End));
+
+    // 旧格式的根标签带名称
+    let named = NamedNbt {
+        name: "root".into(),
+        root: NbtTag::Compound(compound),
+    };
+    let bytes = [0x0a, 0x00, 0x04, b'r', b'o', b'o', b't', 0x01, 0x00, 0x01, b'a', 0x01, 0x00];
+    assert_eq!(encode(&named), bytes);
+    assert_eq!(decode::<NamedNbt>(&bytes).unwrap(), named);
+}
+
+#[test]
+fn modified_utf8() {
+    // U+0000 为两字节,增补字符为两个三字节的代理项
+    let value = "a\0é😀";
+    let mutf8 = [b'a', 0xC0, 0x80, 0xC3, 0xA9, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80];
+    let nbt = Nbt(NbtTag::String(value.into()));
+    assert_eq!(encode(&nbt), string_root(&mutf8));
+    assert_eq!(decode::<Nbt>(&string_root(&mutf8)).unwrap(), nbt);
+
+    let invalid = |mutf8: &[u8]| decode::<Nbt>(&string_root(mutf8)).unwrap_err().message;
+    // 截断的多字节序列
+    assert_eq!(invalid(&[0xC3]), "Invalid Modified UTF-8 string");
+    // 错误的后续字节
+    assert_eq!(invalid(&[0xE2, 0x41, 0x80]), "Invalid Modified UTF-8 string");
+    // 未配对的代理项
+    assert_eq!(invalid(&[0xED, 0xA0, 0xBD]), "Invalid Modified UTF-8 string");
+    // 四字节的 UTF-8 不是合法的 Modified UTF-8
+    assert_eq!(invalid(&[0xF0, 0x9F, 0x98, 0x80]), "Invalid Modified UTF-8 string");
+}
+
+#[test]
+fn long_string_truncated() {
+    // 65534 字节后的增补字符需要 6 字节,整个被截掉,不会留下半个代理对
+    let value = format!("{}😀{}", "a".repeat(65534), "b".repeat(10));
+    let nbt = NbtCompound(vec![
+        ("s".into(), NbtTag::String(value)),
+        ("after".into(), NbtTag::Byte(7)),
+    ]);
+    let bytes = encode(&Nbt(NbtTag::Compound(nbt)));
+    // 类型、名称 "s"、字符串类型之后是长度前缀
+    assert_eq!(&bytes[..7], [0x0a, 0x08, 0x00, 0x01, b's', 0xFF, 0xFE]);
+
+    let Nbt(NbtTag::Compound(decoded)) = decode::<Nbt>(&bytes).unwrap() else {
+        panic!("root is not a compound");
+    };
+    assert_eq!(decoded.0[0].1, NbtTag::String("a".repeat(65534)));
+    assert_eq!(decoded.0[1], ("after".into(), NbtTag::Byte(7)));
+}
+
+#[test]
+fn nesting_limit() {
+    // depth 层嵌套的列表,最内层为空的 Byte 列表
+    let nested = |depth: usize| {
+        let mut bytes = vec![TAG_LIST];
+        for _ in 0..depth {
+            bytes.extend([TAG_LIST, 0, 0, 0, 1]);
+        }
+        bytes.extend([TAG_BYTE, 0, 0, 0, 0]);
+        bytes
+    };
+    let nbt = decode::<Nbt>(&nested(512)).unwrap();
+    assert!(matches!(nbt.0, NbtTag::List(NbtList { element_type: TAG_LIST, .. })));
+    assert_eq!(decode::<Nbt>(&nested(513)).unwrap_err().message, "NBT nesting too deep");
+}
+
+#[test]
+fn invalid_data() {
+    let error = |bytes: &[u8]| decode::<Nbt>(bytes).unwrap_err().message;
+    assert_eq!(error(&[0x0d]), "Invalid NBT tag type: 13");
+    assert_eq!(error(&[0x07, 0xff, 0xff, 0xff, 0xff]), "Negative length: -1");
+    assert_eq!(error(&[0x0b, 0x00, 0x00, 0x00, 0x02, 0x00]), "Length 2 exceeds remaining data");
+    assert_eq!(error(&[0x08, 0x00, 0x05, b'a']), "Unexpected end of data: need 5 bytes");
+    // 复合标签缺少结尾
+    assert_eq!(error(&[0x0a, 0x01, 0x00, 0x01, b'a', 0x01]), "Unexpected end of data: need 1 bytes");
+}
+
+#[test]
+fn snbt() {
+    let mut list = NbtList::new(TAG_BYTE);
+    list.push(NbtTag::Byte(1));
+    list.push(NbtTag::Byte(-2));
+    let tag = NbtTag::Compound(NbtCompound(vec![
+        ("name".into(), NbtTag::String("Ste\"ve".into())),
+        ("has space".into(), NbtTag::Short(3)),
+        ("list".into(), NbtTag::List(list)),
+        ("ints".into(), NbtTag::IntArray(vec![1, 2])),
+        ("longs".into(), NbtTag::LongArray(vec![3])),
+        ("float".into(), NbtTag::Float(1.5)),
+        ("double".into(), NbtTag::Double(2.0)),
+        ("empty".into(), NbtTag::Compound(NbtCompound::new())),
+    ]));
+    assert_eq!(
+        tag.to_snbt(),
+        r#"{name:"Ste\"ve","has space":3s,list:[1b,-2b],ints:[I;1,2],longs:[L;3L],float:1.5f,double:2.0d,empty:{}}"#
+    );
+    let inner = NbtTag::Compound(NbtCompound(vec![("a".into(), NbtTag::Long(1))]));
+    assert_eq!(inner.to_snbt_pretty(), "{\n  a: 1L\n}");
+    let outer = NbtTag::Compound(NbtCompound(vec![("inner".into(), inner)]));
+    assert_eq!(outer.to_snbt_pretty(), "{\n  inner: {\n    a: 1L\n  }\n}");
+    assert_eq!(format!("{:?}", Nbt(NbtTag::Int(7))), "Nbt(7)");
+}
+
+#[test]
+fn nbt_field_type_mismatch() {
+    // #[nbt] 字段要求复合标签时,其他类型是解码错误
+    let bytes = [0x03, 0x00, 0x00, 0x00, 0x01];
+    let mut data = &bytes[..];
+    let mut r = PacketReader::new(Box::new(&mut data));
+    assert_eq!(r.nbt_as::<NbtCompound>(), NbtCompound::new());
+    assert_eq!(r.finish().unwrap_err().message, "Expected NBT compound, got tag type 3");
+}
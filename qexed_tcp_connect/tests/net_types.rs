@@ -0,0 +1,703 @@
+use std::fmt::Debug;
+
+use bytes::{Bytes, BytesMut};
+use qexed_tcp_connect::{
+    net_types::{
+        angle::Angle,
+        array::{Array, ArrayU8, ArrayU16, ArrayU32, ArrayVarInt, FixedArray, RestArray},
+        bitset::{Bitset, FixedBitSet},
+        chunk::{ChunkSection, ContainerKind, Palette, PalettedContainer},
+        entity_metadata::{EntityMetadata, MetadataEntry, MetadataValue},
+        fixed_point::{FixedI8, FixedI32},
+        id_or::IdOr,
+        id_set::IdSet,
+        identifier::Identifier,
+        lp_vec3::LpVec3,
+        particle::{Particle, ParticleData, PositionSource},
+        position::Position,
+        rest_buffer::RestBuffer,
+        slot::{
+            AttributeModifier, BlockPredicate, Component, ComponentRegistry, ComponentValue, EffectDetails, Food, FoodEffect,
+            ModifierId, PotionEffect, PropertyMatcher, PropertyValue, Slot, Tool, ToolRule,
+        },
+        subdata::{PrefixedOptional, Subdata},
+        teleport_flags::TeleportFlags,
+        var_int::VarInt,
+        var_long::VarLong,
+    },
+    packet::{
+        decode::{DecodeError, PacketReader},
+        encode::PacketWriter,
+    },
+};
+
+fn encode<T: Subdata>(value: &T) -> Vec<u8> {
+    let mut buf = BytesMut::new();
+    let mut w = PacketWriter::new(&mut buf);
+    value.serialize(&mut w);
+    buf.to_vec()
+}
+
+fn decode<T: Subdata>(bytes: &[u8]) -> T {
+    let mut data = bytes;
+    let mut r = PacketReader::new(Box::new(&mut data));
+    let value = r.deserialize();
+    r.finish().unwrap();
+    assert!(data.is_empty(), "{} bytes left unread", data.len());
+    value
+}
+
+fn decode_error<T: Subdata>(bytes: &[u8]) -> DecodeError {
+    let mut data = bytes;
+    let mut r = PacketReader::new(Box::new(&mut data));
+    r.deserialize::<T>();
+    r.finish().unwrap_err()
+}
+
+fn round_trip<T: Subdata + PartialEq + Debug>(value: T, bytes: &[u8]) {
+    assert_eq!(encode(&value), bytes, "encoding {:?}", value);
+    assert_eq!(decode::<T>(bytes), value);
+}
+
+#[test]
+fn var_long() {
+    round_trip(VarLong(0), &[0x00]);
+    round_trip(VarLong(1), &[0x01]);
+    round_trip(VarLong(127), &[0x7f]);
+    round_trip(VarLong(128), &[0x80, 0x01]);
+    round_trip(VarLong(255), &[0xff, 0x01]);
+    round_trip(VarLong(i32::MAX as i64), &[0xff, 0xff, 0xff, 0xff, 0x07]);
+    round_trip(VarLong(i64::MAX), &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]);
+    round_trip(VarLong(-1), &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]);
+    round_trip(VarLong(i64::MIN), &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01]);
+}
+
+#[test]
+fn position() {
+    let position = Position::new(18357644, 831, -20882616);
+    assert_eq!(position.to_packed(), 0x4607632C15B4833F);
+    round_trip(position, &0x4607632C15B4833Fu64.to_be_bytes());
+    round_trip(Position::new(-1, -1, -1), &[0xff; 8]);
+    round_trip(Position::new(0, -64, 0), &[0, 0, 0, 0, 0, 0, 0x0f, 0xc0]);
+}
+
+#[test]
+fn angle() {
+    round_trip(Angle(64), &[0x40]);
+    assert_eq!(Angle::from_degrees(90.0), Angle(64));
+    assert_eq!(Angle::from_degrees(-90.0), Angle(192));
+    assert_eq!(Angle(128).degrees(), 180.0);
+}
+
+#[test]
+fn identifier() {
+    let stone = Identifier::new("minecraft", "stone").unwrap();
+    round_trip(stone.clone(), b"\x0fminecraft:stone");
+    assert_eq!(Identifier::parse("stone").unwrap(), stone);
+    assert_eq!("minecraft:stone".parse::<Identifier>().unwrap(), stone);
+    assert_eq!(stone.to_string(), "minecraft:stone");
+    assert!(Identifier::parse("my_pack:blocks/ore.deep-1").is_ok());
+    assert!(Identifier::parse("Minecraft:stone").is_err());
+    assert!(Identifier::parse("minecraft:stone block").is_err());
+    assert!(Identifier::parse("a/b:stone").is_err());
+
+    // 读取时不校验,非法的标识符原样保留
+    let invalid: Identifier = decode(b"\x0bMy Pack:Ore");
+    assert_eq!(invalid.to_string(), "My Pack:Ore");
+    assert!(invalid.validate().is_err());
+    assert_eq!(encode(&invalid), b"\x0bMy Pack:Ore");
+    assert_eq!(Identifier::parse_unchecked("a:b:c").path, "b:c");
+}
+
+#[test]
+fn fixed_point() {
+    round_trip(FixedI32::from_f64(1.5), &[0x00, 0x00, 0x00, 0x30]);
+    round_trip(FixedI32::from_f64(-1.0), &[0xff, 0xff, 0xff, 0xe0]);
+    round_trip(FixedI8::from_f64(0.5), &[0x10]);
+    assert_eq!(FixedI32(48).to_f64(), 1.5);
+}
+
+#[test]
+fn lp_vec3() {
+    round_trip(LpVec3::default(), &[0x00]);
+    assert_eq!(encode(&LpVec3::new(1e-6, 0.0, -1e-6)), [0x00]);
+    assert_eq!(encode(&LpVec3::new(1.0, 0.0, 0.0)), [0xf1, 0xff, 0x7f, 0xfe, 0xff, 0xff]);
+    assert_eq!(encode(&LpVec3::new(10.0, 0.0, 0.0)), [0xf6, 0xff, 0x7f, 0xfe, 0xff, 0xff, 0x02]);
+    let decoded: LpVec3 = decode(&[0xf6, 0xff, 0x7f, 0xfe, 0xff, 0xff, 0x02]);
+    assert_eq!(decoded.x, 10.0);
+    assert!(decoded.y.abs() < 1e-3 && decoded.z.abs() < 1e-3);
+    let velocity = LpVec3::new(0.25, -0.5, 0.125);
+    let decoded: LpVec3 = decode(&encode(&velocity));
+    assert!((decoded.x - velocity.x).abs() < 1e-4);
+    assert!((decoded.y - velocity.y).abs() < 1e-4);
+    assert!((decoded.z - velocity.z).abs() < 1e-4);
+}
+
+#[test]
+fn id_or() {
+    round_trip(IdOr::<VarInt>::Id(5), &[0x06]);
+    round_trip(IdOr::<VarInt>::Id(0), &[0x01]);
+    round_trip(IdOr::Value(VarInt(300)), &[0x00, 0xac, 0x02]);
+}
+
+#[test]
+fn id_set() {
+    round_trip(IdSet::Ids(vec![1, 2]), &[0x03, 0x01, 0x02]);
+    round_trip(IdSet::Ids(vec![]), &[0x01]);
+    round_trip(
+        IdSet::Tag(Identifier::parse("minecraft:logs").unwrap()),
+        b"\x00\x0eminecraft:logs",
+    );
+    assert_eq!(
+        decode_error::<IdSet>(&[0xff, 0xff, 0xff, 0xff, 0x07]).message,
+        "Length 2147483646 exceeds remaining data"
+    );
+}
+
+#[test]
+fn prefixed_optional() {
+    round_trip::<PrefixedOptional<VarInt>>(Some(VarInt(7)), &[0x01, 0x07]);
+    round_trip::<PrefixedOptional<VarInt>>(None, &[0x00]);
+}
+
+#[test]
+fn teleport_flags() {
+    let mut flags = TeleportFlags::default();
+    flags.set(TeleportFlags::RELATIVE_X, true);
+    flags.set(TeleportFlags::RELATIVE_Y, true);
+    flags.set(TeleportFlags::RELATIVE_Z, true);
+    round_trip(flags, &[0x00, 0x00, 0x00, 0x07]);
+    assert!(flags.has(TeleportFlags::RELATIVE_Y));
+    assert!(!flags.has(TeleportFlags::ROTATE_VELOCITY));
+    flags.set(TeleportFlags::RELATIVE_Y, false);
+    assert_eq!(flags, TeleportFlags(0x05));
+}
+
+#[test]
+fn slot() {
+    round_trip(Slot::default(), &[0x00]);
+    round_trip(Slot::new(1, 64), &[0x40, 0x01, 0x00, 0x00]);
+    let mut sword = Slot::new(800, 1);
+    sword.components.push(Component {
+        id: 3,
+        value: ComponentValue::VarInt(12),
+    });
+    sword.components.push(Component {
+        id: 9,
+        value: ComponentValue::Enchantments {
+            levels: vec![(13, 5)],
+            show_in_tooltip: true,
+        },
+    });
+    sword.removed.push(4);
+    round_trip(
+        sword,
+        &[0x01, 0xa0, 0x06, 0x02, 0x01, 0x03, 0x0c, 0x09, 0x01, 0x0d, 0x05, 0x01, 0x04],
+    );
+    assert_eq!(ComponentRegistry::default().name(9), Some("minecraft:enchantments"));
+}
+
+#[test]
+fn slot_unknown_component() {
+    // damage = 1, 然后是未注册的组件 200
+    let bytes = [0x01, 0x05, 0x02, 0x01, 0x03, 0x01, 0xc8, 0x01, 0xaa, 0xbb, 0x07];
+    let slot: Slot = decode(&bytes);
+    assert_eq!(slot.component(3), Some(&ComponentValue::VarInt(1)));
+    let unknown = slot.unknown.as_ref().unwrap();
+    assert_eq!((unknown.added, unknown.removed, unknown.id), (2, 1, 200));
+    assert_eq!(unknown.data, [0xaa, 0xbb, 0x07]);
+    assert_eq!(encode(&slot), bytes);
+
+    // 无法知道物品在哪里结束,之后的数据 (这里是第二个物品) 都不再解析
+    let mut list = vec![0x02];
+    list.extend(bytes);
+    list.extend([0x01, 0x06, 0x00, 0x00]);
+    let mut data = &list[..];
+    let mut r = PacketReader::new(Box::new(&mut data));
+    let slots: Vec<Slot> = r.vec();
+    assert_eq!(slots.len(), 1);
+    assert_eq!(slots[0].unknown.as_ref().unwrap().data, [0xaa, 0xbb, 0x07, 0x01, 0x06, 0x00, 0x00]);
+    assert_eq!(r.opaque(), Some("Unknown item component 200"));
+    assert!(r.finish().is_ok());
+}
+
+/// 按指定协议版本编码再解码
+fn round_trip_version<T: Subdata + PartialEq + Debug>(value: &T, protocol_version: i32) -> Vec<u8> {
+    let mut buf = BytesMut::new();
+    value.serialize(&mut PacketWriter::new(&mut buf).with_protocol_version(protocol_version));
+    let mut data = &buf[..];
+    let mut r = PacketReader::new(Box::new(&mut data)).with_protocol_version(protocol_version);
+    assert_eq!(&r.deserialize::<T>(), value);
+    r.finish().unwrap();
+    assert!(data.is_empty());
+    buf.to_vec()
+}
+
+fn slot_with(id: i32, value: ComponentValue) -> Slot {
+    let mut slot = Slot::new(5, 1);
+    slot.components.push(Component { id, value });
+    slot
+}
+
+#[test]
+fn slot_typed_components() {
+    let can_place_on = slot_with(
+        10,
+        ComponentValue::BlockPredicates {
+            predicates: vec![BlockPredicate {
+                blocks: Some(IdSet::Ids(vec![1])),
+                properties: Some(vec![PropertyMatcher {
+                    name: "facing".into(),
+                    value: PropertyValue::Exact("north".into()),
+                }]),
+                nbt: None,
+            }],
+            show_in_tooltip: true,
+        },
+    );
+    round_trip(
+        can_place_on,
+        &[
+            0x01, 0x05, 0x01, 0x00, 0x0a, 0x01, 0x01, 0x02, 0x01, 0x01, 0x01, 0x06, b'f', b'a', b'c', b'i', b'n', b'g',
+            0x01, 0x05, b'n', b'o', b'r', b't', b'h', 0x00, 0x01,
+        ],
+    );
+    let can_break = slot_with(
+        11,
+        ComponentValue::BlockPredicates {
+            predicates: vec![BlockPredicate {
+                properties: Some(vec![PropertyMatcher {
+                    name: "age".into(),
+                    value: PropertyValue::Range { min: Some("1".into()), max: None },
+                }]),
+                ..Default::default()
+            }],
+            show_in_tooltip: false,
+        },
+    );
+    round_trip_version(&can_break, 767);
+
+    // 1.21 起修饰符使用命名空间ID,之前为 UUID 加名字
+    let modifier = AttributeModifier {
+        attribute: 2,
+        id: ModifierId::Identifier(Identifier::minecraft("speed").unwrap()),
+        amount: 1.0,
+        operation: 0,
+        slot: 1,
+    };
+    let modifiers = |modifier: AttributeModifier| {
+        slot_with(
+            12,
+            ComponentValue::AttributeModifiers {
+                modifiers: vec![modifier],
+                show_in_tooltip: true,
+            },
+        )
+    };
+    let new_format = round_trip_version(&modifiers(modifier.clone()), 767);
+    let old = AttributeModifier {
+        id: ModifierId::Uuid {
+            uuid: uuid::Uuid::from_u128(1),
+            name: "speed".into(),
+        },
+        ..modifier
+    };
+    let old_format = round_trip_version(&modifiers(old), 766);
+    // "minecraft:speed" 占 16 字节,UUID 加 "speed" 占 22 字节
+    assert_eq!(old_format.len() - new_format.len(), 6);
+
+    // 1.21 起食物多了食用后转换成的物品
+    let food = Food {
+        nutrition: 4,
+        saturation: 2.4,
+        can_always_eat: false,
+        eat_seconds: 1.6,
+        using_converts_to: Some(Box::new(Slot::new(7, 1))),
+        effects: vec![FoodEffect {
+            effect: PotionEffect {
+                id: 3,
+                details: vec![
+                    EffectDetails {
+                        amplifier: 1,
+                        duration: 100,
+                        show_icon: true,
+                        ..Default::default()
+                    },
+                    EffectDetails {
+                        duration: 40,
+                        ..Default::default()
+                    },
+                ],
+            },
+            probability: 0.5,
+        }],
+    };
+    round_trip_version(&slot_with(20, ComponentValue::Food(food.clone())), 767);
+    let old_food = Food {
+        using_converts_to: None,
+        ..food
+    };
+    let new_format = round_trip_version(&slot_with(20, ComponentValue::Food(old_food.clone())), 767);
+    let old_format = round_trip_version(&slot_with(20, ComponentValue::Food(old_food)), 766);
+    assert_eq!(new_format.len(), old_format.len() + 1);
+
+    let tool = Tool {
+        rules: vec![ToolRule {
+            blocks: IdSet::Tag(Identifier::minecraft("mineable/pickaxe").unwrap()),
+            speed: Some(8.0),
+            correct_for_drops: Some(true),
+        }],
+        default_mining_speed: 1.0,
+        damage_per_block: 1,
+    };
+    round_trip_version(&slot_with(22, ComponentValue::Tool(tool)), 767);
+
+    let registry = ComponentRegistry::default();
+    for (id, name) in [(10, "minecraft:can_place_on"), (12, "minecraft:attribute_modifiers"), (20, "minecraft:food"), (22, "minecraft:tool")] {
+        assert_eq!(registry.name(id), Some(name));
+    }
+}
+
+#[test]
+fn slot_nesting_limit() {
+    // 食物转换成的物品又是食物,嵌套过深时报错而不是栈溢出
+    let mut bytes = vec![];
+    for _ in 0..200 {
+        bytes.extend([0x01, 0x05, 0x01, 0x00, 0x14, 0x01, 0, 0, 0, 0, 0x00, 0, 0, 0, 0, 0x01]);
+    }
+    assert_eq!(decode_error::<Slot>(&bytes).message, "Nesting too deep");
+}
+
+#[test]
+fn slot_invalid_lengths() {
+    // 组件个数、移除个数和附魔个数都不能超过剩余数据
+    let added = [0x01, 0x05, 0xff, 0xff, 0xff, 0xff, 0x07, 0x00];
+    assert_eq!(decode_error::<Slot>(&added).message, "Length 2147483647 exceeds remaining data");
+    let removed = [0x01, 0x05, 0x00, 0xff, 0xff, 0xff, 0xff, 0x07];
+    assert_eq!(decode_error::<Slot>(&removed).message, "Length 2147483647 exceeds remaining data");
+    let enchantments = [0x01, 0x05, 0x01, 0x00, 0x09, 0xff, 0xff, 0xff, 0xff, 0x07, 0x01];
+    assert_eq!(decode_error::<Slot>(&enchantments).message, "Length 2147483647 exceeds remaining data");
+}
+
+#[test]
+fn chunk_section() {
+    // 全是石头 (1),生物群系全是 0
+    let stone = [0x10, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00];
+    let mut section: ChunkSection = decode(&stone);
+    assert_eq!(section.block_count, 4096);
+    assert_eq!(section.block(3, 7, 15), Some(1));
+    round_trip(section.clone(), &stone);
+
+    section.set_block(3, 7, 15, 0).unwrap();
+    section.set_block(0, 0, 0, 9).unwrap();
+    assert_eq!(section.block_count, 4095);
+    assert_eq!(section.blocks.palette, Palette::Indirect(vec![1, 0, 9]));
+    assert_eq!(section.blocks.bits_per_entry, 4);
+    assert_eq!(section.blocks.data.len(), 256);
+    assert_eq!(section.block(3, 7, 15), Some(0));
+    assert_eq!(section.block(0, 0, 0), Some(9));
+    assert_eq!(section.block(1, 0, 0), Some(1));
+
+    let bytes = ChunkSection::write_all(&[section.clone(), section.clone()]);
+    assert_eq!(ChunkSection::read_all(&bytes).unwrap(), vec![section.clone(), section]);
+    assert!(ChunkSection::read_all(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn chunk_section_invalid() {
+    // 4 位间接调色板只有一个条目,但数据中的下标为 1
+    let mut bytes = vec![0x00, 0x10, 0x04, 0x01, 0x07, 0x80, 0x02];
+    bytes.extend([0, 0, 0, 0, 0, 0, 0, 0x01]);
+    bytes.extend([0; 255 * 8]);
+    bytes.extend([0x00, 0x00]);
+    assert_eq!(
+        decode_error::<ChunkSection>(&bytes).message,
+        "Palette index 1 at entry 0 out of range for 1 palette entries"
+    );
+
+    // 数据数组长度不对
+    let short = [0x00, 0x10, 0x04, 0x01, 0x07, 0x01, 0, 0, 0, 0, 0, 0, 0, 0, 0x00, 0x00, 0x00];
+    assert_eq!(
+        decode_error::<ChunkSection>(&short).message,
+        "Invalid paletted container: 1 longs for 4 bits per entry, expected 256"
+    );
+
+    // 长度前缀不可信,不按它预分配
+    let huge = [0x00, 0x10, 0x04, 0xff, 0xff, 0xff, 0xff, 0x07];
+    assert_eq!(decode_error::<ChunkSection>(&huge).message, "Length 2147483647 exceeds remaining data");
+    let huge = [0x00, 0x10, 0x0f, 0xff, 0xff, 0xff, 0xff, 0x07];
+    assert_eq!(decode_error::<ChunkSection>(&huge).message, "Length 2147483647 exceeds remaining data");
+
+    // 每条目超过 64 位时一个 long 放不下
+    let wide = [0x00, 0x00, 0x41, 0x00, 0x00, 0x00];
+    assert_eq!(
+        decode_error::<ChunkSection>(&wide).message,
+        "Invalid paletted container: 65 bits per entry"
+    );
+    let mut container = PalettedContainer::single(ContainerKind::Biomes, 0);
+    container.bits_per_entry = 64;
+    container.palette = Palette::Direct;
+    container.data = vec![u64::MAX; 64];
+    assert!(container.validate().is_ok());
+    assert_eq!(container.get(3, 3, 3), Some(-1));
+    assert_eq!(container.set(3, 3, 3, 7), Some(()));
+    assert_eq!(container.data[63], 7);
+    container.bits_per_entry = 65;
+    assert_eq!(container.get(0, 0, 0), None);
+
+    // 手动构造的不合法容器读取时返回 None
+    let mut container = PalettedContainer::single(ContainerKind::Biomes, 0);
+    container.bits_per_entry = 1;
+    container.palette = Palette::Indirect(vec![5]);
+    container.data = vec![u64::MAX];
+    assert_eq!(container.get(0, 0, 0), None);
+    assert_eq!(container.values(), None);
+    assert!(container.validate().is_err());
+    container.data.clear();
+    assert_eq!(container.set(0, 0, 0, 5), None);
+}
+
+#[test]
+fn paletted_container_resize() {
+    let values: Vec<i32> = (0..4096).map(|i| i % 300).collect();
+    let mut container = PalettedContainer::from_values(ContainerKind::Blocks, &values);
+    assert_eq!(container.palette, Palette::Direct);
+    assert_eq!(container.bits_per_entry, 15);
+    assert_eq!(container.data.len(), 4096usize.div_ceil(4));
+    assert_eq!(container.values().unwrap(), values);
+    container.set(15, 15, 15, 20000).unwrap();
+    assert_eq!(container.get(15, 15, 15), Some(20000));
+
+    let mut biomes = PalettedContainer::single(ContainerKind::Biomes, 2);
+    for i in 0..4 {
+        biomes.set(i, 0, 0, i as i32);
+    }
+    assert_eq!(biomes.bits_per_entry, 2);
+    assert_eq!(biomes.values().unwrap()[..5], [0, 1, 2, 3, 2]);
+    for i in 0..4 {
+        biomes.set(i, 1, 0, 10 + i as i32);
+    }
+    assert_eq!(biomes.palette, Palette::Indirect(vec![0, 1, 2, 3, 10, 11, 12, 13]));
+    biomes.set(0, 2, 0, 20);
+    assert_eq!(biomes.palette, Palette::Direct);
+    assert_eq!(biomes.get(0, 2, 0), Some(20));
+    assert_eq!(biomes.get(3, 1, 0), Some(13));
+}
+
+#[test]
+fn paletted_container_negative_ids() {
+    let values: Vec<i32> = (0..64).map(|i| i % 20).collect();
+    let mut container = PalettedContainer::from_values(ContainerKind::Biomes, &values);
+    assert_eq!(container.palette, Palette::Direct);
+    let data = container.data.clone();
+    // 负数的全局ID不写入,也不会覆盖相邻的条目
+    assert_eq!(container.set(1, 0, 0, -1), None);
+    assert_eq!(container.data, data);
+    let mut single = PalettedContainer::single(ContainerKind::Biomes, 0);
+    assert_eq!(single.set(0, 0, 0, -1), None);
+    assert_eq!(single.palette, Palette::Single(0));
+
+    // 网络上读到的调色板可能含有负数,需要重新编码时返回 None
+    let mut container = PalettedContainer::single(ContainerKind::Biomes, 0);
+    container.bits_per_entry = 1;
+    container.palette = Palette::Indirect(vec![-5, 1]);
+    container.data = vec![0];
+    assert_eq!(container.set(0, 0, 0, 1), Some(()));
+    assert_eq!(container.set(1, 0, 0, 2), None);
+    assert_eq!(container.palette, Palette::Indirect(vec![-5, 1]));
+}
+
+#[test]
+#[should_panic(expected = "Negative global ID -3 in paletted container")]
+fn paletted_container_from_negative_values() {
+    let mut values = vec![0; 64];
+    values[5] = -3;
+    PalettedContainer::from_values(ContainerKind::Biomes, &values);
+}
+
+#[test]
+fn entity_metadata() {
+    let mut metadata = EntityMetadata::default();
+    metadata.set(0, MetadataValue::Byte(0x20));
+    metadata.set(2, MetadataValue::OptionalTextComponent(None));
+    metadata.set(6, MetadataValue::Pose(5));
+    metadata.set(10, MetadataValue::OptionalBlockState(None));
+    metadata.set(
+        11,
+        MetadataValue::Particle(Particle {
+            id: 13,
+            data: ParticleData::Dust {
+                color: [1.0, 0.0, 0.0],
+                scale: 1.0,
+            },
+        }),
+    );
+    metadata.set(
+        18,
+        MetadataValue::VillagerData {
+            kind: 2,
+            profession: 7,
+            level: 1,
+        },
+    );
+    metadata.set(19, MetadataValue::OptionalVarInt(Some(0)));
+    round_trip(
+        metadata.clone(),
+        &[
+            0x00, 0x00, 0x20, // Byte
+            0x02, 0x06, 0x00, // Optional Text Component
+            0x06, 0x15, 0x05, // Pose
+            0x0a, 0x0f, 0x00, // Optional Block State
+            0x0b, 0x11, 0x0d, 0x3f, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x3f, 0x80, 0x00, 0x00, // Particle
+            0x12, 0x13, 0x02, 0x07, 0x01, // Villager Data
+            0x13, 0x14, 0x01, // Optional VarInt
+            0xff,
+        ],
+    );
+    assert_eq!(metadata.get(6), Some(&MetadataValue::Pose(5)));
+    assert_eq!(metadata.get(7), None);
+
+    // 值 + 1 按 i32 回绕,不会溢出
+    let extreme = EntityMetadata(vec![MetadataEntry {
+        index: 1,
+        value: MetadataValue::OptionalVarInt(Some(i32::MAX)),
+    }]);
+    round_trip(extreme, &[0x01, 0x14, 0x80, 0x80, 0x80, 0x80, 0x08, 0xff]);
+}
+
+#[test]
+fn entity_metadata_unknown_type() {
+    // Pose, 然后是未知类型 99: 之后的数据 (含结束标记) 原样保留
+    let bytes = [0x06, 0x15, 0x05, 0x03, 0x63, 0x00, 0x01, 0xff];
+    let mut data = &bytes[..];
+    let mut r = PacketReader::new(Box::new(&mut data));
+    let metadata: EntityMetadata = r.deserialize();
+    assert_eq!(r.opaque(), Some("Unknown entity metadata type 99 at index 3"));
+    assert!(r.finish().is_ok());
+    assert_eq!(metadata.get(6), Some(&MetadataValue::Pose(5)));
+    assert_eq!(
+        metadata.get(3),
+        Some(&MetadataValue::Unknown {
+            type_id: 99,
+            data: vec![0x00, 0x01, 0xff]
+        })
+    );
+    assert_eq!(encode(&metadata), bytes);
+
+    // 截断时是解码错误,不会无限读取
+    assert_eq!(decode_error::<EntityMetadata>(&[0x06, 0x15]).message, "Unexpected end of data: need 1 bytes");
+}
+
+#[test]
+fn particle_unknown_position_source() {
+    // vibration, 位置来源类型 7
+    let bytes = [0x2d, 0x07, 0xaa, 0xbb];
+    let mut data = &bytes[..];
+    let mut r = PacketReader::new(Box::new(&mut data));
+    let particle: Particle = r.deserialize();
+    assert_eq!(r.opaque(), Some("Unknown position source type 7"));
+    assert!(r.finish().is_ok());
+    assert_eq!(
+        particle.data,
+        ParticleData::Vibration {
+            source: PositionSource::Unknown {
+                kind: 7,
+                data: vec![0xaa, 0xbb]
+            },
+            ticks: 0
+        }
+    );
+    assert_eq!(encode(&particle), bytes);
+}
+
+#[test]
+fn arrays() {
+    round_trip(ArrayVarInt::from_vec(vec![VarInt(1), VarInt(300)]), &[0x02, 0x01, 0xac, 0x02]);
+    round_trip(ArrayU8::from_vec(vec![1u16, 2]), &[0x02, 0x00, 0x01, 0x00, 0x02]);
+    round_trip(ArrayU16::<u8>::from_vec(vec![]), &[0x00, 0x00]);
+    round_trip(FixedArray([1u8, 2, 3]), &[0x01, 0x02, 0x03]);
+    round_trip(RestArray(vec![VarInt(1), VarInt(128)]), &[0x01, 0x80, 0x01]);
+
+    let mut array = ArrayVarInt::from_vec(vec![VarInt(1)]);
+    array.data.push(VarInt(2));
+    assert_eq!(encode(&array), [0x02, 0x01, 0x02]);
+}
+
+#[test]
+fn rest_array_element_without_data() {
+    // 不读取数据的元素会让读到结尾的循环停不下来
+    let error = decode_error::<RestArray<FixedArray<u8, 0>>>(&[0x01, 0x02, 0x03]);
+    assert_eq!(error.message, "Element 0 of rest array read no data");
+}
+
+#[test]
+fn array_limit() {
+    let error = decode_error::<Array<VarInt, u8, 2>>(&[0x03, 0x01, 0x02, 0x03]);
+    assert_eq!(error.message, "Array length 3 exceeds limit 2");
+}
+
+#[test]
+fn array_invalid_length() {
+    let error = decode_error::<ArrayVarInt<u8>>(&[0xff, 0xff, 0xff, 0xff, 0x0f]);
+    assert_eq!(error.message, "Negative length: -1");
+    // 声明的长度超过剩余数据,不会按声明长度预分配
+    let error = decode_error::<ArrayU32<u64>>(&[0xff, 0xff, 0xff, 0xff, 0x01]);
+    assert_eq!((error.message.as_str(), error.remaining), ("Length 4294967295 exceeds remaining data", 1));
+}
+
+#[test]
+fn reader_errors() {
+    assert_eq!(decode_error::<i32>(&[0x01, 0x02]).message, "Unexpected end of data: need 4 bytes");
+    assert_eq!(decode_error::<VarInt>(&[0xff; 6]).message, "VarInt too big");
+    assert_eq!(decode_error::<VarLong>(&[0x80]).message, "Unexpected end of data: need 1 bytes");
+    assert_eq!(decode_error::<String>(&[0x05, b'a']).message, "Length 5 exceeds remaining data");
+
+    // 只保留第一个错误,之后的读取不消耗数据
+    let mut data = &[0x01, 0x02, 0x03][..];
+    let mut r = PacketReader::new(Box::new(&mut data));
+    r.field("value");
+    assert_eq!(r.i32(), 0);
+    assert_eq!(r.u8(), 0);
+    assert!(r.failed());
+    r.fail("ignored");
+    let error = r.finish().unwrap_err();
+    assert_eq!(error.to_string(), "Unexpected end of data: need 4 bytes in field `value` (3 bytes left)");
+    assert!(r.finish().is_ok());
+    drop(r);
+    assert_eq!(data, [0x01, 0x02, 0x03]);
+
+    let mut data = &b"\x03{x}"[..];
+    let mut r = PacketReader::new(Box::new(&mut data));
+    assert_eq!(r.json_as::<Vec<i32>>(), Vec::<i32>::new());
+    assert!(r.finish().unwrap_err().message.starts_with("Invalid JSON"));
+}
+
+#[test]
+fn bitsets() {
+    let bitset: Bitset = [0, 3, 64].into_iter().collect();
+    round_trip(bitset.clone(), &[0x02, 0, 0, 0, 0, 0, 0, 0, 0x09, 0, 0, 0, 0, 0, 0, 0, 0x01]);
+    assert!(bitset.get(3) && !bitset.get(4) && !bitset.get(1000));
+    assert_eq!(bitset.len_bits(), 128);
+    assert_eq!(bitset.iter_ones().collect::<Vec<_>>(), [0, 3, 64]);
+    assert_eq!(format!("{:?}", bitset), "{0, 3, 64}");
+
+    let mut acknowledged: FixedBitSet<20> = [1, 19].into_iter().collect();
+    round_trip(acknowledged.clone(), &[0x02, 0x00, 0x08]);
+    acknowledged.clear(1);
+    assert_eq!(acknowledged.iter_ones().collect::<Vec<_>>(), [19]);
+    assert_eq!(acknowledged.len_bits(), 20);
+}
+
+#[test]
+fn rest_buffer_zero_copy() {
+    let frame = Bytes::from_static(b"\x05hello\x01\x02\x03");
+    let mut buf = frame.clone();
+    let mut r = PacketReader::new(Box::new(&mut buf));
+    assert_eq!(r.string(), "hello");
+    let rest: RestBuffer = r.deserialize();
+    assert_eq!(&rest.0[..], [1, 2, 3]);
+    // 剩余数据引用原始帧
+    assert_eq!(rest.0.as_ptr(), frame[6..].as_ptr());
+    assert_eq!(encode(&rest), [1, 2, 3]);
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "serde")]
+
+use qexed_tcp_connect::net_types::{
+    array::{Array, ArrayU8, ArrayVarInt, FixedArray},
+    bitset::FixedBitSet,
+    identifier::Identifier,
+    nbt::{Nbt, NbtCompound, NbtTag},
+    position::Position,
+    rest_buffer::RestBuffer,
+    var_int::VarInt,
+};
+use serde_json::json;
+
+#[test]
+fn readable_json() {
+    assert_eq!(serde_json::to_value(VarInt(300)).unwrap(), json!(300));
+    assert_eq!(serde_json::from_value::<VarInt>(json!(-1)).unwrap(), VarInt(-1));
+
+    let uuid = uuid::Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap();
+    assert_eq!(serde_json::to_value(uuid).unwrap(), json!("069a79f4-44e9-4726-a5be-fca90e38aaf5"));
+
+    let buffer = RestBuffer::from(vec![0x00, 0xab, 0x10]);
+    assert_eq!(serde_json::to_value(&buffer).unwrap(), json!("00ab10"));
+    assert_eq!(serde_json::from_value::<RestBuffer>(json!("00AB10")).unwrap(), buffer);
+    assert!(serde_json::from_value::<RestBuffer>(json!("abc")).is_err());
+
+    let stone = Identifier::parse("stone").unwrap();
+    assert_eq!(serde_json::to_value(&stone).unwrap(), json!("minecraft:stone"));
+    assert!(serde_json::from_value::<Identifier>(json!("Bad:Id")).is_err());
+
+    let array = ArrayVarInt::from_vec(vec![VarInt(1), VarInt(2)]);
+    assert_eq!(serde_json::to_value(&array).unwrap(), json!([1, 2]));
+    assert_eq!(serde_json::from_value::<ArrayVarInt<VarInt>>(json!([1, 2])).unwrap(), array);
+    assert!(serde_json::from_value::<FixedArray<u8, 3>>(json!([1, 2])).is_err());
+    // 超过长度前缀或 MAX 上限的数组在导入时拒绝,而不是写入时 panic
+    assert!(serde_json::from_value::<ArrayU8<VarInt>>(json!(vec![0; 300])).is_err());
+    assert!(serde_json::from_value::<Array<VarInt, u8, 2>>(json!([1, 2, 3])).is_err());
+    assert!(serde_json::from_value::<Array<VarInt, u8, 2>>(json!([1, 2])).is_ok());
+
+    let flags: FixedBitSet<20> = [3, 19].into_iter().collect();
+    assert_eq!(serde_json::to_value(&flags).unwrap(), json!([8, 0, 8]));
+    assert_eq!(serde_json::from_value::<FixedBitSet<20>>(json!([8, 0, 8])).unwrap(), flags);
+    assert!(serde_json::from_value::<FixedBitSet<20>>(json!([])).is_err());
+    assert!(serde_json::from_value::<FixedBitSet<20>>(json!([0, 0, 0, 0])).is_err());
+
+    assert_eq!(serde_json::to_value(Position::new(1, 2, 3)).unwrap(), json!({"x": 1, "y": 2, "z": 3}));
+}
+
+#[test]
+fn nbt_round_trip() {
+    let mut compound = NbtCompound::default();
+    compound.insert("name", NbtTag::String("Steve".to_owned()));
+    compound.insert("health", NbtTag::Float(20.0));
+    let nbt = Nbt(NbtTag::Compound(compound));
+    let json = serde_json::to_string(&nbt).unwrap();
+    assert_eq!(serde_json::from_str::<Nbt>(&json).unwrap(), nbt);
+}
@@ -0,0 +1,137 @@
+use bytes::BytesMut;
+use qexed_tcp_connect::{
+    net_types::{
+        nbt::NbtTag,
+        subdata::Subdata,
+        text_component::{TextComponent, TextShape},
+    },
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+use serde_json::{Value, json};
+
+/// 带长度前缀的字符串
+fn wire(text: &str) -> Vec<u8> {
+    let mut bytes = vec![text.len() as u8];
+    bytes.extend(text.as_bytes());
+    bytes
+}
+
+fn encode<T: Subdata>(value: &T) -> Vec<u8> {
+    let mut buf = BytesMut::new();
+    value.serialize(&mut PacketWriter::new(&mut buf));
+    buf.to_vec()
+}
+
+fn decode<T: Subdata>(bytes: &[u8]) -> T {
+    let mut data = bytes;
+    let mut r = PacketReader::new(Box::new(&mut data));
+    let value = r.deserialize();
+    r.finish().unwrap();
+    value
+}
+
+/// 解码再编码后字节不变
+fn assert_faithful(text: &str) -> TextComponent {
+    let component: TextComponent = decode(&wire(text));
+    assert_eq!(String::from_utf8(encode(&component)[1..].to_vec()).unwrap(), text);
+    component
+}
+
+#[test]
+fn reader_json() {
+    let read = |text: &str| {
+        let bytes = wire(text);
+        let mut data = &bytes[..];
+        let mut r = PacketReader::new(Box::new(&mut data));
+        let value = r.json();
+        r.finish().unwrap();
+        value
+    };
+    assert_eq!(read(r#"{"text":"hi","bold":true}"#), json!({"text": "hi", "bold": true}));
+    assert_eq!(read(r#"["a",{"text":"b"}]"#), json!(["a", {"text": "b"}]));
+    assert_eq!(read(r#""quoted""#), json!("quoted"));
+    // 不是 JSON 时作为字符串
+    assert_eq!(read("Server closed"), json!("Server closed"));
+    assert_eq!(read("{broken"), json!("{broken"));
+}
+
+#[test]
+fn parse_shapes() {
+    let component = TextComponent::from_value(json!("hello")).unwrap();
+    assert_eq!(component.text.as_deref(), Some("hello"));
+    assert_eq!(component.shape, TextShape::String);
+
+    let component = TextComponent::from_value(json!(["", {"text": "a", "color": "red"}, "b"])).unwrap();
+    assert_eq!(component.plain_text(), "ab");
+    assert_eq!(component.extra[0].color.as_deref(), Some("red"));
+    assert_eq!(
+        component.shape,
+        TextShape::Array {
+            head: Box::new(TextShape::String),
+            head_extra: 0
+        }
+    );
+
+    let component = TextComponent::from_value(json!({
+        "translate": "chat.type.text",
+        "with": [{"text": "Steve"}, "hi"],
+        "click_event": {"action": "open_url", "url": "https://example.com"},
+        "hoverEvent": {"action": "show_text", "contents": "tip"},
+    }))
+    .unwrap();
+    assert_eq!(component.with.len(), 2);
+    assert_eq!(component.click_event.unwrap().fields["url"], "https://example.com");
+    assert_eq!(component.hover_event.unwrap().contents, Some(json!("tip")));
+
+    assert!(TextComponent::from_value(json!([])).is_err());
+    assert!(TextComponent::from_value(Value::Null).is_err());
+    assert!(TextComponent::from_value(json!({"bold": "yes"})).is_err());
+}
+
+#[test]
+fn unknown_keys_are_kept() {
+    let component = TextComponent::from_value(json!({
+        "score": {"name": "@p", "objective": "kills"},
+        "shadow_color": -1,
+        "type": "score",
+    }))
+    .unwrap();
+    assert_eq!(component.extra_fields["type"], "score");
+    assert_eq!(component.extra_fields["shadow_color"], -1);
+    assert_eq!(component.to_value()["score"]["objective"], "kills");
+}
+
+#[test]
+fn wire_round_trip_keeps_shape() {
+    assert_eq!(assert_faithful("Server closed").shape, TextShape::Plain);
+    assert_eq!(assert_faithful(r#""quoted""#).shape, TextShape::String);
+    assert_faithful(r#"["",{"text":"a","color":"red"},"b"]"#);
+    assert_faithful(r#"[{"text":"a","extra":["b"]},"c"]"#);
+    assert_faithful(r#"{"text":"x","bold":true,"extra":["y"]}"#);
+    assert_faithful(r#"{"selector":"@a","separator":", "}"#);
+}
+
+#[test]
+fn styled_component_leaves_string_shape() {
+    let component = TextComponent::from_value(json!("hi")).unwrap().color("red");
+    assert_eq!(component.to_value(), json!({"text": "hi", "color": "red"}));
+
+    let mut component: TextComponent = decode(&wire("Server closed"));
+    component.bold = Some(true);
+    assert_eq!(encode(&component)[1..], *br#"{"text":"Server closed","bold":true}"#);
+}
+
+#[test]
+fn invalid_component_is_a_decode_error() {
+    let bytes = wire(r#"{"bold":"yes"}"#);
+    let mut data = &bytes[..];
+    let mut r = PacketReader::new(Box::new(&mut data));
+    r.deserialize::<TextComponent>();
+    assert!(r.finish().unwrap_err().message.starts_with("Invalid text component"));
+}
+
+#[test]
+fn nbt_keeps_shape() {
+    let component = TextComponent::from_nbt(&NbtTag::String("hi".to_string())).unwrap();
+    assert_eq!(component.to_nbt(), NbtTag::String("hi".to_string()));
+}
@@ -0,0 +1,10 @@
+//! 宏的编译期错误,期望输出在 tests/ui/*.stderr
+//!
+//! 修改宏的错误信息后用 `TRYBUILD=overwrite cargo test --test ui` 更新。
+//! 用例在任何特性组合下的输出都应相同,字段类型等需要同时满足 serde / arbitrary 生成的约束
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,9 @@
+#[qexed_packet_macros::packet(id = 0x00)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct PluginMessage {
+    #[rest]
+    data: Vec<u8>,
+    channel: String,
+}
+
+fn main() {}
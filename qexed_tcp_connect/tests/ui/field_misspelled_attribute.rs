@@ -0,0 +1,8 @@
+#[qexed_packet_macros::packet(id = 0x00)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct KeepAlive {
+    #[varnt]
+    id: i32,
+}
+
+fn main() {}
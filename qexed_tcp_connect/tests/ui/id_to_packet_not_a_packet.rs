@@ -0,0 +1,22 @@
+#[qexed_packet_macros::packet(id = 0x00)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct StatusRequest {}
+
+#[derive(Debug, Default, PartialEq, Clone)]
+struct PingRequest {
+    payload: i64,
+}
+
+#[qexed_packet_macros::packet(id = 0xFFF)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct NullPacket {}
+
+impl NullPacket {
+    fn new() -> Self {
+        Self::default()
+    }
+}
+
+qexed_packet_macros::id_to_packet!([StatusRequest, PingRequest], NullPacket);
+
+fn main() {}
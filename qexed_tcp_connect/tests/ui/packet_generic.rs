@@ -0,0 +1,7 @@
+#[qexed_packet_macros::packet(id = 0x00)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn main() {}
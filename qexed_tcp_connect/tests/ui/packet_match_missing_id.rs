@@ -0,0 +1,18 @@
+use qexed_tcp_connect::net_types::packet::Packet;
+
+mod status {
+    #[qexed_packet_macros::packet(id = 0x00)]
+    #[derive(Debug, Default, PartialEq, Clone)]
+    pub struct StatusRequest {}
+
+    // 手写的包池,没有 ID_* 常量
+    pub mod pool {}
+}
+
+fn handle(packet: Box<dyn Packet>) {
+    qexed_packet_macros::packet_match!(packet, status, {
+        case! ID_StatusRequest => StatusRequest as _request {},
+    });
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[qexed_packet_macros::packet(id = 0x00)]
+#[derive(Debug, Default, PartialEq)]
+struct Ping {
+    payload: i64,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[qexed_packet_macros::packet(since = 764, state = login)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct LoginStart {
+    name: String,
+}
+
+fn main() {}
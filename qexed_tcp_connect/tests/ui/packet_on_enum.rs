@@ -0,0 +1,8 @@
+#[qexed_packet_macros::packet(id = 0x00)]
+#[derive(Debug, Default, PartialEq, Clone)]
+enum Action {
+    #[default]
+    Start,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[qexed_packet_macros::packet(id = 0x00, stat = login)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct LoginStart {
+    name: String,
+}
+
+fn main() {}
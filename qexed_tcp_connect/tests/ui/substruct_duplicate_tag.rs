@@ -0,0 +1,11 @@
+#[qexed_packet_macros::substruct]
+#[derive(Debug, Default, PartialEq, Clone)]
+enum Action {
+    #[default]
+    #[id = 1]
+    Start,
+    #[id = 1]
+    Stop,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[qexed_packet_macros::substruct(tag = u8)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct Property {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+#[qexed_packet_macros::substruct(tag = u8)]
+#[derive(Debug, Default, PartialEq, Clone)]
+enum Action {
+    #[default]
+    #[id = 255]
+    Start,
+    Stop,
+}
+
+fn main() {}
@@ -0,0 +1,177 @@
+use std::fmt::Write;
+
+use bytes::Bytes;
+use qexed_tcp_connect::packet::decode::FieldMark;
+
+use crate::DecodedFrame;
+use crate::interceptor::Direction;
+use crate::packets::status::PacketState;
+
+/// 字段颜色 (ANSI)
+const FIELD_COLORS: [&str; 6] = ["\x1b[31m", "\x1b[32m", "\x1b[33m", "\x1b[34m", "\x1b[35m", "\x1b[36m"];
+const COLOR_RESET: &str = "\x1b[0m";
+
+/// 可读的数据包转储
+///
+/// 每个数据包输出流向、状态、包ID、数据包名称和解析后的 `Debug` 内容,
+/// 可选输出按字段着色的十六进制转储
+#[derive(Debug, Clone, Default)]
+pub struct PacketDump {
+    /// 是否输出十六进制转储和字段边界
+    pub hexdump: bool,
+    /// 是否使用 ANSI 颜色标出字段
+    pub color: bool,
+    /// 以 JSON 输出解析后的内容 (需要启用 `serde` 特性,否则仍使用 `Debug`)
+    pub json: bool,
+}
+
+/// 字段在原始帧中的范围
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldRange {
+    pub name: &'static str,
+    pub start: usize,
+    pub end: usize,
+    /// 嵌套层数,数据包的字段为 0
+    pub depth: usize,
+}
+
+impl PacketDump {
+    pub fn new() -> Self {
+        PacketDump {
+            hexdump: false,
+            color: false,
+            json: false,
+        }
+    }
+
+    /// 格式化一个原始帧(包ID + 数据)
+    ///
+    /// `decoded` 是代理已经解析出的结果,转储不会再次解码;
+    /// 登录完成后不再跟踪状态,此时 `state` 和 `decoded` 都为 None
+    pub fn format(
+        &self,
+        direction: Direction,
+        state: Option<PacketState>,
+        data: &Bytes,
+        decoded: Option<&DecodedFrame>,
+    ) -> String {
+        let mut out = String::new();
+        let Some(id) = crate::frame_id(data) else {
+            let _ = write!(out, "{} [{}] <incomplete> ({} bytes)", direction, state_name(state), data.len());
+            return out;
+        };
+
+        let packet = decoded.and_then(|decoded| decoded.packet.as_ref());
+        let name = packet.map_or("?", |packet| packet.name());
+        let _ = write!(
+            out,
+            "{} [{}] 0x{:02X} {} ({} bytes)",
+            direction,
+            state_name(state),
+            id,
+            name,
+            data.len()
+        );
+        if let Some(error) = decoded.and_then(|decoded| decoded.error.as_ref()) {
+            let _ = write!(out, "\n解码失败: {}", error);
+        }
+        if let Some(reason) = decoded.and_then(|decoded| decoded.opaque.as_ref()) {
+            let _ = write!(out, "\n停止解析,按原始字节转发: {}", reason);
+        }
+        if let Some(packet) = packet {
+            match packet.to_json().filter(|_| self.json) {
+                Some(json) => {
+                    let _ = write!(out, "\n{:#}", json);
+                }
+                None => {
+                    let _ = write!(out, "\n{:#?}", packet);
+                }
+            }
+        }
+        if let Some(decoded) = decoded.filter(|decoded| decoded.packet.is_some() && decoded.remaining > 0) {
+            let _ = write!(out, "\n未读取的字节: {}", decoded.remaining);
+        }
+
+        if self.hexdump {
+            let mut fields = Vec::new();
+            if let Some(decoded) = decoded {
+                fields.push(FieldRange {
+                    name: "packet_id",
+                    start: 0,
+                    end: decoded.id_len,
+                    depth: 0,
+                });
+                fields.extend(field_ranges(data.len(), &decoded.fields));
+            }
+            out.push('\n');
+            out.push_str(&self.hexdump(data, &fields));
+        }
+        out
+    }
+
+    /// 十六进制转储,每行 16 字节,并列出字段范围
+    pub fn hexdump(&self, data: &[u8], fields: &[FieldRange]) -> String {
+        let mut out = String::new();
+        // 嵌套的字段排在所属字段之后,取最内层的字段
+        let field_index = |offset: usize| fields.iter().rposition(|field| field.start <= offset && offset < field.end);
+        for (line, chunk) in data.chunks(16).enumerate() {
+            let _ = write!(out, "{:04x}  ", line * 16);
+            for column in 0..16 {
+                match chunk.get(column) {
+                    Some(byte) => {
+                        let offset = line * 16 + column;
+                        match field_index(offset).filter(|_| self.color) {
+                            Some(index) => {
+                                let color = FIELD_COLORS[index % FIELD_COLORS.len()];
+                                let _ = write!(out, "{}{:02x}{} ", color, byte, COLOR_RESET);
+                            }
+                            None => {
+                                let _ = write!(out, "{:02x} ", byte);
+                            }
+                        }
+                    }
+                    None => out.push_str("   "),
+                }
+            }
+            out.push_str(" |");
+            for byte in chunk {
+                out.push(if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' });
+            }
+            out.push_str("|\n");
+        }
+        for (index, field) in fields.iter().enumerate() {
+            let indent = "  ".repeat(field.depth + 1);
+            if self.color {
+                let color = FIELD_COLORS[index % FIELD_COLORS.len()];
+                let _ = writeln!(out, "{}{}0x{:04x}..0x{:04x} {}{}", indent, color, field.start, field.end, field.name, COLOR_RESET);
+            } else {
+                let _ = writeln!(out, "{}0x{:04x}..0x{:04x} {}", indent, field.start, field.end, field.name);
+            }
+        }
+        out
+    }
+}
+
+/// 将字段记录转换为字段范围,嵌套的字段紧跟在所属字段之后
+///
+/// `total` 为整个帧的长度,字段记录中的剩余字节数以它换算为偏移
+pub fn field_ranges(total: usize, marks: &[FieldMark]) -> Vec<FieldRange> {
+    fn flatten(total: usize, marks: &[FieldMark], depth: usize, ranges: &mut Vec<FieldRange>) {
+        for mark in marks {
+            ranges.push(FieldRange {
+                name: mark.name,
+                start: total - mark.before,
+                end: total - mark.after,
+                depth,
+            });
+            flatten(total, &mark.children, depth + 1, ranges);
+        }
+    }
+    let mut ranges = Vec::new();
+    flatten(total, marks, 0, &mut ranges);
+    ranges
+}
+
+fn state_name(state: Option<PacketState>) -> &'static str {
+    state.map_or("untracked", |state| state.as_str())
+}
@@ -0,0 +1,191 @@
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use qexed_tcp_connect::PacketSend;
+use qexed_tcp_connect::net_types::text_component::TextComponent;
+use tokio::sync::Mutex;
+
+use crate::frame_id;
+use crate::interceptor::Direction;
+use crate::packets::server::login::disconnect::Disconnect;
+use crate::packets::status::PacketState;
+
+/// 校验失败时的处理策略
+///
+/// 每个校验函数、拦截函数和异步检查器通过 [`Registration`] 各自指定
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum FailurePolicy {
+    /// 记录失败并原样转发
+    LogAndForward,
+    /// 记录失败并丢弃该数据包
+    DropPacket,
+    /// 登录阶段先向客户端发送登录断开连接数据包(原因为校验错误),再关闭连接
+    ///
+    /// 其他阶段没有可用的断开连接数据包: 握手和状态查询阶段的协议中没有,
+    /// 登录完成后代理不再跟踪状态,无法确定配置阶段或游戏阶段的包ID。
+    /// 这些阶段与 `Abort` 相同,直接关闭连接
+    LoginDisconnect,
+    /// 直接关闭连接
+    #[default]
+    Abort,
+}
+
+/// 校验函数、拦截函数或异步检查器,以及它失败时的处理策略
+pub struct Registration<T: ?Sized> {
+    pub handler: Arc<T>,
+    pub failure_policy: FailurePolicy,
+}
+
+impl<T: ?Sized> Registration<T> {
+    /// 使用默认的失败策略 (`Abort`)
+    pub fn new(handler: Arc<T>) -> Self {
+        Self {
+            handler,
+            failure_policy: FailurePolicy::default(),
+        }
+    }
+    /// 指定失败时的处理策略
+    pub fn with_policy(handler: Arc<T>, failure_policy: FailurePolicy) -> Self {
+        Self { handler, failure_policy }
+    }
+}
+
+impl<T: ?Sized> Clone for Registration<T> {
+    fn clone(&self) -> Self {
+        Self {
+            handler: Arc::clone(&self.handler),
+            failure_policy: self.failure_policy.clone(),
+        }
+    }
+}
+
+impl<T: ?Sized> From<Arc<T>> for Registration<T> {
+    fn from(handler: Arc<T>) -> Self {
+        Self::new(handler)
+    }
+}
+
+/// 一次校验失败的记录
+#[derive(Debug, Clone)]
+pub struct ValidationFailure {
+    /// 数据包流向
+    pub direction: Direction,
+    /// 连接状态,登录完成后不再跟踪状态,为 None
+    pub state: Option<PacketState>,
+    /// 协议版本
+    pub protocol_version: i32,
+    /// 数据包ID,无法读取时为 None
+    pub packet_id: Option<u32>,
+    /// 原始帧长度
+    pub length: usize,
+    /// 错误信息
+    pub error: String,
+}
+
+impl ValidationFailure {
+    pub(crate) fn new(
+        direction: Direction,
+        state: Option<PacketState>,
+        protocol_version: i32,
+        data: &Bytes,
+        error: anyhow::Error,
+    ) -> Self {
+        Self {
+            direction,
+            state,
+            protocol_version,
+            packet_id: frame_id(data),
+            length: data.len(),
+            error: error.to_string(),
+        }
+    }
+}
+
+impl Display for ValidationFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.map_or("untracked", |state| state.as_str());
+        let packet_id = match self.packet_id {
+            Some(id) => format!("0x{:02X}", id),
+            None => "?".to_string(),
+        };
+        write!(
+            f,
+            "[{}] state={} id={} len={}: {}",
+            self.direction, state, packet_id, self.length, self.error
+        )
+    }
+}
+
+/// 会话结束时的校验失败汇总
+#[derive(Debug, Clone)]
+pub struct FailureReport {
+    /// 客户端地址
+    pub peer: SocketAddr,
+    /// 协议版本
+    pub protocol_version: i32,
+    /// 按发生顺序排列的校验失败
+    pub failures: Vec<ValidationFailure>,
+}
+
+impl Display for FailureReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "会话 {} (协议版本: {}) 共 {} 次校验失败",
+            self.peer,
+            self.protocol_version,
+            self.failures.len()
+        )?;
+        for failure in &self.failures {
+            write!(f, "\n  {}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+// 会话结束回调函数类型
+pub type SessionEndHandler = dyn Fn(&FailureReport) + Send + Sync;
+
+/// 处理失败后数据包的去向
+pub(crate) enum FailureFlow {
+    Forward,
+    Drop,
+    Stop,
+}
+
+/// 按策略处理一次校验失败,并记录到会话的失败列表中
+pub(crate) async fn handle_failure(
+    policy: &FailurePolicy,
+    failure: ValidationFailure,
+    failures: &Mutex<Vec<ValidationFailure>>,
+    to_client: &Mutex<PacketSend>,
+    to_server: &Mutex<PacketSend>,
+) -> FailureFlow {
+    tracing::warn!(policy = ?policy, error = %failure.error, "数据包校验失败");
+    let flow = match policy {
+        FailurePolicy::LogAndForward => FailureFlow::Forward,
+        FailurePolicy::DropPacket => FailureFlow::Drop,
+        FailurePolicy::LoginDisconnect => {
+            let mut client_write_guard = to_client.lock().await;
+            if failure.state == Some(PacketState::Login) {
+                let disconnect = Disconnect {
+                    reason: TextComponent::text(format!("Qsniffer 数据包校验失败: {}", failure.error)).color("red"),
+                };
+                let _ = client_write_guard.send(&disconnect).await;
+            }
+            let _ = client_write_guard.shutdown().await;
+            drop(client_write_guard);
+            let _ = to_server.lock().await.shutdown().await;
+            FailureFlow::Stop
+        }
+        FailurePolicy::Abort => {
+            let _ = to_client.lock().await.shutdown().await;
+            let _ = to_server.lock().await.shutdown().await;
+            FailureFlow::Stop
+        }
+    };
+    failures.lock().await.push(failure);
+    flow
+}
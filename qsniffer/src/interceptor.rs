@@ -0,0 +1,290 @@
+use std::fmt::Display;
+use std::panic::AssertUnwindSafe;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use qexed_tcp_connect::{
+    PacketSend,
+    net_types::{packet::Packet, var_int::VarInt},
+    packet::encode::PacketWriter,
+};
+
+use crate::packets::status::PacketState;
+
+/// 数据包的流向
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    /// (C->P->S) 客户端到服务端
+    ClientToServer,
+    /// (S->P->C) 服务端到客户端
+    ServerToClient,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::ClientToServer => write!(f, "C->S"),
+            Direction::ServerToClient => write!(f, "S->C"),
+        }
+    }
+}
+
+/// 等待发送的数据,可以是原始帧(包ID + 数据)或者数据包
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outgoing {
+    Raw(Bytes),
+    Packet(Box<dyn Packet>),
+}
+
+impl From<Bytes> for Outgoing {
+    fn from(value: Bytes) -> Self {
+        Outgoing::Raw(value)
+    }
+}
+
+impl From<Box<dyn Packet>> for Outgoing {
+    fn from(value: Box<dyn Packet>) -> Self {
+        Outgoing::Packet(value)
+    }
+}
+
+/// 拦截器对当前数据包的处理结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    /// 原样转发
+    Forward,
+    /// 使用新的原始帧(包ID + 数据)替换
+    Replace(Bytes),
+    /// 使用新的数据包替换
+    ReplacePacket(Box<dyn Packet>),
+    /// 丢弃,不转发
+    Drop,
+}
+
+/// 拦截器的上下文
+///
+/// 通过 `inject_to_client` / `inject_to_server` 注入的数据包
+/// 会在当前数据包处理完成后按注入顺序发送
+pub struct InterceptContext<'a> {
+    /// 数据包流向
+    pub direction: Direction,
+    /// 当前连接状态
+    pub state: PacketState,
+    /// 握手阶段得到的协议版本,握手前为 -1
+    pub protocol_version: i32,
+    /// 原始帧(包ID + 数据)
+    pub data: &'a Bytes,
+    /// 解析后的数据包,登录完成后的数据包不会被解析,为 None
+    pub packet: Option<&'a dyn Packet>,
+    pub(crate) to_client: Vec<Outgoing>,
+    pub(crate) to_server: Vec<Outgoing>,
+}
+
+impl<'a> InterceptContext<'a> {
+    pub(crate) fn new(
+        direction: Direction,
+        state: PacketState,
+        protocol_version: i32,
+        data: &'a Bytes,
+        packet: Option<&'a dyn Packet>,
+    ) -> Self {
+        Self {
+            direction,
+            state,
+            protocol_version,
+            data,
+            packet,
+            to_client: vec![],
+            to_server: vec![],
+        }
+    }
+    /// 将解析后的数据包转换为具体类型
+    pub fn packet_as<T: 'static>(&self) -> Option<&T> {
+        self.packet?.as_any().downcast_ref::<T>()
+    }
+    /// 向客户端注入数据包
+    pub fn inject_to_client(&mut self, outgoing: impl Into<Outgoing>) {
+        self.to_client.push(outgoing.into());
+    }
+    /// 向服务端注入数据包
+    pub fn inject_to_server(&mut self, outgoing: impl Into<Outgoing>) {
+        self.to_server.push(outgoing.into());
+    }
+}
+
+// 数据包拦截函数类型
+pub type PacketInterceptor = dyn Fn(&mut InterceptContext) -> Result<Verdict> + Send + Sync;
+
+/// 异步数据包检查器
+///
+/// 适用于需要写数据库、等待通道或调用外部程序的校验和拦截逻辑.
+/// 同一方向上的数据包按顺序逐个检查,上一个调用完成前不会读取下一个数据包.
+/// 检查器在同步拦截器之后执行,看到的是拦截器替换后的数据包,被拦截器丢弃的数据包不会交给检查器.
+/// 只做校验时返回 `Verdict::Forward` 即可
+#[async_trait]
+pub trait PacketInspector: Send + Sync {
+    async fn inspect(&self, ctx: &mut InterceptContext<'_>) -> Result<Verdict>;
+}
+
+/// 拦截结果: 需要转发和注入的原始帧,数据包已在拦截链中编码
+pub(crate) struct Interception {
+    pub forward: Option<Bytes>,
+    pub to_client: Vec<Bytes>,
+    pub to_server: Vec<Bytes>,
+}
+
+/// 拦截链中当前的数据包: 原数据包或者前一环节替换后的数据包
+enum ChainPacket<'a> {
+    Original(Option<&'a dyn Packet>),
+    Replaced(Option<Box<dyn Packet>>),
+}
+
+/// 拦截链: 先执行同步拦截器,再执行异步检查器
+///
+/// 后一环节看到的是前一环节的结果: 拦截器替换后,检查器的 `ctx.data` / `ctx.packet`
+/// 为替换后的数据;拦截器丢弃后检查器不再执行。检查器返回 `Verdict::Forward` 表示转发它
+/// 看到的数据。某一环节出错时由调用方按失败策略处理,继续转发时当前数据保持不变,
+/// 该环节注入的数据包被丢弃,后面的环节照常执行。替换和注入的数据包在所在环节编码,
+/// 编码失败同样算作该环节出错
+pub(crate) struct InterceptChain<'a> {
+    direction: Direction,
+    state: PacketState,
+    protocol_version: i32,
+    data: Bytes,
+    packet: ChainPacket<'a>,
+    dropped: bool,
+    to_client: Vec<Bytes>,
+    to_server: Vec<Bytes>,
+}
+
+impl<'a> InterceptChain<'a> {
+    pub fn new(
+        direction: Direction,
+        state: PacketState,
+        protocol_version: i32,
+        data: Bytes,
+        packet: Option<&'a dyn Packet>,
+    ) -> Self {
+        Self {
+            direction,
+            state,
+            protocol_version,
+            data,
+            packet: ChainPacket::Original(packet),
+            dropped: false,
+            to_client: vec![],
+            to_server: vec![],
+        }
+    }
+
+    fn packet(&self) -> Option<&dyn Packet> {
+        match &self.packet {
+            ChainPacket::Original(packet) => *packet,
+            ChainPacket::Replaced(packet) => packet.as_deref(),
+        }
+    }
+
+    fn context(&self) -> InterceptContext<'_> {
+        InterceptContext::new(self.direction, self.state, self.protocol_version, &self.data, self.packet())
+    }
+
+    /// 执行同步拦截器
+    pub fn intercept(&mut self, interceptor: &PacketInterceptor) -> Result<()> {
+        if self.dropped {
+            return Ok(());
+        }
+        let mut ctx = self.context();
+        let verdict = interceptor(&mut ctx);
+        let InterceptContext { to_client, to_server, .. } = ctx;
+        self.apply(verdict?, to_client, to_server)
+    }
+
+    /// 执行异步检查器,超时视为检查失败
+    pub async fn inspect(&mut self, inspector: &dyn PacketInspector, timeout: Option<Duration>) -> Result<()> {
+        if self.dropped {
+            return Ok(());
+        }
+        let mut ctx = self.context();
+        let verdict = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, inspector.inspect(&mut ctx))
+                .await
+                .unwrap_or_else(|_| Err(anyhow!("数据包检查超时: {:?}", timeout))),
+            None => inspector.inspect(&mut ctx).await,
+        };
+        let InterceptContext { to_client, to_server, .. } = ctx;
+        self.apply(verdict?, to_client, to_server)
+    }
+
+    /// 先编码注入的数据包,全部成功后才应用结果
+    fn apply(&mut self, verdict: Verdict, to_client: Vec<Outgoing>, to_server: Vec<Outgoing>) -> Result<()> {
+        let to_client = self.encode_all(to_client)?;
+        let to_server = self.encode_all(to_server)?;
+        match verdict {
+            Verdict::Forward => {}
+            Verdict::Drop => self.dropped = true,
+            Verdict::Replace(data) => {
+                // 当前数据包没有解析(登录完成后)时,替换的原始帧同样不解析
+                let packet = match self.packet() {
+                    Some(_) => crate::read_packet(self.direction, data.clone(), self.state, self.protocol_version).ok(),
+                    None => None,
+                };
+                self.data = data;
+                self.packet = ChainPacket::Replaced(packet);
+            }
+            Verdict::ReplacePacket(replacement) => {
+                // 与当前数据包相同时保留原有字节,避免重新编码改变字节
+                if !self.packet().is_some_and(|current| replacement.dyn_eq(current)) {
+                    self.data = encode_frame(&*replacement, self.protocol_version)?;
+                    self.packet = ChainPacket::Replaced(Some(replacement));
+                }
+            }
+        }
+        self.to_client.extend(to_client);
+        self.to_server.extend(to_server);
+        Ok(())
+    }
+
+    fn encode_all(&self, outgoing: Vec<Outgoing>) -> Result<Vec<Bytes>> {
+        outgoing
+            .into_iter()
+            .map(|outgoing| match outgoing {
+                Outgoing::Raw(data) => Ok(data),
+                Outgoing::Packet(packet) => encode_frame(&*packet, self.protocol_version),
+            })
+            .collect()
+    }
+
+    /// 结束拦截链,得到需要转发和注入的数据
+    pub fn finish(self) -> Interception {
+        Interception {
+            forward: (!self.dropped).then_some(self.data),
+            to_client: self.to_client,
+            to_server: self.to_server,
+        }
+    }
+}
+
+/// 将拦截器构造的数据包编码为原始帧(包ID + 数据)
+///
+/// 编码不合法的数据包可能 panic (例如数组超过长度上限);与解码一样用 `catch_unwind`
+/// 转换为错误,交给所在环节的失败策略,而不是让一个方向的任务退出
+fn encode_frame(packet: &dyn Packet, protocol_version: i32) -> Result<Bytes> {
+    std::panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut buf = BytesMut::new();
+        let mut writer = PacketWriter::new(&mut buf).with_protocol_version(protocol_version);
+        writer.varint(&VarInt(packet.id() as i32));
+        packet.serialize(&mut writer);
+        buf.freeze()
+    }))
+    .map_err(|panic| anyhow!("编码数据包 {} 时 panic: {}", packet.name(), crate::panic_message(&*panic)))
+}
+
+/// 按顺序发送原始帧
+pub(crate) async fn send_all(write: &mut PacketSend, frames: impl IntoIterator<Item = Bytes>) -> std::io::Result<()> {
+    for data in frames {
+        write.send_raw(data).await?;
+    }
+    Ok(())
+}
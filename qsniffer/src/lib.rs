@@ -1,12 +1,22 @@
-use anyhow::Result;
-use bytes::{Bytes, BytesMut};
+use anyhow::{Result, anyhow};
+use bytes::Bytes;
+use std::net::SocketAddr;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
+use tracing::Instrument;
 
 mod nullpacket;
-mod packets;
+pub mod dump;
+pub mod failure;
+pub mod interceptor;
+pub mod packets;
 
-use qexed_tcp_connect::{net_types::packet::Packet, packet::decode::PacketReader};
+use qexed_tcp_connect::{PacketSend, net_types::{packet::Packet, text_component::TextComponent}, packet::decode::{FieldMark, PacketReader}};
+use dump::PacketDump;
+use failure::{FailureFlow, FailurePolicy, FailureReport, Registration, SessionEndHandler, ValidationFailure, handle_failure};
+use interceptor::{Direction, InterceptChain, Interception, PacketInspector, PacketInterceptor, send_all};
 use packets::server::login::disconnect::Disconnect;
 
 // 共享状态结构体
@@ -29,14 +39,39 @@ impl SharedState {
 // 数据包校验函数类型
 pub type PacketValidator = dyn Fn(&Bytes, i32) -> Result<()> + Send + Sync;
 
+/// 代理配置
+#[derive(Default, Clone)]
+pub struct ProxyConfig {
+    /// 客户端数据包校验函数
+    pub client_validator: Option<Registration<PacketValidator>>,
+    /// 服务端数据包校验函数
+    pub server_validator: Option<Registration<PacketValidator>>,
+    /// 客户端数据包拦截函数,可以修改、丢弃或注入数据包
+    pub client_interceptor: Option<Registration<PacketInterceptor>>,
+    /// 服务端数据包拦截函数,可以修改、丢弃或注入数据包
+    pub server_interceptor: Option<Registration<PacketInterceptor>>,
+    /// 客户端数据包异步检查器
+    pub client_inspector: Option<Registration<dyn PacketInspector>>,
+    /// 服务端数据包异步检查器
+    pub server_inspector: Option<Registration<dyn PacketInspector>>,
+    /// 每次异步检查的超时时间,超时视为检查失败
+    pub inspector_timeout: Option<Duration>,
+    /// 数据包解码失败时的处理策略,两个方向相同
+    pub decode_failure_policy: FailurePolicy,
+    /// 会话结束时的回调,参数为本次会话的校验失败汇总
+    pub on_session_end: Option<Arc<SessionEndHandler>>,
+    /// 可读的数据包转储,通过 `qsniffer::dump` target 输出
+    pub dump: Option<PacketDump>,
+}
+
 /// 运行 Minecraft 代理服务器
-/// 
+///
 /// # 参数
 /// - `proxy_bind_addr`: 代理服务器绑定的地址 (例如: "0.0.0.0:25565")
 /// - `server_addr`: 目标服务器地址 (例如: "127.0.0.1:25566")
 /// - `client_validator`: 客户端数据包校验函数
 /// - `server_validator`: 服务端数据包校验函数
-/// 
+///
 /// # 返回
 /// - `Result<()>`: 如果运行成功返回Ok(()), 否则返回错误
 pub async fn run_proxy(
@@ -44,13 +79,49 @@ pub async fn run_proxy(
     server_addr: &str,
     client_validator: Option<Arc<PacketValidator>>,
     server_validator: Option<Arc<PacketValidator>>,
+) -> Result<()> {
+    let config = ProxyConfig {
+        client_validator: client_validator.map(Registration::new),
+        server_validator: server_validator.map(Registration::new),
+        ..Default::default()
+    };
+    run_proxy_with_config(proxy_bind_addr, server_addr, config).await
+}
+
+/// 使用完整配置运行 Minecraft 代理服务器
+///
+/// # 参数
+/// - `proxy_bind_addr`: 代理服务器绑定的地址 (例如: "0.0.0.0:25565")
+/// - `server_addr`: 目标服务器地址 (例如: "127.0.0.1:25566")
+/// - `config`: 代理配置
+///
+/// # 返回
+/// - `Result<()>`: 如果运行成功返回Ok(()), 否则返回错误
+pub async fn run_proxy_with_config(
+    proxy_bind_addr: &str,
+    server_addr: &str,
+    config: ProxyConfig,
 ) -> Result<()> {
     let tcplistener = tokio::net::TcpListener::bind(proxy_bind_addr).await?;
-    println!("代理服务器启动在: {}", proxy_bind_addr);
-    
+    run_proxy_with_listener(tcplistener, server_addr, config).await
+}
+
+/// 在已绑定的监听器上运行代理服务器
+///
+/// 绑定端口 0 时可以先通过 `local_addr()` 取得实际端口,适合测试
+pub async fn run_proxy_with_listener(
+    tcplistener: tokio::net::TcpListener,
+    server_addr: &str,
+    config: ProxyConfig,
+) -> Result<()> {
+    tracing::info!(addr = %tcplistener.local_addr()?, "代理服务器启动");
+
+    let mut connection_id: u64 = 0;
     while let std::result::Result::Ok((socket, socketaddr)) = tcplistener.accept().await {
-        println!("新的客户端连接: {}", socketaddr);
-        
+        connection_id += 1;
+        let connection_span = tracing::info_span!("connection", id = connection_id, peer = %socketaddr);
+        tracing::info!(parent: &connection_span, "新的客户端连接");
+
         let network_compression_threshold = 256;
         let (socket_read, socket_write) = tokio::io::split(socket);
         let packet_socket = qexed_tcp_connect::PacketListener::new(
@@ -59,22 +130,21 @@ pub async fn run_proxy(
             network_compression_threshold,
         );
 
-        // 克隆验证器以便在异步任务中使用
-        let client_validator_clone = client_validator.clone();
-        let server_validator_clone = server_validator.clone();
+        // 克隆配置以便在异步任务中使用
+        let config = config.clone();
         let server_addr = server_addr.to_string();
-        
+
         tokio::spawn(async move {
             if let Err(e) = client_handle(
-                packet_socket, 
-                network_compression_threshold, 
+                packet_socket,
+                network_compression_threshold,
                 &server_addr,
-                client_validator_clone,
-                server_validator_clone,
+                socketaddr,
+                config,
             ).await {
-                eprintln!("客户端处理错误: {}", e);
+                tracing::error!(error = %e, "客户端处理错误");
             }
-        });
+        }.instrument(connection_span));
     }
     Ok(())
 }
@@ -83,13 +153,13 @@ async fn client_handle(
     packet_socket: qexed_tcp_connect::PacketListener,
     network_compression_threshold: usize,
     server_addr: &str,
-    client_validator: Option<Arc<PacketValidator>>,
-    server_validator: Option<Arc<PacketValidator>>,
+    peer: SocketAddr,
+    config: ProxyConfig,
 ) -> Result<()> {
     let (mut packet_read, packet_write) = packet_socket.split();
     let client_socket = tokio::net::TcpStream::connect(server_addr).await?;
-    println!("连接到目标服务器: {}", server_addr);
-    
+    tracing::info!(server = server_addr, "连接到目标服务器");
+
     let (client_socket_read, client_socket_write) = tokio::io::split(client_socket);
     let client_packet_socket = qexed_tcp_connect::PacketListener::new(
         client_socket_read,
@@ -97,221 +167,497 @@ async fn client_handle(
         network_compression_threshold,
     );
     let (mut client_packet_read, client_packet_write) = client_packet_socket.split();
-    
+
     // 使用Arc和Mutex来共享状态和PacketSend对象
     let shared_state = Arc::new(Mutex::new(SharedState::new()));
     let client_packet_write_shared = Arc::new(Mutex::new(client_packet_write));
     let packet_write_shared = Arc::new(Mutex::new(packet_write));
+    let failures_shared = Arc::new(Mutex::new(Vec::new()));
 
     // (C->P->S) 客户端到服务端的数据流
     let state_clone1 = Arc::clone(&shared_state);
     let client_packet_write_clone = Arc::clone(&client_packet_write_shared);
-    let client_validator_clone = client_validator.clone();
-    
+    let packet_write_clone1 = Arc::clone(&packet_write_shared);
+    let failures_clone1 = Arc::clone(&failures_shared);
+    let client_validator_clone = config.client_validator.clone();
+    let client_interceptor_clone = config.client_interceptor.clone();
+    let client_inspector_clone = config.client_inspector.clone();
+    let decode_failure_policy = config.decode_failure_policy.clone();
+    let inspector_timeout = config.inspector_timeout;
+    let dump_clone1 = config.dump.clone();
+
     let client_to_server_handle = tokio::spawn(async move {
         loop {
-            let raw_packets_result = packet_read.read().await;
+            let raw_packets_result = packet_read.read_frame().await;
             let packets = match raw_packets_result {
-                Ok(packets) => Bytes::from(packets),
+                Ok(packets) => packets,
                 Err(_) => break,
             };
-            
+
             // 获取当前状态
             let (current_state, is_finish, protocol_version) = {
                 let state = state_clone1.lock().await;
                 (state.packet_state, state.is_finish, state.protocol_version)
             };
-            
+            let tracked_state = (!is_finish).then_some(current_state);
+
+            // 只在十六进制转储时记录字段边界
+            let track_fields = dump_clone1.as_ref().is_some_and(|dump| dump.hexdump);
+            let decoded = (!is_finish)
+                .then(|| DecodedFrame::decode(Direction::ClientToServer, current_state, protocol_version, &packets, track_fields));
+            let decoded_packet = decoded.as_ref().and_then(|decoded| decoded.packet.as_deref());
+            let packet_span = packet_span(Direction::ClientToServer, tracked_state, &packets, decoded_packet);
+            tracing::trace!(parent: &packet_span, "收到数据包");
+            if let Some(dump) = &dump_clone1 {
+                let text = dump.format(Direction::ClientToServer, tracked_state, &packets, decoded.as_ref());
+                tracing::info!(target: "qsniffer::dump", parent: &packet_span, "\n{}", text);
+            }
+
+            // 解码失败按校验失败处理,继续转发时没有解析后的数据包
+            let packet = match decoded.map_or(Ok(None), DecodedFrame::into_result) {
+                Ok(packet) => packet,
+                Err(e) => {
+                    let failure = ValidationFailure::new(Direction::ClientToServer, tracked_state, protocol_version, &packets, e);
+                    match handle_failure(&decode_failure_policy, failure, &failures_clone1, &packet_write_clone1, &client_packet_write_clone).instrument(packet_span.clone()).await {
+                        FailureFlow::Forward => None,
+                        FailureFlow::Drop => continue,
+                        FailureFlow::Stop => break,
+                    }
+                }
+            };
+
             // 如果有客户端验证器，执行验证
-            if let Some(validator) = &client_validator_clone {
-                if let Err(e) = validator(&packets, protocol_version) {
-                    eprintln!("客户端数据包验证失败: {}", e);
-                    break;
+            if let Some(validator) = &client_validator_clone
+                && let Err(e) = packet_span.in_scope(|| (validator.handler)(&packets, protocol_version))
+            {
+                let failure = ValidationFailure::new(Direction::ClientToServer, tracked_state, protocol_version, &packets, e);
+                match handle_failure(&validator.failure_policy, failure, &failures_clone1, &packet_write_clone1, &client_packet_write_clone).instrument(packet_span.clone()).await {
+                    FailureFlow::Forward => {}
+                    FailureFlow::Drop => continue,
+                    FailureFlow::Stop => break,
                 }
             }
-            
-            if !is_finish {
-                if let Ok(packet) = read_packet_client(packets.clone(), current_state) {
-                    match current_state {
-                        packets::status::PacketState::Handshaking => {
-                            if packet.id() == 0x00 {
-                                if let Some(handshake) = packet.as_any().downcast_ref::<packets::client::handshaking::set_protocol::SetProtocol>() {
-                                    // 更新共享状态
-                                    let mut state = state_clone1.lock().await;
-                                    state.protocol_version = handshake.protocol_version.0;
-                                    match handshake.next_state.0 {
-                                        1 => state.packet_state = packets::status::PacketState::Status,
-                                        2 => state.packet_state = packets::status::PacketState::Login,
-                                        _ => {}
-                                    }
+            if let Some(packet) = &packet {
+                match current_state {
+                    packets::status::PacketState::Handshaking => {
+                        if packet.id() == 0x00
+                            && let Some(handshake) = packet.as_any().downcast_ref::<packets::client::handshaking::set_protocol::SetProtocol>()
+                        {
+                            // 更新共享状态
+                            {
+                                let mut state = state_clone1.lock().await;
+                                state.protocol_version = handshake.protocol_version.0;
+                                match handshake.next_state.0 {
+                                    1 => state.packet_state = packets::status::PacketState::Status,
+                                    2 => state.packet_state = packets::status::PacketState::Login,
+                                    _ => {}
                                 }
                             }
-                        },
-                        packets::status::PacketState::Status => {
-                            // 更新完成状态
+                            // 拦截器发出的数据包按客户端的协议版本序列化
+                            packet_write_clone1.lock().await.set_protocol_version(handshake.protocol_version.0);
+                            client_packet_write_clone.lock().await.set_protocol_version(handshake.protocol_version.0);
+                        }
+                    },
+                    // 状态查询阶段没有后续状态,一直解析到连接关闭。原来在客户端的第一个
+                    // 数据包后就停止跟踪,服务端的状态响应和 Ping 都不会被解析,
+                    // 拦截器无法按类型改写服务器列表信息
+                    packets::status::PacketState::Status => {}
+                    packets::status::PacketState::Login => {
+                        if packet.id() == 0x03 {
                             let mut state = state_clone1.lock().await;
                             state.is_finish = true;
                         }
-                        packets::status::PacketState::Login => {
-                            if packet.id() == 0x03 {
-                                let mut state = state_clone1.lock().await;
-                                state.is_finish = true;
-                            }
-                        }
                     }
                 }
             }
-            
+
+            // 如果有客户端拦截器，执行拦截: 检查器看到拦截器处理后的结果
+            let mut chain = InterceptChain::new(Direction::ClientToServer, current_state, protocol_version, packets.clone(), packet.as_deref());
+            if let Some(interceptor) = &client_interceptor_clone
+                && let Err(e) = packet_span.in_scope(|| chain.intercept(interceptor.handler.as_ref()))
+            {
+                let failure = ValidationFailure::new(Direction::ClientToServer, tracked_state, protocol_version, &packets, e);
+                match handle_failure(&interceptor.failure_policy, failure, &failures_clone1, &packet_write_clone1, &client_packet_write_clone).instrument(packet_span.clone()).await {
+                    FailureFlow::Forward => {}
+                    FailureFlow::Drop => continue,
+                    FailureFlow::Stop => break,
+                }
+            }
+            if let Some(inspector) = &client_inspector_clone
+                && let Err(e) = chain.inspect(inspector.handler.as_ref(), inspector_timeout).instrument(packet_span.clone()).await
+            {
+                let failure = ValidationFailure::new(Direction::ClientToServer, tracked_state, protocol_version, &packets, e);
+                match handle_failure(&inspector.failure_policy, failure, &failures_clone1, &packet_write_clone1, &client_packet_write_clone).instrument(packet_span.clone()).await {
+                    FailureFlow::Forward => {}
+                    FailureFlow::Drop => continue,
+                    FailureFlow::Stop => break,
+                }
+            }
+            let Interception { forward, to_client, to_server } = chain.finish();
+
             // 使用锁来发送数据
             let mut write_guard = client_packet_write_clone.lock().await;
-            if write_guard.send_raw(packets).await.is_err() {
+            if send_all(&mut write_guard, forward.into_iter().chain(to_server)).await.is_err() {
                 break;
             }
+            drop(write_guard);
+            if !to_client.is_empty() {
+                let mut packet_write_guard = packet_write_clone1.lock().await;
+                if send_to_client(&mut packet_write_guard, to_client, current_state, protocol_version).await.is_err() {
+                    break;
+                }
+            }
         }
-        println!("客户端到服务端的数据流结束");
-    });
+        tracing::info!("客户端到服务端的数据流结束");
+    }.in_current_span());
 
     // (S->P->C) 服务端到客户端的数据流
     let state_clone2 = Arc::clone(&shared_state);
     let client_packet_write_clone2 = Arc::clone(&client_packet_write_shared);
     let packet_write_clone = Arc::clone(&packet_write_shared);
-    let server_validator_clone = server_validator.clone();
-    
+    let failures_clone2 = Arc::clone(&failures_shared);
+    let server_validator_clone = config.server_validator.clone();
+    let server_interceptor_clone = config.server_interceptor.clone();
+    let server_inspector_clone = config.server_inspector.clone();
+    let decode_failure_policy = config.decode_failure_policy.clone();
+    let dump_clone2 = config.dump.clone();
+
     let server_to_client_handle = tokio::spawn(async move {
         loop {
-            let raw_packets_result = client_packet_read.read().await;
+            let raw_packets_result = client_packet_read.read_frame().await;
             let packets = match raw_packets_result {
-                Ok(packets) => Bytes::from(packets),
+                Ok(packets) => packets,
                 Err(_) => break,
             };
-            
+
             // 获取当前状态
             let (current_state, is_finish, protocol_version) = {
                 let state = state_clone2.lock().await;
                 (state.packet_state, state.is_finish, state.protocol_version)
             };
-            
+            let tracked_state = (!is_finish).then_some(current_state);
+
+            // 只在十六进制转储时记录字段边界
+            let track_fields = dump_clone2.as_ref().is_some_and(|dump| dump.hexdump);
+            let decoded = (!is_finish)
+                .then(|| DecodedFrame::decode(Direction::ServerToClient, current_state, protocol_version, &packets, track_fields));
+            let decoded_packet = decoded.as_ref().and_then(|decoded| decoded.packet.as_deref());
+            let packet_span = packet_span(Direction::ServerToClient, tracked_state, &packets, decoded_packet);
+            tracing::trace!(parent: &packet_span, "收到数据包");
+            if let Some(dump) = &dump_clone2 {
+                let text = dump.format(Direction::ServerToClient, tracked_state, &packets, decoded.as_ref());
+                tracing::info!(target: "qsniffer::dump", parent: &packet_span, "\n{}", text);
+            }
+
+            // 解码失败按校验失败处理,继续转发时没有解析后的数据包
+            let packet = match decoded.map_or(Ok(None), DecodedFrame::into_result) {
+                Ok(packet) => packet,
+                Err(e) => {
+                    let failure = ValidationFailure::new(Direction::ServerToClient, tracked_state, protocol_version, &packets, e);
+                    match handle_failure(&decode_failure_policy, failure, &failures_clone2, &packet_write_clone, &client_packet_write_clone2).instrument(packet_span.clone()).await {
+                        FailureFlow::Forward => None,
+                        FailureFlow::Drop => continue,
+                        FailureFlow::Stop => break,
+                    }
+                }
+            };
+
+            // 服务端发出设置压缩后就按它的阈值收发,与校验和拦截的结果无关
+            if current_state == packets::status::PacketState::Login
+                && let Some(compress) = packet.as_ref().and_then(|packet| packet.as_any().downcast_ref::<packets::server::login::compress::Compress>())
+            {
+                let client_write_guard = client_packet_write_clone2.lock().await;
+                set_compression(&client_write_guard, compress.threshold.0);
+                tracing::info!(threshold = compress.threshold.0, "已启用与服务端之间的压缩");
+            }
+
             // 如果有服务端验证器，执行验证
-            if let Some(validator) = &server_validator_clone {
-                if let Err(e) = validator(&packets,  protocol_version) {
-                    eprintln!("服务端数据包验证失败: {}", e);
+            if let Some(validator) = &server_validator_clone
+                && let Err(e) = packet_span.in_scope(|| (validator.handler)(&packets, protocol_version))
+            {
+                let failure = ValidationFailure::new(Direction::ServerToClient, tracked_state, protocol_version, &packets, e);
+                match handle_failure(&validator.failure_policy, failure, &failures_clone2, &packet_write_clone, &client_packet_write_clone2).instrument(packet_span.clone()).await {
+                    FailureFlow::Forward => {}
+                    FailureFlow::Drop => continue,
+                    FailureFlow::Stop => break,
+                }
+            }
+            let is_login = current_state == packets::status::PacketState::Login;
+            if is_login && packet.as_ref().is_some_and(|packet| packet.id() == 0x01) {
+                // 检测到加密请求，发送错误消息并关闭连接
+                let disconnect = Disconnect {
+                    reason: TextComponent::text("Qsniffer 不支持加密的服务端的数据包校验！请关闭服务端加密")
+                        .color("red")
+                        .bold(true),
+                };
+
+                let mut packet_write_guard = packet_write_clone.lock().await;
+                if packet_write_guard.send(&disconnect).await.is_err() {
                     break;
                 }
+                let _ = packet_write_guard.shutdown().await;
+                continue;
             }
-            
-            if !is_finish {
-                if let Ok(packet) = read_packet_server(packets.clone(), current_state, protocol_version) {
-                    match current_state {
-                        packets::status::PacketState::Login => {
-                            match packet.id() {
-                                0x01 => {
-                                    // 检测到加密请求，发送错误消息并关闭连接
-                                    let mut disconnect = Disconnect::default();
-                                    disconnect.reason = serde_json::json!({
-                                        "text": "Qsniffer 不支持加密的服务端的数据包校验！请关闭服务端加密",
-                                        "color": "red",
-                                        "bold": true
-                                    });
-                                    
-                                    let mut packet_write_guard = packet_write_clone.lock().await;
-                                    if packet_write_guard.send(&disconnect).await.is_err() {
-                                        break;
-                                    }
-                                    let _ = packet_write_guard.shutdown().await;
-                                    continue;
-                                }
-                                0x03 => {
-                                    if let Some(compress) = packet.as_any().downcast_ref::<packets::server::login::compress::Compress>() {
-                                        let network_compression_threshold = compress.threshold.0 as usize;
-                                        
-                                        // 先发送压缩包给客户端
-                                        let mut packet_write_guard = packet_write_clone.lock().await;
-                                        if packet_write_guard.send_raw(packets.clone()).await.is_err() {
-                                            break;
-                                        }
-                                        
-                                        // 设置代理端->客户端的压缩
-                                        packet_write_guard.set_compression_value(network_compression_threshold);
-                                        packet_write_guard.set_compression(true);
-                                        
-                                        // 设置代理端->服务端的压缩
-                                        let client_write_guard = client_packet_write_clone2.lock().await;
-                                        client_write_guard.set_compression_value(network_compression_threshold);
-                                        client_write_guard.set_compression(true);
-                                        
-                                        println!("已启用压缩，阈值: {}", network_compression_threshold);
-                                        continue;
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                        _ => {}
-                    }
+
+            // 如果有服务端拦截器，执行拦截: 检查器看到拦截器处理后的结果
+            let mut chain = InterceptChain::new(Direction::ServerToClient, current_state, protocol_version, packets.clone(), packet.as_deref());
+            if let Some(interceptor) = &server_interceptor_clone
+                && let Err(e) = packet_span.in_scope(|| chain.intercept(interceptor.handler.as_ref()))
+            {
+                let failure = ValidationFailure::new(Direction::ServerToClient, tracked_state, protocol_version, &packets, e);
+                match handle_failure(&interceptor.failure_policy, failure, &failures_clone2, &packet_write_clone, &client_packet_write_clone2).instrument(packet_span.clone()).await {
+                    FailureFlow::Forward => {}
+                    FailureFlow::Drop => continue,
+                    FailureFlow::Stop => break,
+                }
+            }
+            if let Some(inspector) = &server_inspector_clone
+                && let Err(e) = chain.inspect(inspector.handler.as_ref(), inspector_timeout).instrument(packet_span.clone()).await
+            {
+                let failure = ValidationFailure::new(Direction::ServerToClient, tracked_state, protocol_version, &packets, e);
+                match handle_failure(&inspector.failure_policy, failure, &failures_clone2, &packet_write_clone, &client_packet_write_clone2).instrument(packet_span.clone()).await {
+                    FailureFlow::Forward => {}
+                    FailureFlow::Drop => continue,
+                    FailureFlow::Stop => break,
                 }
             }
-            
-            // 正常发送其他数据包
+            let Interception { forward, to_client, to_server } = chain.finish();
+
+            // 与客户端之间的压缩按实际发给客户端的数据包切换
             let mut packet_write_guard = packet_write_clone.lock().await;
-            if packet_write_guard.send_raw(packets).await.is_err() {
+            if send_to_client(&mut packet_write_guard, forward.into_iter().chain(to_client), current_state, protocol_version).await.is_err() {
                 break;
             }
+            drop(packet_write_guard);
+            if !to_server.is_empty() {
+                let mut client_write_guard = client_packet_write_clone2.lock().await;
+                if send_all(&mut client_write_guard, to_server).await.is_err() {
+                    break;
+                }
+            }
         }
-        println!("服务端到客户端的数据流结束");
-    });
+        tracing::info!("服务端到客户端的数据流结束");
+    }.in_current_span());
 
     // 等待两个任务完成
     let (client_result, server_result) = tokio::join!(client_to_server_handle, server_to_client_handle);
-    
+
     // 检查任务是否正常结束
     if let Err(e) = client_result {
-        eprintln!("客户端到服务端任务错误: {}", e);
+        tracing::error!(error = %e, "客户端到服务端任务错误");
     }
     if let Err(e) = server_result {
-        eprintln!("服务端到客户端任务错误: {}", e);
+        tracing::error!(error = %e, "服务端到客户端任务错误");
+    }
+
+    // 汇总本次会话的校验失败
+    let report = FailureReport {
+        peer,
+        protocol_version: shared_state.lock().await.protocol_version,
+        failures: std::mem::take(&mut *failures_shared.lock().await),
+    };
+    if !report.failures.is_empty() {
+        tracing::warn!("{}", report);
     }
-    
+    if let Some(on_session_end) = &config.on_session_end {
+        on_session_end(&report);
+    }
+
     Ok(())
 }
 
-// 数据包读取函数
-fn read_packet_client(
-    data: Bytes,
+/// 向客户端发送数据
+///
+/// 登录阶段发出设置压缩数据包后,之后的数据立即按它的阈值压缩。阈值取自实际发出的数据包:
+/// 拦截器丢弃、替换或注入设置压缩数据包时,与客户端之间的连接跟随替换后的结果
+async fn send_to_client(
+    write: &mut PacketSend,
+    frames: impl IntoIterator<Item = Bytes>,
+    state: packets::status::PacketState,
+    protocol_version: i32,
+) -> std::io::Result<()> {
+    for data in frames {
+        let threshold = (state == packets::status::PacketState::Login)
+            .then(|| compression_threshold(&data, protocol_version))
+            .flatten();
+        send_all(write, [data]).await?;
+        if let Some(threshold) = threshold {
+            set_compression(write, threshold);
+            tracing::info!(threshold, "已启用与客户端之间的压缩");
+        }
+    }
+    Ok(())
+}
+
+/// 待发送的数据是设置压缩数据包时返回它的阈值
+fn compression_threshold(data: &Bytes, protocol_version: i32) -> Option<i32> {
+    use packets::server::login::compress::Compress;
+    let packet = read_packet(Direction::ServerToClient, data.clone(), packets::status::PacketState::Login, protocol_version).ok()?;
+    packet.as_any().downcast_ref::<Compress>().map(|compress| compress.threshold.0)
+}
+
+/// `catch_unwind` 捕获的 panic 信息
+pub(crate) fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_default()
+}
+
+/// 按设置压缩数据包的阈值切换压缩,阈值为负数时关闭压缩
+fn set_compression(write: &PacketSend, threshold: i32) {
+    if threshold < 0 {
+        write.set_compression(false);
+    } else {
+        write.set_compression_value(threshold as usize);
+        write.set_compression(true);
+    }
+}
+
+/// 读取原始帧开头的包ID,数据不完整时返回 None
+pub(crate) fn frame_id(data: &[u8]) -> Option<u32> {
+    let mut value = 0u32;
+    for (position, byte) in data.iter().take(5).enumerate() {
+        value |= (*byte as u32 & 0x7F) << (7 * position);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// 为单个数据包创建 tracing span
+fn packet_span(
+    direction: Direction,
+    state: Option<packets::status::PacketState>,
+    data: &Bytes,
+    packet: Option<&dyn Packet>,
+) -> tracing::Span {
+    tracing::debug_span!(
+        "packet",
+        direction = %direction,
+        state = state.map_or("untracked", |state| state.as_str()),
+        id = frame_id(data),
+        name = packet.map(|packet| packet.name()),
+        size = data.len(),
+    )
+}
+
+// 根据流向、状态和包ID创建对应的数据包
+pub(crate) fn id_to_packet(
+    direction: Direction,
     status: packets::status::PacketState,
-) -> Result<Box<dyn Packet>> {
-    let mut buf = BytesMut::new();
-    buf.extend_from_slice(&data);
-    let mut reader = PacketReader::new(Box::new(&mut buf));
-    let id = reader.varint().0 as u32;
-    
-    let mut decoded = match status {
-        packets::status::PacketState::Handshaking => {
-            packets::client::handshaking::pool::id_to_packet(id)
+    id: u32,
+    protocol_version: i32,
+) -> Box<dyn Packet> {
+    match direction {
+        Direction::ClientToServer => match status {
+            packets::status::PacketState::Handshaking => {
+                packets::client::handshaking::pool::id_to_packet(id, protocol_version)
+            }
+            packets::status::PacketState::Status => {
+                packets::client::status::pool::id_to_packet(id, protocol_version)
+            }
+            packets::status::PacketState::Login => {
+                packets::client::login::pool::id_to_packet(id, protocol_version)
+            }
+        },
+        Direction::ServerToClient => match status {
+            packets::status::PacketState::Handshaking => Box::new(nullpacket::NullPacket::new()),
+            packets::status::PacketState::Status => {
+                packets::server::status::pool::id_to_packet(id, protocol_version)
+            }
+            packets::status::PacketState::Login => {
+                packets::server::login::pool::id_to_packet(id, protocol_version)
+            }
+        },
+    }
+}
+
+/// 一个帧(包ID + 数据)的解析结果
+pub struct DecodedFrame {
+    /// 解析出的数据包,解码失败时只读出了部分字段;解码时 panic 为 None
+    pub packet: Option<Box<dyn Packet>>,
+    /// 包ID占用的字节数
+    pub id_len: usize,
+    /// 读取结束后剩余的字节数
+    pub remaining: usize,
+    /// 字段边界,只在要求记录时才有
+    pub fields: Vec<FieldMark>,
+    /// 解码错误
+    pub error: Option<anyhow::Error>,
+    /// 数据包中有无法解析的数据 (例如未注册的物品组件) 时停止解析的原因,
+    /// 此时 `packet` 只有部分字段,按原始字节转发
+    pub opaque: Option<String>,
+}
+
+impl DecodedFrame {
+    /// 按流向和状态解析一个帧,`track_fields` 为 true 时记录字段边界
+    ///
+    /// 解码本身不会 panic;`catch_unwind` 只是兜底,数据包实现中的 bug
+    /// 不应该让一个方向的任务退出而留下半开的会话
+    pub fn decode(
+        direction: Direction,
+        state: packets::status::PacketState,
+        protocol_version: i32,
+        data: &Bytes,
+        track_fields: bool,
+    ) -> Self {
+        std::panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut buf = data.clone();
+            let mut reader = PacketReader::new(Box::new(&mut buf)).with_protocol_version(protocol_version);
+            if track_fields {
+                reader.track_fields();
+            }
+            let id = reader.varint().0 as u32;
+            let id_len = data.len() - reader.buf.remaining();
+
+            let mut packet = id_to_packet(direction, state, id, protocol_version);
+            packet.deserialize(&mut reader);
+            DecodedFrame {
+                packet: Some(packet),
+                id_len,
+                remaining: reader.buf.remaining(),
+                fields: reader.take_fields(),
+                opaque: reader.opaque().map(str::to_owned),
+                error: reader.finish().err().map(anyhow::Error::from),
+            }
+        }))
+        .unwrap_or_else(|panic| {
+            let message = panic_message(&*panic);
+            DecodedFrame {
+                packet: None,
+                id_len: 0,
+                remaining: data.len(),
+                fields: vec![],
+                error: Some(anyhow!("解码数据包时 panic: {}", message)),
+                opaque: None,
+            }
+        })
+    }
+
+    /// 解码成功时返回数据包
+    ///
+    /// 停止解析的数据包只有部分字段,返回 None,校验和拦截只能使用原始字节
+    pub fn into_result(self) -> Result<Option<Box<dyn Packet>>> {
+        match (self.packet, self.error) {
+            (_, Some(error)) => Err(error),
+            (Some(_), None) if self.opaque.is_some() => Ok(None),
+            (Some(packet), None) => Ok(Some(packet)),
+            (None, None) => Err(anyhow!("数据包未解析")),
         }
-        packets::status::PacketState::Status => packets::client::status::pool::id_to_packet(id),
-        packets::status::PacketState::Login => packets::client::login::pool::id_to_packet(id),
-    };
-    decoded.deserialize(&mut reader);
-    Ok(decoded)
+    }
 }
 
-fn read_packet_server(
+/// 解析数据包,解码失败时返回错误
+fn read_packet(
+    direction: Direction,
     data: Bytes,
     status: packets::status::PacketState,
     protocol_version: i32,
 ) -> Result<Box<dyn Packet>> {
-    let mut buf = BytesMut::new();
-    buf.extend_from_slice(&data);
-    let mut reader = PacketReader::new(Box::new(&mut buf));
-    let id = reader.varint().0 as u32;
-    
-    let mut decoded = match status {
-        packets::status::PacketState::Handshaking => Box::new(nullpacket::NullPacket::new()),
-        packets::status::PacketState::Status => packets::server::status::pool::id_to_packet(id),
-        packets::status::PacketState::Login => {
-            packets::server::login::pool::id_to_packet(id, protocol_version)
-        }
-    };
-    decoded.deserialize(&mut reader);
-    Ok(decoded)
-}
\ No newline at end of file
+    DecodedFrame::decode(direction, status, protocol_version, &data, false)
+        .into_result()?
+        .ok_or_else(|| anyhow!("数据包无法完整解析"))
+}
@@ -1,5 +1,10 @@
 /// 空数据包,处理报错的
 #[derive(Debug, Default, PartialEq,Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(qexed_tcp_connect::serde::Serialize, qexed_tcp_connect::serde::Deserialize),
+    serde(crate = "qexed_tcp_connect::serde")
+)]
 pub struct NullPacket {}
 impl NullPacket {
     pub fn new() -> Self {
@@ -10,9 +15,25 @@ impl qexed_tcp_connect::net_types::packet::Packet for NullPacket {
     fn id(&self) -> u32 {
         0xfff
     }
+    fn name(&self) -> &'static str {
+        "NullPacket"
+    }
     fn serialize(&self, _w: &mut qexed_tcp_connect::packet::encode::PacketWriter) {}
     fn deserialize(&mut self, _r: &mut qexed_tcp_connect::packet::decode::PacketReader) {}
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+    fn into_any(self: Box<Self>) -> Box<dyn std::any::Any + Send + Sync> {
+        self
+    }
+    fn box_clone(&self) -> Box<dyn qexed_tcp_connect::net_types::packet::Packet> {
+        Box::new(self.clone())
+    }
+    fn dyn_eq(&self, other: &dyn qexed_tcp_connect::net_types::packet::Packet) -> bool {
+        other.as_any().is::<NullPacket>()
+    }
+    #[cfg(feature = "serde")]
+    fn to_json(&self) -> Option<qexed_tcp_connect::serde_json::Value> {
+        qexed_tcp_connect::serde_json::to_value(self).ok()
+    }
 }
@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0xfe)]
+#[qexed_packet_macros::packet(id = 0xfe, state = handshaking, direction = serverbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct LegacyServerListPing {
     pub payload:u8,
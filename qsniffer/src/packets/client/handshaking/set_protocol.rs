@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x00)]
+#[qexed_packet_macros::packet(id = 0x00, state = handshaking, direction = serverbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct SetProtocol {
     pub protocol_version:qexed_tcp_connect::net_types::var_int::VarInt,
@@ -1,3 +1,3 @@
-#[qexed_packet_macros::packet(id = 0x04)]
+#[qexed_packet_macros::packet(id = 0x04, state = login, direction = serverbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct CookieResponse {}
@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x01)]
+#[qexed_packet_macros::packet(id = 0x01, state = login, direction = serverbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct EncryptionBegin {
     pub shared_secret:Vec<u8>,
@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x03)]
+#[qexed_packet_macros::packet(id = 0x03, state = login, direction = serverbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct LoginAcknowledged {
 }
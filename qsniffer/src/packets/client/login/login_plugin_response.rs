@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x02)]
+#[qexed_packet_macros::packet(id = 0x02, state = login, direction = serverbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct LoginPluginResponse {
     pub message_id:qexed_tcp_connect::net_types::var_int::VarInt,
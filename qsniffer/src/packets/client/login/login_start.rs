@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x00)]
+#[qexed_packet_macros::packet(id = 0x00, state = login, direction = serverbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct LoginStart {
     pub username:String,
@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x01)]
+#[qexed_packet_macros::packet(id = 0x01, state = status, direction = serverbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct Ping {
     pub time:i64,
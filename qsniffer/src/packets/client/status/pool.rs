@@ -1,15 +1,11 @@
 use crate::nullpacket::NullPacket;
-use crate::packets::client::status::ping_start::PingStart;
-use crate::packets::client::status::ping::Ping;
-pub fn id_to_packet(id: u32) -> Box<dyn qexed_tcp_connect::net_types::packet::Packet,> {
-    // protocol_version 还没做专门的适配
-    match id {
-        0x00 =>{Box::new(PingStart::default())},
-        0x01 =>{Box::new(Ping::default())},
-        _ => {
-            // 使用传入的NullPacket类型
-            log::warn!("Unknown packet ID: 0x{:X}, returning {} instance", id, stringify!(#null_packet));
-            Box::new(NullPacket::new())
-        },
-    }
-}
\ No newline at end of file
+use crate::packets::client::status::{ping, ping_start};
+
+qexed_packet_macros::id_to_packet!(
+    [
+        ping_start::PingStart,
+        ping::Ping,
+    ],
+    NullPacket,
+    enum ClientStatusPacket
+);
@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x03)]
+#[qexed_packet_macros::packet(id = 0x03, state = login, direction = clientbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct Compress {
     pub threshold:qexed_tcp_connect::net_types::var_int::VarInt,
@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x05)]
+#[qexed_packet_macros::packet(id = 0x05, state = login, direction = clientbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct CookieRequest {
 }
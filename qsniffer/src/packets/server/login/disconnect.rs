@@ -1,5 +1,5 @@
-#[qexed_packet_macros::packet(id = 0x00)]
+#[qexed_packet_macros::packet(id = 0x00, state = login, direction = clientbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct Disconnect {
-    pub reason:serde_json::Value,
+    pub reason:qexed_tcp_connect::net_types::text_component::TextComponent,
 }
\ No newline at end of file
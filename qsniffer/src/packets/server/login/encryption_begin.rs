@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x01)]
+#[qexed_packet_macros::packet(id = 0x01, state = login, direction = clientbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct EncryptionBegin {
     pub server_id:String,
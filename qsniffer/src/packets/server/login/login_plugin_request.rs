@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x04)]
+#[qexed_packet_macros::packet(id = 0x04, state = login, direction = clientbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct LoginPluginRequest {
     pub message_id:qexed_tcp_connect::net_types::var_int::VarInt,
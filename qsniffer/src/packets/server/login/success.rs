@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x02)]
+#[qexed_packet_macros::packet(id = 0x02, state = login, direction = clientbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct Success {
     pub uuid:uuid::Uuid,
@@ -1,4 +1,4 @@
-#[qexed_packet_macros::packet(id = 0x00)]
+#[qexed_packet_macros::packet(id = 0x00, state = status, direction = clientbound)]
 #[derive(Debug, Default, PartialEq, Clone)]
 pub struct ServerInfo {
     pub response:serde_json::Value,
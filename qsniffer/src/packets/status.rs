@@ -17,12 +17,18 @@ impl From<&str> for PacketState {
         }
     }
 }
-impl Display for PacketState {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl PacketState {
+    /// 状态名称,与 `Display` 相同
+    pub fn as_str(&self) -> &'static str {
         match self {
-            PacketState::Handshaking => write!(f, "handshaking"),
-            PacketState::Status => write!(f, "status"),
-            PacketState::Login => write!(f, "login"),
+            PacketState::Handshaking => "handshaking",
+            PacketState::Status => "status",
+            PacketState::Login => "login",
         }
     }
 }
+impl Display for PacketState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
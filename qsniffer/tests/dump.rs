@@ -0,0 +1,99 @@
+//! 数据包转储: 字段范围和十六进制输出
+
+use bytes::Bytes;
+use qsniffer::{
+    DecodedFrame,
+    dump::{FieldRange, PacketDump, field_ranges},
+    interceptor::Direction,
+    packets::status::PacketState,
+};
+
+/// Success: 包ID + UUID + 用户名 "ab" + 一个带签名的属性
+fn success_frame() -> Bytes {
+    let mut frame = vec![0x02];
+    frame.extend([0x11; 16]);
+    frame.extend([0x02, b'a', b'b']);
+    frame.push(0x01);
+    frame.extend([0x01, b'n', 0x01, b'v', 0x01, 0x01, b's']);
+    Bytes::from(frame)
+}
+
+fn decode(data: &Bytes) -> DecodedFrame {
+    DecodedFrame::decode(Direction::ServerToClient, PacketState::Login, 767, data, true)
+}
+
+fn range(name: &'static str, start: usize, end: usize, depth: usize) -> FieldRange {
+    FieldRange { name, start, end, depth }
+}
+
+#[test]
+fn nested_field_ranges() {
+    let data = success_frame();
+    let decoded = decode(&data);
+    assert!(decoded.error.is_none());
+    assert_eq!(decoded.id_len, 1);
+    assert_eq!(
+        field_ranges(data.len(), &decoded.fields),
+        [
+            range("uuid", 1, 17, 0),
+            range("username", 17, 20, 0),
+            range("properties", 20, 28, 0),
+            range("name", 21, 23, 1),
+            range("value", 23, 25, 1),
+            range("signature", 25, 28, 1),
+        ]
+    );
+}
+
+#[test]
+fn truncated_field_ranges() {
+    // 属性在签名处截断,已读取的字段仍有范围
+    let data = success_frame().slice(..26);
+    let decoded = decode(&data);
+    assert!(decoded.error.is_some());
+    let ranges = field_ranges(data.len(), &decoded.fields);
+    assert_eq!(ranges.last(), Some(&range("signature", 25, 26, 1)));
+}
+
+#[test]
+fn hexdump() {
+    let dump = PacketDump::new();
+    let data = [0x00, 0x41, 0x42, 0x0a, 0x20, 0xff, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x7e];
+    let fields = [range("id", 0, 1, 0), range("text", 1, 5, 0), range("inner", 2, 4, 1)];
+    assert_eq!(
+        dump.hexdump(&data, &fields),
+        "0000  00 41 42 0a 20 ff 10 11 12 13 14 15 16 17 18 19  |.AB. ...........|\n\
+         0010  7e                                               |~|\n\
+         \x20 0x0000..0x0001 id\n\
+         \x20 0x0001..0x0005 text\n\
+         \x20   0x0002..0x0004 inner\n"
+    );
+
+    // 着色时取最内层的字段
+    let colored = PacketDump { color: true, ..PacketDump::new() }.hexdump(&data, &fields);
+    assert!(colored.starts_with("0000  \x1b[31m00\x1b[0m \x1b[32m41\x1b[0m \x1b[33m42\x1b[0m \x1b[33m0a\x1b[0m \x1b[32m20\x1b[0m ff "));
+}
+
+#[test]
+fn format_reuses_decoded_packet() {
+    let data = success_frame();
+    let decoded = decode(&data);
+    let dump = PacketDump { hexdump: true, ..PacketDump::new() };
+    let text = dump.format(Direction::ServerToClient, Some(PacketState::Login), &data, Some(&decoded));
+    let mut lines = text.lines();
+    assert_eq!(lines.next(), Some("S->C [login] 0x02 Success (28 bytes)"));
+    assert!(text.contains("  0x0000..0x0001 packet_id\n"));
+    assert!(text.contains("    0x0019..0x001c signature\n"));
+
+    // 不跟踪状态时只输出原始数据
+    let text = dump.format(Direction::ServerToClient, None, &data, None);
+    assert!(text.starts_with("S->C [untracked] 0x02 ? (28 bytes)\n0000  02 11"));
+}
+
+#[test]
+fn format_reports_decode_error() {
+    let data = success_frame().slice(..26);
+    let decoded = decode(&data);
+    let text = PacketDump::new().format(Direction::ServerToClient, Some(PacketState::Login), &data, Some(&decoded));
+    assert!(text.contains("\n解码失败: Unexpected end of data: need 1 bytes in field `signature` (0 bytes left)"), "{}", text);
+}
@@ -0,0 +1,548 @@
+//! 通过真实的 TCP 连接测试代理: 拦截、注入、异步检查和压缩切换
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::{Bytes, BytesMut};
+use qexed_tcp_connect::{
+    PacketListener, PacketRead, PacketSend,
+    net_types::{array::ArrayU8, packet::Packet, text_component::TextComponent, var_int::VarInt},
+    packet::{decode::PacketReader, encode::PacketWriter},
+};
+use qsniffer::failure::{FailurePolicy, FailureReport, Registration};
+use qsniffer::interceptor::{InterceptContext, PacketInspector, Verdict};
+use qsniffer::packets::client::{
+    handshaking::set_protocol::SetProtocol,
+    login::{login_acknowledged::LoginAcknowledged, login_start::LoginStart},
+    status::ping_start::PingStart,
+};
+use qsniffer::packets::server::{
+    login::{compress::Compress, disconnect::Disconnect, success::Success},
+    status::server_info::ServerInfo,
+};
+use qsniffer::{ProxyConfig, run_proxy_with_listener};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+const PROTOCOL_VERSION: i32 = 767;
+
+/// 一次经过代理的会话: 客户端和目标服务端两端
+struct Session {
+    client_read: PacketRead,
+    client_send: PacketSend,
+    server_read: PacketRead,
+    server_send: PacketSend,
+}
+
+fn split(stream: TcpStream) -> (PacketRead, PacketSend) {
+    let (read, write) = tokio::io::split(stream);
+    PacketListener::new(read, write, 256).split()
+}
+
+async fn start(config: ProxyConfig) -> Session {
+    let server = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = server.local_addr().unwrap().to_string();
+    let proxy = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy_addr = proxy.local_addr().unwrap();
+    tokio::spawn(async move { run_proxy_with_listener(proxy, &server_addr, config).await });
+
+    let client = TcpStream::connect(proxy_addr).await.unwrap();
+    let (server, _) = server.accept().await.unwrap();
+    let (client_read, client_send) = split(client);
+    let (server_read, server_send) = split(server);
+    Session {
+        client_read,
+        client_send,
+        server_read,
+        server_send,
+    }
+}
+
+/// 握手后进入 `next_state` (1: 状态查询, 2: 登录)
+async fn handshake(session: &mut Session, next_state: i32) {
+    let handshake = SetProtocol {
+        protocol_version: VarInt(PROTOCOL_VERSION),
+        server_host: "localhost".to_string(),
+        server_port: 25565,
+        next_state: VarInt(next_state),
+    };
+    session.client_send.send(&handshake).await.unwrap();
+    assert_eq!(decode::<SetProtocol>(read(&mut session.server_read).await), handshake);
+}
+
+async fn read(read: &mut PacketRead) -> Bytes {
+    tokio::time::timeout(Duration::from_secs(5), read.read_frame())
+        .await
+        .expect("timed out waiting for a frame")
+        .unwrap()
+}
+
+fn encode(packet: &dyn Packet) -> Bytes {
+    let mut buf = BytesMut::new();
+    let mut w = PacketWriter::new(&mut buf);
+    w.varint(&VarInt(packet.id() as i32));
+    packet.serialize(&mut w);
+    buf.freeze()
+}
+
+fn decode<T: Packet + Default>(frame: Bytes) -> T {
+    let mut buf = frame;
+    let mut r = PacketReader::new(Box::new(&mut buf));
+    let id = r.varint().0 as u32;
+    let mut packet = T::default();
+    assert_eq!(id, packet.id(), "unexpected packet {:#04x}", id);
+    packet.deserialize(&mut r);
+    r.finish().unwrap();
+    packet
+}
+
+fn login_start(username: &str) -> LoginStart {
+    LoginStart {
+        username: username.to_string(),
+        player_uuid: uuid::Uuid::nil(),
+    }
+}
+
+#[tokio::test]
+async fn interceptor_replaces_drops_and_injects() {
+    let interceptor = Arc::new(|ctx: &mut InterceptContext| {
+        if let Some(start) = ctx.packet_as::<LoginStart>() {
+            let mut start = start.clone();
+            start.username = "renamed".to_string();
+            // 注入的数据包在当前数据包之后按顺序发送
+            ctx.inject_to_client(Bytes::from_static(&[0x7f, 0x01]));
+            ctx.inject_to_client(Bytes::from_static(&[0x7f, 0x02]));
+            ctx.inject_to_server(Bytes::from_static(&[0x7e]));
+            return Ok(Verdict::ReplacePacket(Box::new(start)));
+        }
+        if ctx.packet_as::<LoginAcknowledged>().is_some() {
+            return Ok(Verdict::Drop);
+        }
+        if ctx.data[..] == [0x10, 0x00] {
+            return Ok(Verdict::Replace(Bytes::from_static(&[0x10, 0x01])));
+        }
+        Ok(Verdict::Forward)
+    });
+    let mut session = start(ProxyConfig {
+        client_interceptor: Some(Registration::new(interceptor)),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+
+    session.client_send.send(&login_start("player")).await.unwrap();
+    assert_eq!(decode::<LoginStart>(read(&mut session.server_read).await), login_start("renamed"));
+    assert_eq!(read(&mut session.server_read).await[..], [0x7e]);
+    assert_eq!(read(&mut session.client_read).await[..], [0x7f, 0x01]);
+    assert_eq!(read(&mut session.client_read).await[..], [0x7f, 0x02]);
+
+    // 被丢弃的登录确认不会到达服务端,之后的原始帧按拦截结果替换
+    session.client_send.send(&LoginAcknowledged {}).await.unwrap();
+    session.client_send.send_raw(Bytes::from_static(&[0x10, 0x00])).await.unwrap();
+    assert_eq!(read(&mut session.server_read).await[..], [0x10, 0x01]);
+}
+
+#[tokio::test]
+async fn unchanged_replacement_forwards_original_bytes() {
+    let interceptor = Arc::new(|ctx: &mut InterceptContext| {
+        match ctx.packet_as::<ServerInfo>() {
+            Some(info) => Ok(Verdict::ReplacePacket(Box::new(info.clone()))),
+            None => Ok(Verdict::Forward),
+        }
+    });
+    let mut session = start(ProxyConfig {
+        server_interceptor: Some(Registration::new(interceptor)),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 1).await;
+    session.client_send.send(&PingStart {}).await.unwrap();
+    read(&mut session.server_read).await;
+
+    // 状态查询阶段的服务端响应同样会被解析;内容不变时不重新编码,保留原有的空白
+    let info = ServerInfo {
+        response: serde_json::json!({ "description": "motd" }),
+    };
+    let mut frame = BytesMut::from(&[0x00][..]);
+    let json = br#"{ "description" : "motd" }"#;
+    frame.extend_from_slice(&[json.len() as u8]);
+    frame.extend_from_slice(json);
+    let frame = frame.freeze();
+    assert_eq!(decode::<ServerInfo>(frame.clone()), info);
+    session.server_send.send_raw(frame.clone()).await.unwrap();
+    assert_eq!(read(&mut session.client_read).await, frame);
+}
+
+/// 服务端发出设置压缩后,登录成功数据包已经压缩
+async fn finish_login(session: &mut Session, threshold: i32) {
+    session.server_send.set_compression_value(threshold as usize);
+    session.server_send.set_compression(true);
+    let success = Success {
+        uuid: uuid::Uuid::nil(),
+        username: "player".repeat(20),
+        properties: vec![],
+    };
+    session.server_send.send(&success).await.unwrap();
+    assert_eq!(decode::<Success>(read(&mut session.client_read).await), success);
+
+    session.client_send.send(&LoginAcknowledged {}).await.unwrap();
+    assert_eq!(decode::<LoginAcknowledged>(read(&mut session.server_read).await), LoginAcknowledged {});
+}
+
+#[tokio::test]
+async fn dropped_compress_leaves_client_uncompressed() {
+    let interceptor = Arc::new(|ctx: &mut InterceptContext| {
+        match ctx.packet_as::<Compress>() {
+            Some(_) => Ok(Verdict::Drop),
+            None => Ok(Verdict::Forward),
+        }
+    });
+    let mut session = start(ProxyConfig {
+        server_interceptor: Some(Registration::new(interceptor)),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+    session.server_send.send(&Compress { threshold: VarInt(64) }).await.unwrap();
+
+    // 与服务端之间压缩,与客户端之间保持未压缩
+    finish_login(&mut session, 64).await;
+}
+
+#[tokio::test]
+async fn replaced_compress_uses_forwarded_threshold() {
+    let interceptor = Arc::new(|ctx: &mut InterceptContext| {
+        match ctx.packet_as::<Compress>() {
+            Some(_) => Ok(Verdict::ReplacePacket(Box::new(Compress { threshold: VarInt(1024) }))),
+            None => Ok(Verdict::Forward),
+        }
+    });
+    let mut session = start(ProxyConfig {
+        server_interceptor: Some(Registration::new(interceptor)),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+    session.server_send.send(&Compress { threshold: VarInt(64) }).await.unwrap();
+
+    let forwarded = read(&mut session.client_read).await;
+    assert_eq!(forwarded, encode(&Compress { threshold: VarInt(1024) }));
+    session.client_send.set_compression_value(1024);
+    session.client_send.set_compression(true);
+    finish_login(&mut session, 64).await;
+}
+
+/// 记录看到的用户名,按配置延迟后转发
+struct RecordingInspector {
+    seen: mpsc::UnboundedSender<String>,
+    delay: Duration,
+}
+
+#[async_trait::async_trait]
+impl PacketInspector for RecordingInspector {
+    async fn inspect(&self, ctx: &mut InterceptContext<'_>) -> anyhow::Result<Verdict> {
+        if let Some(start) = ctx.packet_as::<LoginStart>() {
+            assert_eq!(ctx.data, &encode(start));
+            self.seen.send(start.username.clone()).unwrap();
+            tokio::time::sleep(self.delay).await;
+        }
+        Ok(Verdict::Forward)
+    }
+}
+
+fn recording_inspector(delay: Duration) -> (Arc<RecordingInspector>, mpsc::UnboundedReceiver<String>) {
+    let (seen, receiver) = mpsc::unbounded_channel();
+    (Arc::new(RecordingInspector { seen, delay }), receiver)
+}
+
+/// 会话结束时把失败汇总发到通道
+fn report_channel() -> (Arc<qsniffer::failure::SessionEndHandler>, mpsc::UnboundedReceiver<FailureReport>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    (Arc::new(move |report: &FailureReport| sender.send(report.clone()).unwrap()), receiver)
+}
+
+async fn close(session: Session, mut reports: mpsc::UnboundedReceiver<FailureReport>) -> FailureReport {
+    drop(session);
+    tokio::time::timeout(Duration::from_secs(5), reports.recv()).await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn inspector_sees_interceptor_result() {
+    let interceptor = Arc::new(|ctx: &mut InterceptContext| {
+        match ctx.packet_as::<LoginStart>() {
+            Some(start) if start.username == "dropped" => Ok(Verdict::Drop),
+            Some(start) => {
+                let mut start = start.clone();
+                start.username = format!("{}-renamed", start.username);
+                Ok(Verdict::ReplacePacket(Box::new(start)))
+            }
+            None => Ok(Verdict::Forward),
+        }
+    });
+    let (inspector, mut seen) = recording_inspector(Duration::ZERO);
+    let mut session = start(ProxyConfig {
+        client_interceptor: Some(Registration::new(interceptor)),
+        client_inspector: Some(Registration::new(inspector)),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+
+    // 拦截器丢弃的数据包不会交给检查器
+    session.client_send.send(&login_start("dropped")).await.unwrap();
+    session.client_send.send(&login_start("player")).await.unwrap();
+    assert_eq!(decode::<LoginStart>(read(&mut session.server_read).await), login_start("player-renamed"));
+    assert_eq!(seen.recv().await.unwrap(), "player-renamed");
+    assert!(seen.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn failed_interceptor_still_inspected() {
+    let interceptor = Arc::new(|ctx: &mut InterceptContext| {
+        if ctx.packet_as::<LoginStart>().is_some() {
+            ctx.inject_to_server(Bytes::from_static(&[0x7e]));
+            anyhow::bail!("interceptor failed");
+        }
+        Ok(Verdict::Forward)
+    });
+    let (inspector, mut seen) = recording_inspector(Duration::ZERO);
+    let (on_session_end, reports) = report_channel();
+    let mut session = start(ProxyConfig {
+        client_interceptor: Some(Registration::with_policy(interceptor, FailurePolicy::LogAndForward)),
+        client_inspector: Some(Registration::new(inspector)),
+        on_session_end: Some(on_session_end),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+
+    // 失败的拦截器注入的数据包被丢弃,检查器看到原数据包
+    session.client_send.send(&login_start("player")).await.unwrap();
+    assert_eq!(decode::<LoginStart>(read(&mut session.server_read).await), login_start("player"));
+    assert_eq!(seen.recv().await.unwrap(), "player");
+
+    let report = close(session, reports).await;
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].error, "interceptor failed");
+}
+
+/// 长度超过前缀上限,编码时 panic
+#[qexed_packet_macros::packet(id = 0x00)]
+#[derive(Debug, Default, PartialEq, Clone)]
+struct Oversized {
+    items: ArrayU8<u8>,
+}
+
+fn oversized() -> Oversized {
+    Oversized {
+        items: ArrayU8::from_vec(vec![0; 300]),
+    }
+}
+
+#[tokio::test]
+async fn encode_panic_follows_failure_policy() {
+    let interceptor = Arc::new(|ctx: &mut InterceptContext| {
+        match ctx.packet_as::<LoginStart>() {
+            Some(start) if start.username == "replace" => Ok(Verdict::ReplacePacket(Box::new(oversized()))),
+            Some(start) if start.username == "inject" => {
+                ctx.inject_to_client(Box::new(oversized()) as Box<dyn Packet>);
+                Ok(Verdict::Forward)
+            }
+            _ => Ok(Verdict::Forward),
+        }
+    });
+    let (on_session_end, reports) = report_channel();
+    let mut session = start(ProxyConfig {
+        client_interceptor: Some(Registration::with_policy(interceptor, FailurePolicy::LogAndForward)),
+        on_session_end: Some(on_session_end),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+
+    // 编码失败的环节不生效,原数据包照常转发,任务继续运行
+    session.client_send.send(&login_start("replace")).await.unwrap();
+    assert_eq!(decode::<LoginStart>(read(&mut session.server_read).await), login_start("replace"));
+    session.client_send.send(&login_start("inject")).await.unwrap();
+    assert_eq!(decode::<LoginStart>(read(&mut session.server_read).await), login_start("inject"));
+    session.client_send.send(&login_start("after")).await.unwrap();
+    assert_eq!(decode::<LoginStart>(read(&mut session.server_read).await), login_start("after"));
+
+    let report = close(session, reports).await;
+    assert_eq!(report.failures.len(), 2);
+    for failure in &report.failures {
+        assert_eq!(failure.error, "编码数据包 Oversized 时 panic: Array length 300 exceeds limit 255");
+    }
+}
+
+#[tokio::test]
+async fn inspector_timeout() {
+    let (inspector, mut seen) = recording_inspector(Duration::from_secs(60));
+    let (on_session_end, reports) = report_channel();
+    let mut session = start(ProxyConfig {
+        client_inspector: Some(Registration::with_policy(inspector, FailurePolicy::DropPacket)),
+        inspector_timeout: Some(Duration::from_millis(50)),
+        on_session_end: Some(on_session_end),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+
+    // 超时的数据包按策略丢弃,之后的数据包照常转发
+    session.client_send.send(&login_start("player")).await.unwrap();
+    session.client_send.send(&LoginAcknowledged {}).await.unwrap();
+    assert_eq!(decode::<LoginAcknowledged>(read(&mut session.server_read).await), LoginAcknowledged {});
+    assert_eq!(seen.recv().await.unwrap(), "player");
+
+    let report = close(session, reports).await;
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].error, "数据包检查超时: 50ms");
+    assert_eq!(report.failures[0].packet_id, Some(0x00));
+}
+
+/// 用户名为 `bad` 的登录开始数据包校验失败
+fn reject_bad_username(policy: FailurePolicy) -> Option<Registration<qsniffer::PacketValidator>> {
+    let validator = Arc::new(|data: &Bytes, _version| {
+        if data[..] == encode(&login_start("bad"))[..] {
+            anyhow::bail!("bad username");
+        }
+        Ok(())
+    });
+    Some(Registration::with_policy(validator, policy))
+}
+
+/// 连接已被代理关闭
+async fn closed(read: &mut PacketRead) -> bool {
+    tokio::time::timeout(Duration::from_secs(5), read.read_frame()).await.unwrap().is_err()
+}
+
+#[tokio::test]
+async fn log_and_forward_policy() {
+    let (on_session_end, reports) = report_channel();
+    let mut session = start(ProxyConfig {
+        client_validator: reject_bad_username(FailurePolicy::LogAndForward),
+        on_session_end: Some(on_session_end),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+    session.client_send.send(&login_start("bad")).await.unwrap();
+    assert_eq!(decode::<LoginStart>(read(&mut session.server_read).await), login_start("bad"));
+
+    let report = close(session, reports).await;
+    assert_eq!(report.protocol_version, PROTOCOL_VERSION);
+    assert_eq!(report.failures.len(), 1);
+    let length = encode(&login_start("bad")).len();
+    assert_eq!(report.failures[0].to_string(), format!("[C->S] state=login id=0x00 len={}: bad username", length));
+}
+
+#[tokio::test]
+async fn drop_packet_policy() {
+    let mut session = start(ProxyConfig {
+        client_validator: reject_bad_username(FailurePolicy::DropPacket),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+    session.client_send.send(&login_start("bad")).await.unwrap();
+    session.client_send.send(&login_start("good")).await.unwrap();
+    assert_eq!(decode::<LoginStart>(read(&mut session.server_read).await), login_start("good"));
+}
+
+#[tokio::test]
+async fn login_disconnect_policy() {
+    let mut session = start(ProxyConfig {
+        client_validator: reject_bad_username(FailurePolicy::LoginDisconnect),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+    session.client_send.send(&login_start("bad")).await.unwrap();
+
+    // 登录阶段先收到带原因的断开连接数据包
+    let disconnect = decode::<Disconnect>(read(&mut session.client_read).await);
+    assert_eq!(disconnect.reason, TextComponent::text("Qsniffer 数据包校验失败: bad username").color("red"));
+    assert!(closed(&mut session.client_read).await);
+    assert!(closed(&mut session.server_read).await);
+}
+
+#[tokio::test]
+async fn login_disconnect_policy_outside_login() {
+    let validator = Arc::new(|data: &Bytes, _version| {
+        if data[..] == encode(&PingStart {})[..] {
+            anyhow::bail!("status request rejected");
+        }
+        Ok(())
+    });
+    let mut session = start(ProxyConfig {
+        client_validator: Some(Registration::with_policy(validator, FailurePolicy::LoginDisconnect)),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 1).await;
+    session.client_send.send(&PingStart {}).await.unwrap();
+
+    // 状态查询阶段没有断开连接数据包,直接关闭
+    assert!(closed(&mut session.client_read).await);
+    assert!(closed(&mut session.server_read).await);
+}
+
+#[tokio::test]
+async fn abort_policy() {
+    let mut session = start(ProxyConfig {
+        client_validator: reject_bad_username(FailurePolicy::Abort),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+    session.client_send.send(&login_start("bad")).await.unwrap();
+    assert!(closed(&mut session.client_read).await);
+    assert!(closed(&mut session.server_read).await);
+}
+
+#[tokio::test]
+async fn policies_are_per_registration() {
+    // 同一方向上校验函数丢弃数据包,拦截器失败时关闭连接
+    let interceptor = Arc::new(|ctx: &mut InterceptContext| {
+        match ctx.packet_as::<LoginStart>() {
+            Some(start) if start.username == "fatal" => anyhow::bail!("fatal username"),
+            _ => Ok(Verdict::Forward),
+        }
+    });
+    let mut session = start(ProxyConfig {
+        client_validator: reject_bad_username(FailurePolicy::DropPacket),
+        client_interceptor: Some(Registration::with_policy(interceptor, FailurePolicy::Abort)),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+    session.client_send.send(&login_start("bad")).await.unwrap();
+    session.client_send.send(&login_start("fatal")).await.unwrap();
+    assert!(closed(&mut session.server_read).await);
+}
+
+#[tokio::test]
+async fn decode_failure_policy() {
+    // 字符串声明 5 个字节,实际只有 1 个
+    let truncated = Bytes::from_static(&[0x00, 0x05, b'a']);
+    let (on_session_end, reports) = report_channel();
+    let mut session = start(ProxyConfig {
+        decode_failure_policy: FailurePolicy::LogAndForward,
+        on_session_end: Some(on_session_end),
+        ..Default::default()
+    })
+    .await;
+    handshake(&mut session, 2).await;
+    session.client_send.send_raw(truncated.clone()).await.unwrap();
+    assert_eq!(read(&mut session.server_read).await, truncated);
+
+    let report = close(session, reports).await;
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].error, "Length 5 exceeds remaining data in field `username` (1 bytes left)");
+
+    // 默认策略关闭连接
+    let mut session = start(ProxyConfig::default()).await;
+    handshake(&mut session, 2).await;
+    session.client_send.send_raw(truncated).await.unwrap();
+    assert!(closed(&mut session.server_read).await);
+}
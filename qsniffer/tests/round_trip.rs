@@ -0,0 +1,22 @@
+//! 每个包池中的数据包序列化再反序列化后保持不变
+//!
+//! 运行: `cargo test -p qsniffer --features arbitrary`
+#![cfg(feature = "arbitrary")]
+
+use qexed_tcp_connect::packet::round_trip::assert_round_trips;
+use qsniffer::packets::{client, server};
+
+const CASES: usize = 256;
+
+#[test]
+fn client_packets() {
+    assert_round_trips(client::handshaking::pool::ARBITRARY_PACKETS, client::handshaking::pool::id_to_packet, CASES);
+    assert_round_trips(client::status::pool::ARBITRARY_PACKETS, client::status::pool::id_to_packet, CASES);
+    assert_round_trips(client::login::pool::ARBITRARY_PACKETS, client::login::pool::id_to_packet, CASES);
+}
+
+#[test]
+fn server_packets() {
+    assert_round_trips(server::status::pool::ARBITRARY_PACKETS, server::status::pool::id_to_packet, CASES);
+    assert_round_trips(server::login::pool::ARBITRARY_PACKETS, server::login::pool::id_to_packet, CASES);
+}